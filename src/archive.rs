@@ -0,0 +1,546 @@
+use crate::config::CONFIG;
+use crate::file_browser::FileEntry;
+use crate::handlers::utils::is_excluded_entry;
+use crate::jobs::{JobHandle, ProgressReader};
+use crate::visibility::Access;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hyper::body::Bytes;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+// Stop reading the member table of absurdly large archives
+const MAX_LISTED_MEMBERS: usize = 100_000;
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+// Limits for server-side extraction and compression, so a crafted archive or
+// an over-eager selection cannot fill the disk. The byte limit is
+// `limits.max_archive_size` in the configuration.
+pub const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+pub struct ArchiveMember {
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+pub fn archive_kind(filename: &str) -> Option<ArchiveKind> {
+    let lower = filename.to_lowercase();
+    if lower.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if lower.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+// Splits a path that points into an archive into the archive file and the
+// member path inside it, e.g. `~/src.zip/lib/util.rs` -> (`~/src.zip`, "lib/util.rs")
+pub fn split_archive_path(path: &Path) -> Option<(PathBuf, String)> {
+    for ancestor in path.ancestors() {
+        if ancestor.is_dir() {
+            return None;
+        }
+        if ancestor.is_file() {
+            archive_kind(ancestor.file_name()?.to_str()?)?;
+            let inner = path.strip_prefix(ancestor).ok()?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            return Some((ancestor.to_path_buf(), inner));
+        }
+    }
+    None
+}
+
+// Normalizes a member name to `a/b/c`, dropping anything that could escape
+// the archive root when the listing is later mapped onto URLs
+pub fn normalize_member_path(name: &str) -> Option<String> {
+    let mut parts = Vec::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            _ => parts.push(part),
+        }
+    }
+    if parts.is_empty() || parts[0].contains(':') {
+        return None;
+    }
+    Some(parts.join("/"))
+}
+
+pub fn read_members(archive: &Path) -> io::Result<Vec<ArchiveMember>> {
+    let kind = archive_name_kind(archive)?;
+    let mut members = Vec::new();
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = zip::ZipArchive::new(BufReader::new(File::open(archive)?))
+                .map_err(io::Error::other)?;
+            for index in 0..zip.len().min(MAX_LISTED_MEMBERS) {
+                let entry = zip.by_index_raw(index).map_err(io::Error::other)?;
+                if let Some(path) = normalize_member_path(entry.name()) {
+                    members.push(ArchiveMember { path, is_dir: entry.is_dir(), size: entry.size() });
+                }
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let mut tar = tar::Archive::new(open_tar_stream(archive, kind)?);
+            for entry in tar.entries()?.take(MAX_LISTED_MEMBERS) {
+                let entry = entry?;
+                let entry_type = entry.header().entry_type();
+                if !entry_type.is_file() && !entry_type.is_dir() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().to_string();
+                if let Some(path) = normalize_member_path(&name) {
+                    members.push(ArchiveMember { path, is_dir: entry_type.is_dir(), size: entry.size() });
+                }
+            }
+        }
+    }
+
+    Ok(members)
+}
+
+// Lists the immediate children of `inner` as if the archive were a directory.
+// Returns `None` when `inner` is not a folder inside the archive.
+pub fn list_archive_directory(archive: &Path, inner: &str) -> io::Result<Option<Vec<FileEntry>>> {
+    let members = read_members(archive)?;
+    let prefix = if inner.is_empty() { String::new() } else { format!("{}/", inner) };
+    let mut found = inner.is_empty();
+    let mut children: BTreeMap<String, (bool, u64)> = BTreeMap::new();
+
+    for member in &members {
+        if member.is_dir && member.path == inner {
+            found = true;
+            continue;
+        }
+        let Some(remainder) = member.path.strip_prefix(&prefix) else {
+            continue;
+        };
+        found = true;
+
+        // Intermediate folders are often not stored explicitly
+        match remainder.split_once('/') {
+            Some((child, _)) => {
+                children.insert(child.to_string(), (true, 0));
+            }
+            None => {
+                children.entry(remainder.to_string()).or_insert((member.is_dir, member.size));
+            }
+        }
+    }
+
+    if !found {
+        return Ok(None);
+    }
+
+    let mut entries: Vec<FileEntry> = children.into_iter()
+        .map(|(name, (is_dir, size))| {
+            let path = archive.join(&prefix).join(&name);
+            FileEntry::virtual_entry(name, path, is_dir, if is_dir { None } else { Some(size) })
+        })
+        .collect();
+
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(Some(entries))
+}
+
+pub fn find_member(archive: &Path, member: &str) -> io::Result<Option<ArchiveMember>> {
+    Ok(read_members(archive)?
+        .into_iter()
+        .find(|m| !m.is_dir && m.path == member))
+}
+
+// Streams a single member out of the archive from a blocking task, without
+// extracting anything to disk
+pub fn stream_member(archive: PathBuf, member: String) -> mpsc::Receiver<io::Result<Bytes>> {
+    let (sender, receiver) = mpsc::channel(8);
+
+    tokio::task::spawn_blocking(move || {
+        let result = (|| -> io::Result<()> {
+            match archive_name_kind(&archive)? {
+                ArchiveKind::Zip => {
+                    let mut zip = zip::ZipArchive::new(BufReader::new(File::open(&archive)?))
+                        .map_err(io::Error::other)?;
+                    for index in 0..zip.len() {
+                        let entry = zip.by_index(index).map_err(io::Error::other)?;
+                        if !entry.is_dir() && normalize_member_path(entry.name()).as_deref() == Some(member.as_str()) {
+                            return send_chunks(entry, &sender);
+                        }
+                    }
+                }
+                kind => {
+                    let mut tar = tar::Archive::new(open_tar_stream(&archive, kind)?);
+                    for entry in tar.entries()? {
+                        let entry = entry?;
+                        let name = entry.path()?.to_string_lossy().to_string();
+                        if entry.header().entry_type().is_file() && normalize_member_path(&name).as_deref() == Some(member.as_str()) {
+                            return send_chunks(entry, &sender);
+                        }
+                    }
+                }
+            }
+            Err(io::Error::new(io::ErrorKind::NotFound, "Archive member not found"))
+        })();
+
+        if let Err(e) = result {
+            let _ = sender.blocking_send(Err(e));
+        }
+    });
+
+    receiver
+}
+
+fn send_chunks(mut reader: impl Read, sender: &mpsc::Sender<io::Result<Bytes>>) -> io::Result<()> {
+    let mut buffer = vec![0u8; STREAM_CHUNK_SIZE];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        // The receiver is gone when the client disconnects
+        if sender.blocking_send(Ok(Bytes::copy_from_slice(&buffer[..read]))).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+pub fn open_tar_stream(archive: &Path, kind: ArchiveKind) -> io::Result<Box<dyn Read + Send>> {
+    let file = BufReader::new(File::open(archive)?);
+    Ok(match kind {
+        ArchiveKind::TarGz => Box::new(GzDecoder::new(file)),
+        _ => Box::new(file),
+    })
+}
+
+fn archive_name_kind(archive: &Path) -> io::Result<ArchiveKind> {
+    archive.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(archive_kind)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not a supported archive"))
+}
+
+// Extracts `archive` into `destination`, refusing members that would land
+// outside it and stopping once the size or entry limits are exceeded.
+// Existing files are never overwritten and members hidden from `access` are
+// left out; both are reported as job errors.
+pub fn extract_archive(archive: &Path, destination: &Path, access: &Access, job: &JobHandle) -> Result<String, String> {
+    let kind = archive_name_kind(archive).map_err(|e| e.to_string())?;
+    let members = read_members(archive).map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    let file_count = members.iter().filter(|m| !m.is_dir).count();
+    if members.len() > MAX_ARCHIVE_ENTRIES {
+        return Err(format!("Archive has more than {} entries", MAX_ARCHIVE_ENTRIES));
+    }
+    let declared_bytes: u64 = members.iter().map(|m| m.size).sum();
+    if declared_bytes > CONFIG.limits.max_archive_size {
+        return Err(format!("Archive would expand to more than {} bytes", CONFIG.limits.max_archive_size));
+    }
+    job.set_totals(file_count as u64, declared_bytes);
+
+    fs::create_dir_all(destination).map_err(|e| format!("Failed to create destination: {}", e))?;
+    // Visibility rules match the path as configured, not the resolved one
+    let visible_root = destination;
+    let destination = destination.canonicalize().map_err(|e| format!("Failed to resolve destination: {}", e))?;
+
+    // Declared sizes and counts can lie, so check what is actually seen as well
+    let mut written = 0u64;
+    let mut extracted = 0usize;
+    let mut seen = 0usize;
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut zip = zip::ZipArchive::new(BufReader::new(File::open(archive).map_err(|e| e.to_string())?))
+                .map_err(|e| format!("Failed to read archive: {}", e))?;
+            for index in 0..zip.len() {
+                if job.is_cancelled() {
+                    return Err("Cancelled".to_string());
+                }
+                seen += 1;
+                if seen > MAX_ARCHIVE_ENTRIES {
+                    return Err(format!("Archive has more than {} entries", MAX_ARCHIVE_ENTRIES));
+                }
+                let entry = zip.by_index(index).map_err(|e| format!("Failed to read entry: {}", e))?;
+                let name = entry.name().to_string();
+                if entry.is_symlink() {
+                    job.add_error(format!("{}: symbolic links are not extracted", name));
+                    continue;
+                }
+                let is_dir = entry.is_dir();
+                if is_hidden_member(visible_root, &name, is_dir, access) {
+                    job.add_error(format!("{}: hidden by the folder's rules, skipped", name));
+                    continue;
+                }
+                if extract_member(entry, &name, is_dir, &destination, &mut written, job)? {
+                    extracted += 1;
+                }
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let stream = open_tar_stream(archive, kind).map_err(|e| e.to_string())?;
+            let mut tar = tar::Archive::new(stream);
+            for entry in tar.entries().map_err(|e| format!("Failed to read archive: {}", e))? {
+                if job.is_cancelled() {
+                    return Err("Cancelled".to_string());
+                }
+                seen += 1;
+                if seen > MAX_ARCHIVE_ENTRIES {
+                    return Err(format!("Archive has more than {} entries", MAX_ARCHIVE_ENTRIES));
+                }
+                let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+                let name = entry.path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+                let entry_type = entry.header().entry_type();
+                if !entry_type.is_file() && !entry_type.is_dir() {
+                    job.add_error(format!("{}: links and special files are not extracted", name));
+                    continue;
+                }
+                if is_hidden_member(visible_root, &name, entry_type.is_dir(), access) {
+                    job.add_error(format!("{}: hidden by the folder's rules, skipped", name));
+                    continue;
+                }
+                if extract_member(entry, &name, entry_type.is_dir(), &destination, &mut written, job)? {
+                    extracted += 1;
+                }
+            }
+        }
+    }
+
+    Ok(format!("Extracted {} files", extracted))
+}
+
+// True when the member, or any folder on its way down, would be hidden from
+// `access` once extracted. Unsafe names are left for `extract_member` to report.
+fn is_hidden_member(root: &Path, name: &str, is_dir: bool, access: &Access) -> bool {
+    let relative = match normalize_member_path(name) {
+        Some(relative) => relative,
+        None => return false,
+    };
+    let mut partial = root.to_path_buf();
+    let components: Vec<&str> = relative.split('/').collect();
+    for (i, component) in components.iter().enumerate() {
+        partial.push(component);
+        if is_excluded_entry(&partial, is_dir || i + 1 < components.len(), access) {
+            return true;
+        }
+    }
+    false
+}
+
+// Returns Ok(true) when a file was written
+fn extract_member(
+    mut reader: impl Read,
+    name: &str,
+    is_dir: bool,
+    destination: &Path,
+    written: &mut u64,
+    job: &JobHandle,
+) -> Result<bool, String> {
+    let relative = match normalize_member_path(name) {
+        Some(relative) => relative,
+        None => {
+            job.add_error(format!("{}: unsafe path skipped", name));
+            return Ok(false);
+        }
+    };
+    let target = destination.join(&relative);
+    let parent = target.parent().unwrap_or(destination);
+
+    // A symlinked folder already inside the destination must not redirect us,
+    // so nothing is created until the folders that exist have been resolved
+    let folder = if is_dir { target.as_path() } else { parent };
+    let existing = folder.ancestors().find(|path| path.exists()).unwrap_or(destination);
+    let real_folder = existing.canonicalize().map_err(|e| format!("{}: {}", relative, e))?;
+    if !real_folder.starts_with(destination) {
+        job.add_error(format!("{}: resolves outside the destination, skipped", relative));
+        return Ok(false);
+    }
+
+    if is_dir {
+        fs::create_dir_all(&target).map_err(|e| format!("{}: {}", relative, e))?;
+        return Ok(false);
+    }
+
+    job.set_current(&relative);
+    fs::create_dir_all(parent).map_err(|e| format!("{}: {}", relative, e))?;
+
+    let file = match fs::OpenOptions::new().write(true).create_new(true).open(&target) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            job.add_error(format!("{}: already exists, skipped", relative));
+            return Ok(false);
+        }
+        Err(e) => return Err(format!("{}: {}", relative, e)),
+    };
+
+    let mut writer = BufWriter::new(file);
+    let mut buffer = vec![0u8; STREAM_CHUNK_SIZE];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(read) => read,
+            Err(e) => {
+                drop(writer);
+                let _ = fs::remove_file(&target);
+                return Err(format!("{}: {}", relative, e));
+            }
+        };
+        if read == 0 {
+            break;
+        }
+        *written += read as u64;
+        if *written > CONFIG.limits.max_archive_size || job.is_cancelled() {
+            drop(writer);
+            let _ = fs::remove_file(&target);
+            if job.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            return Err(format!("Archive expands to more than {} bytes, extraction stopped", CONFIG.limits.max_archive_size));
+        }
+        writer.write_all(&buffer[..read]).map_err(|e| format!("{}: {}", relative, e))?;
+        job.add_bytes(read as u64);
+    }
+    writer.flush().map_err(|e| format!("{}: {}", relative, e))?;
+    job.file_done();
+
+    Ok(true)
+}
+
+// Packs `sources` into a new archive at `destination`. Each selected file or
+// folder is stored under its own name at the top level of the archive.
+// Entries hidden from `access` are left out.
+pub fn create_archive(sources: &[PathBuf], destination: &Path, access: &Access, job: &JobHandle) -> Result<String, String> {
+    let kind = archive_name_kind(destination).map_err(|e| e.to_string())?;
+
+    let mut files = Vec::new();
+    for source in sources {
+        let base = source.parent().unwrap_or(source);
+        collect_files(source, base, destination, access, &mut files)?;
+    }
+    let total_bytes: u64 = files.iter().map(|(_, _, size)| size).sum();
+    if total_bytes > CONFIG.limits.max_archive_size {
+        return Err(format!("Selection is larger than {} bytes", CONFIG.limits.max_archive_size));
+    }
+    job.set_totals(files.len() as u64, total_bytes);
+
+    // Build next to the destination and only move it into place when complete
+    let file_name = destination.file_name().and_then(|n| n.to_str()).unwrap_or("archive");
+    let partial_path = destination.with_file_name(format!(".{}.partial", file_name));
+    let result = write_archive(kind, &files, &partial_path, job)
+        .and_then(|_| {
+            if job.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            fs::rename(&partial_path, destination).map_err(|e| format!("Failed to move archive into place: {}", e))
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&partial_path);
+    }
+    result.map(|_| format!("Compressed {} files", files.len()))
+}
+
+fn collect_files(path: &Path, base: &Path, destination: &Path, access: &Access, files: &mut Vec<(PathBuf, String, u64)>) -> Result<(), String> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if metadata.file_type().is_symlink() || path == destination || is_excluded_entry(path, metadata.is_dir(), access) {
+        return Ok(());
+    }
+
+    if metadata.is_dir() {
+        let entries = fs::read_dir(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        for entry in entries.flatten() {
+            collect_files(&entry.path(), base, destination, access, files)?;
+        }
+    } else if metadata.is_file() {
+        if files.len() >= MAX_ARCHIVE_ENTRIES {
+            return Err(format!("Selection has more than {} files", MAX_ARCHIVE_ENTRIES));
+        }
+        let member = path.strip_prefix(base).unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push((path.to_path_buf(), member, metadata.len()));
+    }
+    Ok(())
+}
+
+fn write_archive(kind: ArchiveKind, files: &[(PathBuf, String, u64)], output: &Path, job: &JobHandle) -> Result<(), String> {
+    let file = File::create(output).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let writer = BufWriter::new(file);
+
+    // Every layer is finished explicitly so a failed final write is reported
+    // instead of being lost when the writer is dropped
+    let writer = match kind {
+        ArchiveKind::Zip => {
+            let mut zip = zip::ZipWriter::new(writer);
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated)
+                .large_file(true);
+            for (path, member, _) in files {
+                if job.is_cancelled() {
+                    return Err("Cancelled".to_string());
+                }
+                job.set_current(member);
+                zip.start_file(member.as_str(), options).map_err(|e| format!("{}: {}", member, e))?;
+                copy_with_progress(path, &mut zip, job).map_err(|e| format!("{}: {}", member, e))?;
+                job.file_done();
+            }
+            zip.finish().map_err(|e| format!("Failed to finish archive: {}", e))?
+        }
+        ArchiveKind::Tar => {
+            let mut tar = tar::Builder::new(writer);
+            append_tar_members(&mut tar, files, job)?;
+            tar.into_inner().map_err(|e| format!("Failed to finish archive: {}", e))?
+        }
+        ArchiveKind::TarGz => {
+            let mut tar = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+            append_tar_members(&mut tar, files, job)?;
+            tar.into_inner()
+                .and_then(|encoder| encoder.finish())
+                .map_err(|e| format!("Failed to finish archive: {}", e))?
+        }
+    };
+
+    let file = writer.into_inner().map_err(|e| format!("Failed to finish archive: {}", e.error()))?;
+    file.sync_all().map_err(|e| format!("Failed to finish archive: {}", e))
+}
+
+fn append_tar_members<W: Write>(tar: &mut tar::Builder<W>, files: &[(PathBuf, String, u64)], job: &JobHandle) -> Result<(), String> {
+    for (path, member, size) in files {
+        if job.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        job.set_current(member);
+        let mut header = tar::Header::new_gnu();
+        let metadata = fs::metadata(path).map_err(|e| format!("{}: {}", member, e))?;
+        header.set_metadata(&metadata);
+        header.set_size(*size);
+        let reader = ProgressReader::new(File::open(path).map_err(|e| format!("{}: {}", member, e))?, job);
+        tar.append_data(&mut header, member, reader.take(*size)).map_err(|e| format!("{}: {}", member, e))?;
+        job.file_done();
+    }
+    Ok(())
+}
+
+fn copy_with_progress(path: &Path, output: &mut impl Write, job: &JobHandle) -> io::Result<()> {
+    let mut reader = ProgressReader::new(File::open(path)?, job);
+    io::copy(&mut reader, output)?;
+    Ok(())
+}
@@ -2,10 +2,14 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
+use crate::config::CONFIG;
+use crate::utils::escape_html;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub username: String,
+    // Selects the role-specific visibility rules; "admin" may also show hidden files
+    pub role: String,
     pub created_at: u64,
     pub expires_at: u64,
 }
@@ -13,48 +17,61 @@ pub struct Session {
 pub struct AuthManager {
     sessions: Arc<Mutex<HashMap<String, Session>>>,
     valid_credentials: HashMap<String, String>,
+    roles: HashMap<String, String>,
+    session_timeout_secs: u64,
+}
+
+impl Default for AuthManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AuthManager {
     pub fn new() -> Self {
         let mut credentials = HashMap::new();
-        credentials.insert("admin".to_string(), "admin123".to_string());
+        credentials.insert(CONFIG.auth.username.clone(), CONFIG.auth.password.clone());
+        let mut roles = HashMap::new();
+        roles.insert(CONFIG.auth.username.clone(), "admin".to_string());
         
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
             valid_credentials: credentials,
+            roles,
+            session_timeout_secs: CONFIG.auth.session_timeout_minutes * 60,
         }
     }
     
     pub fn authenticate(&self, username: &str, password: &str) -> Option<String> {
-        if let Some(stored_password) = self.valid_credentials.get(username) {
-            if stored_password == password {
-                // Generate session token
-                let token = format!("{}{}", username, 
-                    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
-                
-                let session = Session {
-                    username: username.to_string(),
-                    created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-                    expires_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600, // 1 hour
-                };
-                
-                if let Ok(mut sessions) = self.sessions.lock() {
-                    sessions.insert(token.clone(), session);
-                }
-                
-                return Some(token);
+        if let Some(stored_password) = self.valid_credentials.get(username)
+            && stored_password == password
+        {
+            // Generate session token
+            let token = format!("{}{}", username, 
+                SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+            
+            let session = Session {
+                username: username.to_string(),
+                role: self.roles.get(username).cloned().unwrap_or_else(|| "user".to_string()),
+                created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+                expires_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + self.session_timeout_secs,
+            };
+            
+            if let Ok(mut sessions) = self.sessions.lock() {
+                sessions.insert(token.clone(), session);
             }
+            
+            return Some(token);
         }
         None
     }
     
     pub fn is_valid_token(&self, token: &str) -> bool {
-        if let Ok(sessions) = self.sessions.lock() {
-            if let Some(session) = sessions.get(token) {
-                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-                return session.expires_at > now;
-            }
+        if let Ok(sessions) = self.sessions.lock()
+            && let Some(session) = sessions.get(token)
+        {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            return session.expires_at > now;
         }
         false
     }
@@ -91,12 +108,25 @@ impl AuthManager {
     }
 }
 
+// The demo credentials are only shown while the built-in password is in use
 pub fn generate_login_html() -> String {
+    let (credentials, username, password) = if CONFIG.has_default_credentials() {
+        (format!(r#"
+            <div class="credentials">
+                <h3>🔑 Demo Credentials</h3>
+                <p>👤 Username: {}</p>
+                <p>🔐 Password: {}</p>
+            </div>
+            "#, CONFIG.auth.username, CONFIG.auth.password), CONFIG.auth.username.as_str(), CONFIG.auth.password.as_str())
+    } else {
+        (String::new(), "", "")
+    };
+
     r#"
     <!DOCTYPE html>
     <html>
     <head>
-        <title>Login - FirxTTech Solutions</title>
+        <title>Login - {organization}</title>
         <meta charset="UTF-8">
         <style>
             body { 
@@ -207,29 +237,28 @@ pub fn generate_login_html() -> String {
     <body>
         <div class="login-container">
             <div class="logo">
-                <h1>🦀 File Manager</h1>
-                <p>FirxTTech Solutions</p>
+                <h1>🦀 {app_title}</h1>
+                <p>{organization}</p>
             </div>
-            
-            <div class="credentials">
-                <h3>🔑 Demo Credentials</h3>
-                <p>👤 Username: admin</p>
-                <p>🔐 Password: admin123</p>
-            </div>
-            
+            {credentials}
             <form method="POST" action="/login">
                 <div class="form-group">
                     <label for="username">👤 Username</label>
-                    <input type="text" id="username" name="username" required value="admin">
+                    <input type="text" id="username" name="username" required value="{username}">
                 </div>
                 <div class="form-group">
                     <label for="password">🔐 Password</label>
-                    <input type="password" id="password" name="password" required value="admin123">
+                    <input type="password" id="password" name="password" required value="{password}">
                 </div>
                 <button type="submit" class="login-btn">🚀 Login</button>
             </form>
         </div>
     </body>
     </html>
-    "#.to_string()
+    "#
+    .replace("{credentials}", &credentials)
+    .replace("{app_title}", &escape_html(&CONFIG.branding.title))
+    .replace("{organization}", &escape_html(&CONFIG.branding.organization))
+    .replace("{username}", username)
+    .replace("{password}", password)
 }
\ No newline at end of file
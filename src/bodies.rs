@@ -3,6 +3,7 @@ use hyper::body::Bytes;
 use std::io;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use tokio::sync::mpsc;
 
 pub struct StringBody {
     data: Option<String>,
@@ -56,4 +57,32 @@ impl Body for BytesBody {
             Poll::Ready(None)
         }
     }
-}
\ No newline at end of file
+}
+
+// Streams chunks produced elsewhere (usually a blocking task) as they arrive
+pub struct ChannelBody {
+    receiver: mpsc::Receiver<io::Result<Bytes>>,
+}
+
+impl ChannelBody {
+    pub fn new(receiver: mpsc::Receiver<io::Result<Bytes>>) -> Self {
+        Self { receiver }
+    }
+}
+
+impl Body for ChannelBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<hyper::body::Frame<Self::Data>, Self::Error>>> {
+        match self.receiver.poll_recv(cx) {
+            Poll::Ready(Some(Ok(bytes))) => Poll::Ready(Some(Ok(hyper::body::Frame::data(bytes)))),
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
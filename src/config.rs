@@ -0,0 +1,393 @@
+use crate::content_index::{DEFAULT_INDEX_DIR, DEFAULT_RESCAN_MINUTES};
+use crate::handlers::utils::get_home_directory;
+use crate::trash::{DEFAULT_RETENTION_DAYS, DEFAULT_TRASH_DIR};
+use crate::utils::parse_size;
+use crate::thumbnails::DEFAULT_THUMBNAIL_DIR;
+use crate::versions::{DEFAULT_KEEP_VERSIONS, DEFAULT_VERSIONS_DIR};
+use crate::volumes::{RoleConfig, VolumeConfig, Volumes};
+use clap::Parser;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub const DEFAULT_CONFIG_FILE: &str = "./config.toml";
+// Volumes used to be listed here; refuse to start rather than ignore it
+const OLD_VOLUMES_FILE: &str = "./volumes.json";
+pub const DEFAULT_PORT: u16 = 8000;
+pub const DEFAULT_USERNAME: &str = "admin";
+pub const DEFAULT_PASSWORD: &str = "admin123";
+
+// Command-line options. Each setting can also come from the environment
+// variable shown in --help; flags win over the environment, and both win
+// over the config file.
+#[derive(Parser, Debug)]
+#[command(about = "Web-based file manager", version)]
+pub struct Args {
+    /// TOML configuration file [default: ./config.toml, if present]
+    #[arg(long, short, env = "CONFIG_FILE", value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Validate the configuration and exit
+    #[arg(long)]
+    check_config: bool,
+    /// Address to listen on
+    #[arg(long, env = "BIND_ADDRESS", value_name = "IP")]
+    bind: Option<IpAddr>,
+    /// Port to listen on
+    #[arg(long, short, env = "PORT")]
+    port: Option<u16>,
+    /// Folder served when no volumes are configured
+    #[arg(long, env = "ROOT_DIR", value_name = "DIR")]
+    root: Option<PathBuf>,
+    /// Where uploads are saved
+    #[arg(long, env = "UPLOAD_DIR", value_name = "DIR")]
+    upload_dir: Option<PathBuf>,
+    /// Where resumable uploads are assembled
+    #[arg(long, env = "TEMP_DIR", value_name = "DIR")]
+    temp_dir: Option<PathBuf>,
+    /// Name of the admin account
+    #[arg(long, env = "ADMIN_USERNAME", value_name = "NAME")]
+    username: Option<String>,
+    /// Password of the admin account
+    #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+    /// Minutes before a login expires
+    #[arg(long, env = "SESSION_TIMEOUT_MINUTES", value_name = "MINUTES")]
+    session_timeout: Option<u64>,
+    /// Largest accepted upload, such as 2G; 0 for no limit
+    #[arg(long, env = "MAX_UPLOAD_SIZE", value_name = "SIZE", value_parser = parse_size_arg)]
+    max_upload_size: Option<u64>,
+    /// Largest file the editor opens
+    #[arg(long, env = "MAX_EDIT_SIZE", value_name = "SIZE", value_parser = parse_size_arg)]
+    max_edit_size: Option<u64>,
+    /// Largest archive that is created or extracted
+    #[arg(long, env = "MAX_ARCHIVE_SIZE", value_name = "SIZE", value_parser = parse_size_arg)]
+    max_archive_size: Option<u64>,
+    /// Application name shown in page titles and headings
+    #[arg(long, env = "BRAND_TITLE", value_name = "TEXT")]
+    brand_title: Option<String>,
+    /// Organization shown under the application name
+    #[arg(long, env = "BRAND_ORGANIZATION", value_name = "TEXT")]
+    brand_organization: Option<String>,
+    /// Where deleted items are kept until they are purged
+    #[arg(long, env = "TRASH_DIR", value_name = "DIR")]
+    trash_dir: Option<PathBuf>,
+    /// Days before trashed items are purged; 0 keeps them
+    #[arg(long, env = "TRASH_RETENTION_DAYS", value_name = "DAYS")]
+    trash_retention_days: Option<u64>,
+    /// Where earlier versions of replaced files are kept
+    #[arg(long, env = "VERSIONS_DIR", value_name = "DIR")]
+    versions_dir: Option<PathBuf>,
+    /// Earlier versions kept per file; 0 turns versioning off
+    #[arg(long, env = "FILE_VERSIONS_KEEP", value_name = "COUNT")]
+    versions_keep: Option<usize>,
+    /// Where the content index is saved
+    #[arg(long, env = "CONTENT_INDEX_DIR", value_name = "DIR")]
+    index_dir: Option<PathBuf>,
+    /// Minutes between content index rescans; 0 scans only at startup
+    #[arg(long, env = "CONTENT_INDEX_RESCAN_MINUTES", value_name = "MINUTES")]
+    index_rescan_minutes: Option<u64>,
+    /// Where image thumbnails are cached
+    #[arg(long, env = "THUMBNAIL_DIR", value_name = "DIR")]
+    thumbnail_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub username: String,
+    pub password: String,
+    pub session_timeout_minutes: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            username: DEFAULT_USERNAME.to_string(),
+            password: DEFAULT_PASSWORD.to_string(),
+            session_timeout_minutes: 60,
+        }
+    }
+}
+
+// Sizes are bytes, or strings such as "512K" and "2G"
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    #[serde(deserialize_with = "deserialize_size")]
+    pub max_upload_size: u64,
+    #[serde(deserialize_with = "deserialize_size")]
+    pub max_edit_size: u64,
+    #[serde(deserialize_with = "deserialize_size")]
+    pub max_archive_size: u64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig {
+            max_upload_size: 0,
+            max_edit_size: 1024 * 1024,
+            max_archive_size: 4 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BrandingConfig {
+    pub title: String,
+    pub organization: String,
+}
+
+impl Default for BrandingConfig {
+    fn default() -> Self {
+        BrandingConfig {
+            title: "File Manager".to_string(),
+            organization: "FirxTTech Solutions".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrashConfig {
+    pub dir: PathBuf,
+    pub retention_days: u64,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        TrashConfig { dir: PathBuf::from(DEFAULT_TRASH_DIR), retention_days: DEFAULT_RETENTION_DAYS }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VersionsConfig {
+    pub dir: PathBuf,
+    pub keep: usize,
+}
+
+impl Default for VersionsConfig {
+    fn default() -> Self {
+        VersionsConfig { dir: PathBuf::from(DEFAULT_VERSIONS_DIR), keep: DEFAULT_KEEP_VERSIONS }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContentIndexConfig {
+    pub dir: PathBuf,
+    pub rescan_minutes: u64,
+}
+
+impl Default for ContentIndexConfig {
+    fn default() -> Self {
+        ContentIndexConfig { dir: PathBuf::from(DEFAULT_INDEX_DIR), rescan_minutes: DEFAULT_RESCAN_MINUTES }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThumbnailsConfig {
+    pub dir: PathBuf,
+}
+
+impl Default for ThumbnailsConfig {
+    fn default() -> Self {
+        ThumbnailsConfig { dir: PathBuf::from(DEFAULT_THUMBNAIL_DIR) }
+    }
+}
+
+// Everything that can be set in config.toml. Settings left out keep the
+// defaults the server has always used.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bind: IpAddr,
+    pub port: u16,
+    pub root: PathBuf,
+    pub upload_dir: PathBuf,
+    pub temp_dir: PathBuf,
+    // Hidden in every volume, and per role
+    pub exclude: Vec<String>,
+    pub roles: HashMap<String, RoleConfig>,
+    pub volumes: Vec<VolumeConfig>,
+    pub auth: AuthConfig,
+    pub limits: LimitsConfig,
+    pub branding: BrandingConfig,
+    pub trash: TrashConfig,
+    pub versions: VersionsConfig,
+    pub content_index: ContentIndexConfig,
+    pub thumbnails: ThumbnailsConfig,
+    // Set from the command line only
+    #[serde(skip)]
+    pub file: Option<PathBuf>,
+    #[serde(skip)]
+    pub check_only: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let home = PathBuf::from(get_home_directory());
+        Config {
+            bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: DEFAULT_PORT,
+            upload_dir: home.join("Desktop").join("uploads"),
+            root: home,
+            temp_dir: PathBuf::from("./temp_uploads"),
+            exclude: Vec::new(),
+            roles: HashMap::new(),
+            volumes: Vec::new(),
+            auth: AuthConfig::default(),
+            limits: LimitsConfig::default(),
+            branding: BrandingConfig::default(),
+            trash: TrashConfig::default(),
+            versions: VersionsConfig::default(),
+            content_index: ContentIndexConfig::default(),
+            thumbnails: ThumbnailsConfig::default(),
+            file: None,
+            check_only: false,
+        }
+    }
+}
+
+impl Config {
+    // Reads the config file, then applies the environment and the command
+    // line. A missing default file is fine; one named explicitly must exist.
+    pub fn from_args(args: Args) -> Result<Self, String> {
+        let explicit = args.config.is_some();
+        let path = args.config.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_FILE));
+        let mut config = if explicit || path.exists() {
+            let mut config = Self::from_file(&path)?;
+            config.file = Some(path);
+            config
+        } else {
+            Config::default()
+        };
+        config.apply(args);
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn apply(&mut self, args: Args) {
+        self.check_only = args.check_config;
+        if let Some(bind) = args.bind { self.bind = bind; }
+        if let Some(port) = args.port { self.port = port; }
+        if let Some(root) = args.root { self.root = root; }
+        if let Some(upload_dir) = args.upload_dir { self.upload_dir = upload_dir; }
+        if let Some(temp_dir) = args.temp_dir { self.temp_dir = temp_dir; }
+        if let Some(username) = args.username { self.auth.username = username; }
+        if let Some(password) = args.password { self.auth.password = password; }
+        if let Some(minutes) = args.session_timeout { self.auth.session_timeout_minutes = minutes; }
+        if let Some(size) = args.max_upload_size { self.limits.max_upload_size = size; }
+        if let Some(size) = args.max_edit_size { self.limits.max_edit_size = size; }
+        if let Some(size) = args.max_archive_size { self.limits.max_archive_size = size; }
+        if let Some(title) = args.brand_title { self.branding.title = title; }
+        if let Some(organization) = args.brand_organization { self.branding.organization = organization; }
+        if let Some(dir) = args.trash_dir { self.trash.dir = dir; }
+        if let Some(days) = args.trash_retention_days { self.trash.retention_days = days; }
+        if let Some(dir) = args.versions_dir { self.versions.dir = dir; }
+        if let Some(keep) = args.versions_keep { self.versions.keep = keep; }
+        if let Some(dir) = args.index_dir { self.content_index.dir = dir; }
+        if let Some(minutes) = args.index_rescan_minutes { self.content_index.rescan_minutes = minutes; }
+        if let Some(dir) = args.thumbnail_dir { self.thumbnails.dir = dir; }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !self.root.is_dir() {
+            return Err(format!("root: {} is not a folder", self.root.display()));
+        }
+        if self.volumes.is_empty() && Path::new(OLD_VOLUMES_FILE).exists() {
+            return Err(format!("{} is no longer read; list its volumes as [[volumes]] in the config file", OLD_VOLUMES_FILE));
+        }
+        for (name, dir) in [("upload_dir", &self.upload_dir)].into_iter().chain(self.data_dirs()) {
+            if dir.exists() && !dir.is_dir() {
+                return Err(format!("{}: {} is not a folder", name, dir.display()));
+            }
+        }
+        let data_dirs = self.data_dirs();
+        for (i, (name, dir)) in data_dirs.iter().enumerate() {
+            let absolute = std::path::absolute(dir).map_err(|e| format!("{}: {}", name, e))?;
+            for (other_name, other) in &data_dirs[i + 1..] {
+                if std::path::absolute(other).is_ok_and(|other| other.starts_with(&absolute) || absolute.starts_with(&other)) {
+                    return Err(format!("{} and {} must be separate folders", name, other_name));
+                }
+            }
+        }
+        if self.auth.username.trim().is_empty() || self.auth.password.is_empty() {
+            return Err("auth: username and password must not be empty".to_string());
+        }
+        if self.auth.session_timeout_minutes == 0 {
+            return Err("auth: session_timeout_minutes must be at least 1".to_string());
+        }
+        if self.limits.max_edit_size == 0 || self.limits.max_archive_size == 0 {
+            return Err("limits: max_edit_size and max_archive_size must be greater than 0".to_string());
+        }
+        Volumes::new(self).map(|_| ())
+    }
+
+    // The folders the server keeps its own data in, by config key. They are
+    // hidden in every volume.
+    pub fn data_dirs(&self) -> [(&'static str, &PathBuf); 5] {
+        [
+            ("temp_dir", &self.temp_dir),
+            ("trash.dir", &self.trash.dir),
+            ("versions.dir", &self.versions.dir),
+            ("content_index.dir", &self.content_index.dir),
+            ("thumbnails.dir", &self.thumbnails.dir),
+        ]
+    }
+
+    // Installs the configuration main() loaded. It must run before anything
+    // reads CONFIG, which otherwise settles on the defaults.
+    pub fn init(self) {
+        if LOADED.set(self).is_err() {
+            panic!("Config::init called after the configuration was first used");
+        }
+    }
+
+    // Whether an upload of `size` bytes is over `limits.max_upload_size`
+    pub fn exceeds_upload_limit(&self, size: u64) -> bool {
+        self.limits.max_upload_size != 0 && size > self.limits.max_upload_size
+    }
+
+    // Whether the admin still has the built-in password, which is shown on
+    // the login page and in the startup banner
+    pub fn has_default_credentials(&self) -> bool {
+        self.auth.username == DEFAULT_USERNAME && self.auth.password == DEFAULT_PASSWORD
+    }
+}
+
+fn parse_size_arg(value: &str) -> Result<u64, String> {
+    parse_size(value).ok_or_else(|| format!("invalid size: {}", value))
+}
+
+fn deserialize_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+
+    match Size::deserialize(deserializer)? {
+        Size::Bytes(bytes) => Ok(bytes),
+        Size::Text(text) => parse_size(&text).ok_or_else(|| serde::de::Error::custom(format!("invalid size \"{}\"", text))),
+    }
+}
+
+static LOADED: OnceLock<Config> = OnceLock::new();
+
+lazy_static::lazy_static! {
+    // The configuration given to `Config::init`, or the defaults when there
+    // is none, as in tests
+    pub static ref CONFIG: &'static Config = LOADED.get_or_init(Config::default);
+}
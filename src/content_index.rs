@@ -0,0 +1,545 @@
+use crate::config::CONFIG;
+use crate::file_browser::get_file_type_class;
+use crate::handlers::utils::is_excluded_entry;
+use crate::visibility::Access;
+use crate::volumes::VOLUMES;
+use crate::utils::escape_html;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+// Larger files are skipped; they are rarely documents anyone searches by content
+pub const MAX_INDEXED_BYTES: u64 = 4 * 1024 * 1024;
+pub const DEFAULT_INDEX_DIR: &str = "./search_index";
+pub const DEFAULT_RESCAN_MINUTES: u64 = 30;
+const MAX_RESULTS: usize = 50;
+// Pending changes are written to disk at most this often
+const FLUSH_INTERVAL_SECS: u64 = 60;
+const SNIPPET_CONTEXT_CHARS: usize = 80;
+const MIN_TERM_CHARS: usize = 2;
+const MAX_TERM_CHARS: usize = 40;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedDocument {
+    modified: u64,
+    size: u64,
+    // Number of terms in the document, for length normalisation
+    length: u32,
+    terms: HashMap<String, u32>,
+}
+
+#[derive(Default)]
+struct IndexData {
+    // Keyed by the path relative to the served root
+    documents: HashMap<String, IndexedDocument>,
+    postings: HashMap<String, HashSet<String>>,
+    total_length: u64,
+    dirty: bool,
+}
+
+impl IndexData {
+    fn insert(&mut self, relative_path: String, document: IndexedDocument) {
+        self.remove(&relative_path);
+        for term in document.terms.keys() {
+            self.postings.entry(term.clone()).or_default().insert(relative_path.clone());
+        }
+        self.total_length += document.length as u64;
+        self.documents.insert(relative_path, document);
+        self.dirty = true;
+    }
+
+    fn remove(&mut self, relative_path: &str) -> bool {
+        let document = match self.documents.remove(relative_path) {
+            Some(document) => document,
+            None => return false,
+        };
+        for term in document.terms.keys() {
+            if let Some(paths) = self.postings.get_mut(term) {
+                paths.remove(relative_path);
+                if paths.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        self.total_length -= document.length as u64;
+        self.dirty = true;
+        true
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentMatch {
+    // Relative to the served root, as used in /browse URLs
+    pub path: String,
+    pub name: String,
+    pub score: f64,
+    // HTML with the matched words wrapped in <mark>
+    pub snippet: String,
+    pub size: u64,
+    pub modified: u64,
+}
+
+// An inverted index of the text files under `roots`, kept in memory and saved
+// as `<index_dir>/index.json`. Text pulled out of PDFs is cached under
+// `<index_dir>/text` so snippets do not need a second extraction.
+pub struct ContentIndex {
+    index_dir: PathBuf,
+    // Folders to index, each with the prefix its paths carry in /browse URLs
+    roots: Vec<(String, PathBuf)>,
+    rescan_minutes: u64,
+    data: RwLock<IndexData>,
+    scanning: AtomicBool,
+}
+
+impl ContentIndex {
+    pub fn new(index_dir: &Path, roots: Vec<(String, PathBuf)>, rescan_minutes: u64) -> Self {
+        fs::create_dir_all(index_dir.join("text")).unwrap_or_default();
+
+        let mut data = IndexData::default();
+        let saved = fs::read(index_dir.join("index.json")).ok()
+            .and_then(|bytes| serde_json::from_slice::<HashMap<String, IndexedDocument>>(&bytes).ok())
+            .unwrap_or_default();
+        for (relative_path, document) in saved {
+            data.insert(relative_path, document);
+        }
+        data.dirty = false;
+
+        ContentIndex {
+            index_dir: index_dir.to_path_buf(),
+            roots,
+            rescan_minutes,
+            data: RwLock::new(data),
+            scanning: AtomicBool::new(false),
+        }
+    }
+
+    pub fn rescan_minutes(&self) -> u64 {
+        self.rescan_minutes
+    }
+
+    pub fn document_count(&self) -> usize {
+        self.data.read().map(|data| data.documents.len()).unwrap_or(0)
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.scanning.load(Ordering::Relaxed)
+    }
+
+    // Brings one file up to date after an upload or an edit. Paths outside
+    // the roots, or that should not be indexed, are dropped from the index.
+    pub fn update_file(&self, path: &Path) {
+        let relative_path = match self.relative_path(path) {
+            Some(relative_path) => relative_path,
+            None => return,
+        };
+        let indexable = is_indexable(&relative_path)
+            && fs::symlink_metadata(path).is_ok_and(|m| m.is_file() && m.len() <= MAX_INDEXED_BYTES);
+        if indexable {
+            self.index_file(path, &relative_path);
+        } else if let Ok(mut data) = self.data.write() {
+            data.remove(&relative_path);
+        }
+    }
+
+    // Walks all the roots, indexing new and changed files and dropping the
+    // ones that are gone. Returns how many files were (re)indexed and removed.
+    pub fn rescan(&self) -> (usize, usize) {
+        if self.scanning.swap(true, Ordering::Relaxed) {
+            return (0, 0);
+        }
+
+        let mut seen = HashSet::new();
+        let mut indexed = 0;
+        let mut stack: Vec<(PathBuf, String)> = self.roots.iter()
+            .map(|(prefix, root)| (root.clone(), prefix.clone()))
+            .collect();
+
+        while let Some((dir, relative_dir)) = stack.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let path = entry.path();
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                };
+                if is_excluded_entry(&path, metadata.is_dir(), &Access::default()) {
+                    continue;
+                }
+                let relative_path = if relative_dir.is_empty() { name.clone() } else { format!("{}/{}", relative_dir, name) };
+                if metadata.is_dir() {
+                    stack.push((path, relative_path));
+                    continue;
+                }
+                if !metadata.is_file() || metadata.len() > MAX_INDEXED_BYTES || !is_indexable(&name) {
+                    continue;
+                }
+
+                let unchanged = self.data.read().is_ok_and(|data| data.documents.get(&relative_path)
+                    .is_some_and(|document| document.size == metadata.len() && document.modified == modified_secs(&metadata)))
+                    && (!is_pdf(&name) || self.text_cache_path(&relative_path).is_file());
+                if !unchanged && self.index_file(&path, &relative_path) {
+                    indexed += 1;
+                }
+                seen.insert(relative_path);
+            }
+        }
+
+        let mut removed = 0;
+        if let Ok(mut data) = self.data.write() {
+            let gone: Vec<String> = data.documents.keys().filter(|path| !seen.contains(*path)).cloned().collect();
+            for relative_path in gone {
+                data.remove(&relative_path);
+                let _ = fs::remove_file(self.text_cache_path(&relative_path));
+                removed += 1;
+            }
+        }
+        self.remove_stale_text();
+
+        self.scanning.store(false, Ordering::Relaxed);
+        (indexed, removed)
+    }
+
+    // Writes the index to disk if anything changed since the last flush
+    pub fn flush(&self) -> io::Result<()> {
+        let json = {
+            let mut data = match self.data.write() {
+                Ok(data) => data,
+                Err(_) => return Ok(()),
+            };
+            if !data.dirty {
+                return Ok(());
+            }
+            data.dirty = false;
+            serde_json::to_vec(&data.documents)?
+        };
+        let temp_path = self.index_dir.join("index.json.tmp");
+        fs::write(&temp_path, json)?;
+        fs::rename(&temp_path, self.index_dir.join("index.json"))
+    }
+
+    // Ranks documents with BM25. `folder` limits results to one folder and
+    // everything below it; files the viewer's role may not see are left out.
+    pub fn search(&self, query: &str, folder: &str, access: &Access) -> Vec<ContentMatch> {
+        let terms: Vec<String> = {
+            let mut seen = HashSet::new();
+            tokenize(query).map(|(_, term)| term).filter(|term| seen.insert(term.clone())).collect()
+        };
+        if terms.is_empty() {
+            return Vec::new();
+        }
+        let folder = folder.trim_matches('/');
+
+        let ranked: Vec<(String, f64, u64, u64)> = {
+            let data = match self.data.read() {
+                Ok(data) => data,
+                Err(_) => return Vec::new(),
+            };
+            let document_count = data.documents.len() as f64;
+            let average_length = (data.total_length as f64 / document_count.max(1.0)).max(1.0);
+            let (k1, b) = (1.2, 0.75);
+
+            let mut scores: HashMap<&str, f64> = HashMap::new();
+            for term in &terms {
+                let paths = match data.postings.get(term) {
+                    Some(paths) => paths,
+                    None => continue,
+                };
+                let idf = (1.0 + (document_count - paths.len() as f64 + 0.5) / (paths.len() as f64 + 0.5)).ln();
+                for path in paths {
+                    if !folder.is_empty() && !path.starts_with(&format!("{}/", folder)) {
+                        continue;
+                    }
+                    let document = &data.documents[path];
+                    let frequency = document.terms[term] as f64;
+                    let norm = k1 * (1.0 - b + b * document.length as f64 / average_length);
+                    *scores.entry(path).or_default() += idf * frequency * (k1 + 1.0) / (frequency + norm);
+                }
+            }
+
+            let mut ranked: Vec<_> = scores.into_iter()
+                .map(|(path, score)| {
+                    let document = &data.documents[path];
+                    (path.to_string(), score, document.size, document.modified)
+                })
+                .collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            ranked.retain(|(path, ..)| VOLUMES.resolve(path, access).is_some());
+            ranked.truncate(MAX_RESULTS);
+            ranked
+        };
+
+        // Snippets come from the files themselves, outside the lock; files
+        // deleted since the last scan are left out
+        ranked.into_iter()
+            .filter_map(|(path, score, size, modified)| {
+                let text = self.document_text(&path)?;
+                Some(ContentMatch {
+                    name: path.rsplit('/').next().unwrap_or(&path).to_string(),
+                    snippet: snippet(&text, &terms),
+                    path,
+                    score: (score * 1000.0).round() / 1000.0,
+                    size,
+                    modified,
+                })
+            })
+            .collect()
+    }
+
+    // Only files visible with the base rules are indexed
+    fn relative_path(&self, path: &Path) -> Option<String> {
+        let (prefix, root) = self.roots.iter()
+            .filter(|(_, root)| path.starts_with(root))
+            .max_by_key(|(_, root)| root.components().count())?;
+        let relative = path.strip_prefix(root).ok()?;
+        let mut parts = Vec::new();
+        if !prefix.is_empty() {
+            parts.push(prefix.clone());
+        }
+        let mut partial = root.clone();
+        let components: Vec<_> = relative.components().collect();
+        for (i, component) in components.iter().enumerate() {
+            partial.push(component);
+            let is_dir = i + 1 < components.len() || partial.is_dir();
+            if is_excluded_entry(&partial, is_dir, &Access::default()) {
+                return None;
+            }
+            parts.push(component.as_os_str().to_string_lossy().into_owned());
+        }
+        Some(parts.join("/"))
+    }
+
+    fn index_file(&self, path: &Path, relative_path: &str) -> bool {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(_) => return false,
+        };
+        let text = match extract_text(path) {
+            Some(text) => text,
+            None => return false,
+        };
+        if is_pdf(relative_path) {
+            let _ = fs::write(self.text_cache_path(relative_path), &text);
+        }
+
+        let mut terms: HashMap<String, u32> = HashMap::new();
+        let mut length = 0;
+        for (_, term) in tokenize(&text) {
+            *terms.entry(term).or_default() += 1;
+            length += 1;
+        }
+        let document = IndexedDocument {
+            modified: modified_secs(&metadata),
+            size: metadata.len(),
+            length,
+            terms,
+        };
+        match self.data.write() {
+            Ok(mut data) => {
+                data.insert(relative_path.to_string(), document);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn document_text(&self, relative_path: &str) -> Option<String> {
+        let path = self.full_path(relative_path)?;
+        if !path.is_file() {
+            return None;
+        }
+        if is_pdf(relative_path) {
+            fs::read_to_string(self.text_cache_path(relative_path)).ok()
+        } else {
+            extract_text(&path)
+        }
+    }
+
+    fn full_path(&self, relative_path: &str) -> Option<PathBuf> {
+        self.roots.iter().find_map(|(prefix, root)| {
+            if prefix.is_empty() {
+                Some(root.join(relative_path))
+            } else {
+                let rest = relative_path.strip_prefix(prefix.as_str())?;
+                if rest.is_empty() {
+                    Some(root.clone())
+                } else {
+                    rest.strip_prefix('/').map(|rest| root.join(rest))
+                }
+            }
+        })
+    }
+
+    fn text_cache_path(&self, relative_path: &str) -> PathBuf {
+        self.index_dir.join("text").join(format!("{:x}.txt", Sha256::digest(relative_path)))
+    }
+
+    // Drops cached text no indexed PDF uses, such as files named by an
+    // earlier version of `text_cache_path`
+    fn remove_stale_text(&self) {
+        let wanted: HashSet<PathBuf> = match self.data.read() {
+            Ok(data) => data.documents.keys()
+                .filter(|path| is_pdf(path))
+                .map(|path| self.text_cache_path(path))
+                .collect(),
+            Err(_) => return,
+        };
+        let Ok(entries) = fs::read_dir(self.index_dir.join("text")) else { return };
+        for entry in entries.flatten() {
+            if !wanted.contains(&entry.path()) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+pub fn is_indexable(name: &str) -> bool {
+    get_file_type_class(name) == "text-file" || (cfg!(feature = "pdf-index") && is_pdf(name))
+}
+
+fn is_pdf(name: &str) -> bool {
+    name.to_lowercase().ends_with(".pdf")
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> u64 {
+    metadata.modified().ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn extract_text(path: &Path) -> Option<String> {
+    if is_pdf(&path.to_string_lossy()) {
+        return extract_pdf_text(path);
+    }
+    let bytes = fs::read(path).ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(feature = "pdf-index")]
+fn extract_pdf_text(path: &Path) -> Option<String> {
+    // The extractor panics on some malformed files
+    std::panic::catch_unwind(|| pdf_extract::extract_text(path).ok()).ok().flatten()
+}
+
+#[cfg(not(feature = "pdf-index"))]
+fn extract_pdf_text(_path: &Path) -> Option<String> {
+    None
+}
+
+// Lowercased runs of letters and digits with their byte offsets
+fn tokenize(text: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        loop {
+            let (start, _) = *chars.peek()?;
+            if !chars.peek()?.1.is_alphanumeric() {
+                chars.next();
+                continue;
+            }
+            let mut end = start;
+            while let Some(&(index, c)) = chars.peek() {
+                if !c.is_alphanumeric() {
+                    break;
+                }
+                end = index + c.len_utf8();
+                chars.next();
+            }
+            let word = &text[start..end];
+            let char_count = word.chars().count();
+            if (MIN_TERM_CHARS..=MAX_TERM_CHARS).contains(&char_count) {
+                return Some((start, word.to_lowercase()));
+            }
+        }
+    })
+}
+
+// A window of text around the first matching word, HTML-escaped, with every
+// matching word in the window highlighted
+fn snippet(text: &str, terms: &[String]) -> String {
+    let is_term = |word: &str| terms.iter().any(|term| term == word);
+    let first = tokenize(text).find(|(_, word)| is_term(word)).map(|(start, _)| start).unwrap_or(0);
+
+    let start = text[..first].char_indices().rev().nth(SNIPPET_CONTEXT_CHARS).map(|(i, _)| i).unwrap_or(0);
+    let end = text[first..].char_indices().nth(SNIPPET_CONTEXT_CHARS * 2).map(|(i, _)| first + i).unwrap_or(text.len());
+    let window = &text[start..end];
+
+    let mut html = String::new();
+    if start > 0 {
+        html.push('…');
+    }
+    let mut position = 0;
+    for (offset, word) in tokenize(window) {
+        if !is_term(&word) {
+            continue;
+        }
+        let word_end = offset + window[offset..].chars().take_while(|c| c.is_alphanumeric()).map(char::len_utf8).sum::<usize>();
+        html.push_str(&escape_html(&window[position..offset]));
+        html.push_str("<mark>");
+        html.push_str(&escape_html(&window[offset..word_end]));
+        html.push_str("</mark>");
+        position = word_end;
+    }
+    html.push_str(&escape_html(&window[position..]));
+    if end < text.len() {
+        html.push('…');
+    }
+    // Runs of whitespace and line breaks read badly in a one-line snippet
+    html.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Rescans on startup and every `rescan_minutes` after that (0 turns the
+// periodic rescan off), and saves pending changes once a minute
+pub fn spawn_index_task() {
+    tokio::spawn(async {
+        let rescan_every = Duration::from_secs(CONTENT_INDEX.rescan_minutes() * 60);
+        let mut last_scan: Option<Instant> = None;
+        let mut interval = tokio::time::interval(Duration::from_secs(FLUSH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let due = last_scan.is_none_or(|t| !rescan_every.is_zero() && t.elapsed() >= rescan_every);
+            let result = tokio::task::spawn_blocking(move || {
+                let counts = if due { CONTENT_INDEX.rescan() } else { (0, 0) };
+                CONTENT_INDEX.flush().map(|_| counts)
+            }).await;
+            if due {
+                last_scan = Some(Instant::now());
+            }
+            match result {
+                Ok(Ok((0, 0))) => {}
+                Ok(Ok((indexed, removed))) => println!("🔎 Content index: {} file(s) indexed, {} removed", indexed, removed),
+                Ok(Err(e)) => eprintln!("❌ Could not save the content index: {}", e),
+                Err(e) => eprintln!("❌ Content index task failed: {}", e),
+            }
+        }
+    });
+}
+
+// Updates the index in the background so the request is not held up
+pub fn reindex_in_background(path: PathBuf) {
+    tokio::task::spawn_blocking(move || CONTENT_INDEX.update_file(&path));
+}
+
+// Every volume, under the name it has in /browse
+fn index_roots() -> Vec<(String, PathBuf)> {
+    VOLUMES.list().iter()
+        .map(|volume| {
+            let prefix = if VOLUMES.is_named() { volume.name.clone() } else { String::new() };
+            (prefix, volume.path.clone())
+        })
+        .collect()
+}
+
+lazy_static::lazy_static! {
+    pub static ref CONTENT_INDEX: ContentIndex = ContentIndex::new(&CONFIG.content_index.dir, index_roots(), CONFIG.content_index.rescan_minutes);
+}
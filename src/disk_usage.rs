@@ -0,0 +1,346 @@
+use crate::config::CONFIG;
+use crate::handlers::utils::is_excluded_entry;
+use crate::visibility::Access;
+use crate::utils::escape_html;
+use crate::volumes::VOLUMES;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Sizes older than this are computed again; nested changes do not touch the
+// parent folder's mtime, so there is nothing cheaper to check
+const SIZE_CACHE_TTL: Duration = Duration::from_secs(300);
+// Drop everything rather than track usage once this many folders are cached
+const MAX_CACHED_FOLDERS: usize = 200_000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderSize {
+    pub size: u64,
+    pub files: u64,
+    pub folders: u64,
+}
+
+impl FolderSize {
+    fn add(&mut self, other: FolderSize) {
+        self.size += other.size;
+        self.files += other.files;
+        self.folders += other.folders;
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEntry {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub size: u64,
+    pub files: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilesystemSpace {
+    pub total: u64,
+    pub free: u64,
+    // Free space usable by unprivileged users
+    pub available: u64,
+}
+
+// Recursive folder sizes, counting only what the browser would show the
+// viewer. Every folder visited along the way is cached for the viewer's role,
+// so drilling down is instant.
+pub struct FolderSizeCache {
+    sizes: Mutex<HashMap<(PathBuf, Access), (FolderSize, Instant)>>,
+}
+
+impl Default for FolderSizeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FolderSizeCache {
+    pub fn new() -> Self {
+        FolderSizeCache {
+            sizes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Only returns sizes that are already known and fresh
+    pub fn cached(&self, dir: &Path, access: &Access) -> Option<FolderSize> {
+        let sizes = self.sizes.lock().ok()?;
+        sizes.get(&(dir.to_path_buf(), access.clone()))
+            .filter(|(_, computed_at)| computed_at.elapsed() < SIZE_CACHE_TTL)
+            .map(|(size, _)| *size)
+    }
+
+    pub fn folder_size(&self, dir: &Path, access: &Access) -> FolderSize {
+        if let Some(size) = self.cached(dir, access) {
+            return size;
+        }
+
+        let mut total = FolderSize::default();
+        for (_, path, is_dir, len) in visible_children(dir, access) {
+            if is_dir {
+                total.add(self.folder_size(&path, access));
+                total.folders += 1;
+            } else {
+                total.size += len;
+                total.files += 1;
+            }
+        }
+
+        if let Ok(mut sizes) = self.sizes.lock() {
+            if sizes.len() >= MAX_CACHED_FOLDERS {
+                sizes.clear();
+            }
+            sizes.insert((dir.to_path_buf(), access.clone()), (total, Instant::now()));
+        }
+        total
+    }
+
+    // Forgets `dir` and everything below it, for every role
+    pub fn invalidate(&self, dir: &Path) {
+        if let Ok(mut sizes) = self.sizes.lock() {
+            sizes.retain(|(path, _), _| !path.starts_with(dir));
+        }
+    }
+
+    // The folder's direct children with their recursive sizes, largest first
+    pub fn usage(&self, dir: &Path, access: &Access) -> (FolderSize, Vec<UsageEntry>) {
+        let (total, entries) = self.children_usage(visible_children(dir, access), access);
+        if let Ok(mut sizes) = self.sizes.lock() {
+            sizes.insert((dir.to_path_buf(), access.clone()), (total, Instant::now()));
+        }
+        (total, entries)
+    }
+
+    // The same for the volume list, whose children are the volumes
+    pub fn volume_usage(&self, access: &Access) -> (FolderSize, Vec<UsageEntry>) {
+        let volumes = VOLUMES.list().iter()
+            .map(|volume| (volume.name.clone(), volume.path.clone(), true, 0))
+            .collect();
+        self.children_usage(volumes, access)
+    }
+
+    fn children_usage(&self, children: Vec<(String, PathBuf, bool, u64)>, access: &Access) -> (FolderSize, Vec<UsageEntry>) {
+        let mut total = FolderSize::default();
+        let mut entries: Vec<UsageEntry> = children.into_iter()
+            .map(|(name, path, is_dir, len)| {
+                if is_dir {
+                    let size = self.folder_size(&path, access);
+                    total.add(size);
+                    total.folders += 1;
+                    UsageEntry { name, kind: "directory", size: size.size, files: size.files }
+                } else {
+                    total.size += len;
+                    total.files += 1;
+                    UsageEntry { name, kind: "file", size: len, files: 1 }
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        (total, entries)
+    }
+}
+
+// (name, path, is_dir, size) of each child the browser would list for
+// `access`. Symlinks are left out so nothing is counted twice and link loops
+// cannot recurse.
+fn visible_children(dir: &Path, access: &Access) -> Vec<(String, PathBuf, bool, u64)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries.filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = entry.path();
+            let metadata = entry.metadata().ok()?;
+            if is_excluded_entry(&path, metadata.is_dir(), access) {
+                return None;
+            }
+            if metadata.is_dir() {
+                Some((name, path, true, 0))
+            } else if metadata.is_file() {
+                Some((name, path, false, metadata.len()))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+pub fn filesystem_space(path: &Path) -> Option<FilesystemSpace> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is NUL-terminated and `stats` is a valid out pointer
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    let block_size = stats.f_frsize as u64;
+    Some(FilesystemSpace {
+        total: stats.f_blocks as u64 * block_size,
+        free: stats.f_bfree as u64 * block_size,
+        available: stats.f_bavail as u64 * block_size,
+    })
+}
+
+#[cfg(not(unix))]
+pub fn filesystem_space(_path: &Path) -> Option<FilesystemSpace> {
+    None
+}
+
+// `folder` is the decoded folder relative to the served root; the numbers
+// are fetched from /api/usage once the page has loaded
+pub fn generate_usage_html(folder: &str) -> String {
+    let folder = folder.trim_matches('/');
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Disk Usage - {app_title}</title>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <style>
+        body {{ font-family: Arial, sans-serif; margin: 0; padding: 20px; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; }}
+        .container {{ max-width: 1100px; margin: 0 auto; background: rgba(255, 255, 255, 0.95); border-radius: 16px; box-shadow: 0 8px 32px rgba(0,0,0,0.15); overflow: hidden; }}
+        .usage-header {{ display: flex; align-items: center; justify-content: space-between; gap: 16px; padding: 16px 24px; border-bottom: 1px solid #e5e7eb; }}
+        .usage-title {{ font-size: 18px; font-weight: 700; color: #1f2937; word-break: break-all; }}
+        .usage-meta {{ font-size: 12px; color: #6b7280; margin-top: 4px; }}
+        .usage-actions {{ display: flex; gap: 8px; flex-shrink: 0; }}
+        .action-btn {{ font-size: 13px; padding: 6px 12px; border: 1px solid #2563eb; border-radius: 6px; text-decoration: none; color: #2563eb; background: white; font-weight: 500; cursor: pointer; transition: all 0.2s ease; }}
+        .action-btn:hover {{ background: #2563eb; color: white; }}
+        .volume {{ padding: 16px 24px; border-bottom: 1px solid #e5e7eb; font-size: 13px; color: #374151; }}
+        .volume-bar {{ height: 10px; background: #e5e7eb; border-radius: 5px; overflow: hidden; margin-top: 8px; }}
+        .volume-bar div {{ height: 100%; background: linear-gradient(90deg, #2563eb, #f97316); }}
+        table {{ width: 100%; border-collapse: collapse; font-size: 14px; }}
+        th, td {{ padding: 10px 16px; text-align: left; border-bottom: 1px solid #f3f4f6; }}
+        th {{ color: #6b7280; font-weight: 600; font-size: 12px; text-transform: uppercase; cursor: pointer; user-select: none; }}
+        td a {{ color: #1f2937; text-decoration: none; font-weight: 500; word-break: break-all; }}
+        td a:hover {{ color: #2563eb; }}
+        .share {{ width: 30%; }}
+        .share-bar {{ height: 8px; background: #f97316; border-radius: 4px; min-width: 1px; }}
+        .empty {{ padding: 60px; text-align: center; color: #6b7280; font-size: 16px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="usage-header">
+            <div>
+                <div class="usage-title">📊 Disk usage of /{folder}</div>
+                <div class="usage-meta" id="summary">Calculating folder sizes...</div>
+            </div>
+            <div class="usage-actions">
+                <a href="/browse/{folder_url}" class="action-btn">← Back to files</a>
+                <button class="action-btn" onclick="load(true)">Recalculate</button>
+            </div>
+        </div>
+        <div class="volume" id="volume" hidden></div>
+        <table id="usage" hidden>
+            <thead>
+                <tr>
+                    <th onclick="sortBy('name')">Name</th>
+                    <th onclick="sortBy('size')">Size</th>
+                    <th onclick="sortBy('files')">Files</th>
+                    <th class="share">Share of folder</th>
+                </tr>
+            </thead>
+            <tbody id="rows"></tbody>
+        </table>
+        <div class="empty" id="empty" hidden>This folder is empty</div>
+    </div>
+    <script>
+        const folder = {folder_json};
+        let entries = [];
+        let total = 0;
+        let sortKey = 'size';
+        let descending = true;
+
+        function escapeHtml(text) {{
+            const div = document.createElement('div');
+            div.textContent = text;
+            return div.innerHTML;
+        }}
+
+        function encodePath(path) {{
+            return path.split('/').map(encodeURIComponent).join('/');
+        }}
+
+        function formatSize(bytes) {{
+            const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+            let size = bytes, unit = 0;
+            while (size >= 1024 && unit < units.length - 1) {{ size /= 1024; unit++; }}
+            return (unit === 0 ? size : size.toFixed(1)) + ' ' + units[unit];
+        }}
+
+        function sortBy(key) {{
+            descending = key === sortKey ? !descending : key !== 'name';
+            sortKey = key;
+            render();
+        }}
+
+        function render() {{
+            const sorted = entries.slice().sort((a, b) => {{
+                const order = sortKey === 'name' ? a.name.localeCompare(b.name) : a[sortKey] - b[sortKey];
+                return descending ? -order : order;
+            }});
+            document.getElementById('rows').innerHTML = sorted.map(entry => {{
+                const path = (folder ? folder + '/' : '') + entry.name;
+                const link = entry.type === 'directory'
+                    ? `<a href="/usage/${{encodePath(path)}}">📁 ${{escapeHtml(entry.name)}}</a>`
+                    : `<a href="/file/${{encodePath(path)}}">📄 ${{escapeHtml(entry.name)}}</a>`;
+                const share = total ? entry.size / total * 100 : 0;
+                return `<tr><td>${{link}}</td><td>${{formatSize(entry.size)}}</td><td>${{entry.files}}</td>`
+                    + `<td class="share"><div class="share-bar" style="width: ${{share.toFixed(1)}}%" title="${{share.toFixed(1)}}%"></div></td></tr>`;
+            }}).join('');
+            document.getElementById('usage').hidden = !entries.length;
+            document.getElementById('empty').hidden = !!entries.length;
+        }}
+
+        async function load(refresh) {{
+            document.getElementById('summary').textContent = 'Calculating folder sizes...';
+            const response = await fetch('/api/usage/' + encodePath(folder) + (refresh ? '?refresh=1' : ''));
+            const result = await response.json().catch(() => ({{}}));
+            if (!response.ok) {{
+                document.getElementById('summary').textContent = 'Could not calculate sizes: ' + (result.error || response.status);
+                return;
+            }}
+            entries = result.entries;
+            total = result.size;
+            document.getElementById('summary').textContent =
+                `${{formatSize(result.size)}} in ${{result.files}} file(s) and ${{result.folders}} folder(s)`;
+            const volume = document.getElementById('volume');
+            if (result.filesystem) {{
+                const fs = result.filesystem;
+                const used = fs.total - fs.free;
+                volume.innerHTML = `Volume: ${{formatSize(used)}} used of ${{formatSize(fs.total)}} · ${{formatSize(fs.available)}} free`
+                    + `<div class="volume-bar"><div style="width: ${{fs.total ? (used / fs.total * 100).toFixed(1) : 0}}%"></div></div>`;
+                volume.hidden = false;
+            }}
+            render();
+        }}
+
+        load(false);
+    </script>
+</body>
+</html>
+"#,
+        folder = escape_html(folder),
+        folder_url = folder.split('/').map(|part| urlencoding::encode(part).into_owned()).collect::<Vec<_>>().join("/"),
+        folder_json = serde_json::to_string(folder).unwrap_or_default().replace("</", "<\\/"),
+        app_title = escape_html(&CONFIG.branding.title),
+    )
+}
+
+lazy_static::lazy_static! {
+    pub static ref FOLDER_SIZES: FolderSizeCache = FolderSizeCache::new();
+}
@@ -0,0 +1,440 @@
+use crate::config::CONFIG;
+use crate::handlers::utils::is_excluded_entry;
+use crate::visibility::Access;
+use crate::jobs::{JobHandle, ProgressReader};
+use crate::utils::escape_html;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+// Files that differ usually differ early, so this much is hashed first
+const PARTIAL_HASH_BYTES: u64 = 64 * 1024;
+// Only the most recent reports are kept in memory
+const MAX_REPORTS: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateFile {
+    // Relative to the served root, as used in /browse URLs
+    pub path: String,
+    pub modified: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub hash: String,
+    pub files: Vec<DuplicateFile>,
+    // Space freed by keeping only one copy
+    pub reclaimable: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateReport {
+    pub folder: String,
+    pub scanned_files: u64,
+    pub duplicate_files: u64,
+    pub reclaimable: u64,
+    pub groups: Vec<DuplicateGroup>,
+}
+
+struct Candidate {
+    path: PathBuf,
+    relative_path: String,
+    size: u64,
+    modified: Option<u64>,
+}
+
+// Groups identical files below `roots` (each a folder with its path as
+// clients see it) in three passes: by size, by a hash of the first 64 KB, and
+// by a hash of the whole file. Empty files are ignored and symlinks are never
+// followed. `relative_root` is the folder the report is for.
+pub fn find_duplicates(roots: &[(PathBuf, String)], relative_root: &str, access: &Access, job: &JobHandle) -> Result<DuplicateReport, String> {
+    job.set_current("Listing files");
+    let mut by_size: HashMap<u64, Vec<Candidate>> = HashMap::new();
+    let mut scanned_files = 0;
+    // Hard links to one file are the same data, not copies of it
+    let mut seen_files = HashSet::new();
+    let mut stack = roots.to_vec();
+    while let Some((dir, relative_dir)) = stack.pop() {
+        if job.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                job.add_error(format!("{}: {}", relative_dir, e));
+                continue;
+            }
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = entry.path();
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            if is_excluded_entry(&path, metadata.is_dir(), access) {
+                continue;
+            }
+            let relative_path = if relative_dir.is_empty() { name } else { format!("{}/{}", relative_dir, name) };
+            if metadata.is_dir() {
+                stack.push((path, relative_path));
+            } else if metadata.is_file() && metadata.len() > 0 {
+                if file_id(&metadata).is_some_and(|id| !seen_files.insert(id)) {
+                    continue;
+                }
+                scanned_files += 1;
+                by_size.entry(metadata.len()).or_default().push(Candidate {
+                    path,
+                    relative_path,
+                    size: metadata.len(),
+                    modified: metadata.modified().ok()
+                        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs()),
+                });
+            }
+        }
+    }
+
+    let same_size: Vec<Vec<Candidate>> = by_size.into_values().filter(|group| group.len() > 1).collect();
+    let files_total: usize = same_size.iter().map(Vec::len).sum();
+    let bytes_total: u64 = same_size.iter().flatten()
+        .map(|candidate| if candidate.size > PARTIAL_HASH_BYTES { candidate.size + PARTIAL_HASH_BYTES } else { candidate.size })
+        .sum();
+    job.set_totals(files_total as u64, bytes_total);
+
+    let mut groups = Vec::new();
+    for group in same_size {
+        // Small files are read whole by the partial pass already
+        let small = group[0].size <= PARTIAL_HASH_BYTES;
+        for partial_group in group_by_hash(group, Some(PARTIAL_HASH_BYTES), job)? {
+            let full_groups = if small {
+                vec![partial_group]
+            } else {
+                group_by_hash(partial_group.1, None, job)?
+            };
+            for (hash, mut files) in full_groups {
+                files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+                let size = files[0].size;
+                groups.push(DuplicateGroup {
+                    size,
+                    hash,
+                    reclaimable: size * (files.len() as u64 - 1),
+                    files: files.into_iter()
+                        .map(|candidate| DuplicateFile { path: candidate.relative_path, modified: candidate.modified })
+                        .collect(),
+                });
+            }
+        }
+    }
+    groups.sort_by(|a, b| b.reclaimable.cmp(&a.reclaimable).then_with(|| a.files[0].path.cmp(&b.files[0].path)));
+
+    Ok(DuplicateReport {
+        folder: relative_root.trim_matches('/').to_string(),
+        scanned_files,
+        duplicate_files: groups.iter().map(|group| group.files.len() as u64 - 1).sum(),
+        reclaimable: groups.iter().map(|group| group.reclaimable).sum(),
+        groups,
+    })
+}
+
+// Device and inode, which every hard link to a file shares
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+// Splits candidates by the SHA-256 of their first `limit` bytes (or all of
+// them), keeping only the hashes shared by more than one file
+fn group_by_hash(candidates: Vec<Candidate>, limit: Option<u64>, job: &JobHandle) -> Result<Vec<(String, Vec<Candidate>)>, String> {
+    let mut by_hash: HashMap<String, Vec<Candidate>> = HashMap::new();
+    for candidate in candidates {
+        job.set_current(&candidate.relative_path);
+        match hash_file(&candidate.path, limit, job) {
+            Ok(hash) => by_hash.entry(hash).or_default().push(candidate),
+            Err(_) if job.is_cancelled() => return Err("Cancelled".to_string()),
+            Err(e) => job.add_error(format!("{}: {}", candidate.relative_path, e)),
+        }
+        if limit.is_some() {
+            job.file_done();
+        }
+    }
+    Ok(by_hash.into_iter().filter(|(_, group)| group.len() > 1).collect())
+}
+
+fn hash_file(path: &Path, limit: Option<u64>, job: &JobHandle) -> io::Result<String> {
+    let file = File::open(path)?;
+    let mut reader = ProgressReader::new(file.take(limit.unwrap_or(u64::MAX)), job);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Finished reports, keyed by the id of the job that produced them, along
+// with the user who started it
+pub struct DuplicateReports {
+    reports: Mutex<VecDeque<(String, String, DuplicateReport)>>,
+}
+
+impl Default for DuplicateReports {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DuplicateReports {
+    pub fn new() -> Self {
+        DuplicateReports {
+            reports: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn insert(&self, job_id: String, owner: String, report: DuplicateReport) {
+        if let Ok(mut reports) = self.reports.lock() {
+            if reports.len() >= MAX_REPORTS {
+                reports.pop_front();
+            }
+            reports.push_back((job_id, owner, report));
+        }
+    }
+
+    // Reports of scans started by another user are not found
+    pub fn get(&self, job_id: &str, owner: &str) -> Option<DuplicateReport> {
+        let reports = self.reports.lock().ok()?;
+        reports.iter()
+            .find(|(id, report_owner, _)| id == job_id && report_owner == owner)
+            .map(|(_, _, report)| report.clone())
+    }
+}
+
+// `folder` is the decoded folder to scan, relative to the served root
+pub fn generate_duplicates_html(folder: &str) -> String {
+    let folder = folder.trim_matches('/');
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Duplicate Files - {app_title}</title>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <style>
+        body {{ font-family: Arial, sans-serif; margin: 0; padding: 20px; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; }}
+        .container {{ max-width: 1100px; margin: 0 auto; background: rgba(255, 255, 255, 0.95); border-radius: 16px; box-shadow: 0 8px 32px rgba(0,0,0,0.15); overflow: hidden; }}
+        .dup-header {{ display: flex; align-items: center; justify-content: space-between; gap: 16px; padding: 16px 24px; border-bottom: 1px solid #e5e7eb; }}
+        .dup-title {{ font-size: 18px; font-weight: 700; color: #1f2937; word-break: break-all; }}
+        .dup-meta {{ font-size: 12px; color: #6b7280; margin-top: 4px; }}
+        .dup-meta.error {{ color: #dc2626; }}
+        .dup-actions {{ display: flex; gap: 8px; flex-shrink: 0; flex-wrap: wrap; justify-content: flex-end; }}
+        .action-btn {{ font-size: 13px; padding: 6px 12px; border: 1px solid #2563eb; border-radius: 6px; text-decoration: none; color: #2563eb; background: white; font-weight: 500; cursor: pointer; transition: all 0.2s ease; }}
+        .action-btn:hover {{ background: #2563eb; color: white; }}
+        .action-btn.danger {{ border-color: #dc2626; color: #dc2626; }}
+        .action-btn.danger:hover {{ background: #dc2626; color: white; }}
+        .action-btn:disabled {{ opacity: 0.5; cursor: default; }}
+        .progress {{ height: 6px; background: #e5e7eb; }}
+        .progress div {{ height: 100%; width: 0; background: #2563eb; transition: width 0.3s ease; }}
+        .group {{ border-bottom: 1px solid #e5e7eb; padding: 12px 24px; }}
+        .group-title {{ font-size: 13px; font-weight: 600; color: #374151; margin-bottom: 6px; }}
+        .group-file {{ display: flex; align-items: center; gap: 10px; padding: 4px 0; font-size: 14px; }}
+        .group-file a {{ color: #1f2937; text-decoration: none; word-break: break-all; flex: 1; }}
+        .group-file a:hover {{ color: #2563eb; }}
+        .group-file .when {{ color: #6b7280; font-size: 12px; white-space: nowrap; }}
+        .empty {{ padding: 60px; text-align: center; color: #6b7280; font-size: 16px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="dup-header">
+            <div>
+                <div class="dup-title">🧬 Duplicate files in /{folder}</div>
+                <div class="dup-meta" id="status">Compares file contents in this folder and everything below it.</div>
+            </div>
+            <div class="dup-actions">
+                <a href="/browse/{folder_url}" class="action-btn">← Back to files</a>
+                <button class="action-btn" id="scanBtn" onclick="startScan()">Scan</button>
+                <button class="action-btn" id="cancelBtn" onclick="cancelScan()" hidden>Cancel</button>
+                <button class="action-btn" id="keepOldestBtn" onclick="selectAllButOne(true)" hidden>Keep oldest</button>
+                <button class="action-btn" id="keepNewestBtn" onclick="selectAllButOne(false)" hidden>Keep newest</button>
+                <button class="action-btn danger" id="trashBtn" onclick="trashSelected()" hidden>Move selected to trash</button>
+            </div>
+        </div>
+        <div class="progress"><div id="bar"></div></div>
+        <div id="groups"></div>
+    </div>
+    <script>
+        const folder = {folder_json};
+        let jobId = null;
+
+        function setStatus(text, isError) {{
+            const status = document.getElementById('status');
+            status.textContent = text;
+            status.className = 'dup-meta' + (isError ? ' error' : '');
+        }}
+
+        function escapeHtml(text) {{
+            const div = document.createElement('div');
+            div.textContent = text;
+            return div.innerHTML;
+        }}
+
+        function encodePath(path) {{
+            return path.split('/').map(encodeURIComponent).join('/');
+        }}
+
+        function formatSize(bytes) {{
+            const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+            let size = bytes, unit = 0;
+            while (size >= 1024 && unit < units.length - 1) {{ size /= 1024; unit++; }}
+            return (unit === 0 ? size : size.toFixed(1)) + ' ' + units[unit];
+        }}
+
+        function setRunning(running) {{
+            document.getElementById('scanBtn').disabled = running;
+            document.getElementById('cancelBtn').hidden = !running;
+        }}
+
+        async function startScan() {{
+            document.getElementById('groups').innerHTML = '';
+            ['keepOldestBtn', 'keepNewestBtn', 'trashBtn'].forEach(id => document.getElementById(id).hidden = true);
+            const response = await fetch('/api/duplicates/scan', {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify({{ path: folder }})
+            }});
+            const result = await response.json().catch(() => ({{}}));
+            if (!response.ok) {{
+                setStatus('Could not start the scan: ' + (result.error || response.status), true);
+                return;
+            }}
+            jobId = result.jobId;
+            setRunning(true);
+            poll();
+        }}
+
+        function cancelScan() {{
+            if (jobId) fetch('/api/jobs/' + jobId + '/cancel', {{ method: 'POST' }});
+        }}
+
+        async function poll() {{
+            const response = await fetch('/api/jobs/' + jobId);
+            if (!response.ok) {{
+                setStatus('Scan status unavailable', true);
+                setRunning(false);
+                return;
+            }}
+            const status = await response.json();
+            const percent = status.bytesTotal ? Math.min(100, status.bytesDone / status.bytesTotal * 100) : 0;
+            document.getElementById('bar').style.width = percent + '%';
+            if (status.state === 'running') {{
+                setStatus(status.filesTotal
+                    ? `Comparing ${{status.filesDone}} of ${{status.filesTotal}} same-size files · ${{status.currentItem}}`
+                    : status.currentItem || 'Starting...');
+                setTimeout(poll, 500);
+                return;
+            }}
+            setRunning(false);
+            if (status.state !== 'completed') {{
+                setStatus(status.message, status.state === 'failed');
+                return;
+            }}
+            const reportResponse = await fetch('/api/duplicates/' + jobId);
+            const result = await reportResponse.json().catch(() => ({{}}));
+            if (!reportResponse.ok) {{
+                setStatus('Report unavailable: ' + (result.error || reportResponse.status), true);
+                return;
+            }}
+            render(result.report, status.errors);
+        }}
+
+        function render(report, errors) {{
+            const problems = errors.length ? ` · ${{errors.length}} file(s) could not be read` : '';
+            setStatus(`${{report.groups.length}} group(s) of duplicates in ${{report.scannedFiles}} files · `
+                + `${{formatSize(report.reclaimable)}} reclaimable` + problems);
+            const container = document.getElementById('groups');
+            if (!report.groups.length) {{
+                container.innerHTML = '<div class="empty">No duplicate files found</div>';
+                return;
+            }}
+            container.innerHTML = report.groups.map((group, index) => `
+                <div class="group">
+                    <div class="group-title">${{group.files.length}} copies of ${{formatSize(group.size)}} · ${{formatSize(group.reclaimable)}} reclaimable</div>
+                    ${{group.files.map(file => `
+                        <label class="group-file">
+                            <input type="checkbox" class="select-file" data-group="${{index}}" data-modified="${{file.modified || 0}}" value="${{escapeHtml(file.path)}}">
+                            <a href="/file/${{encodePath(file.path)}}" target="_blank">/${{escapeHtml(file.path)}}</a>
+                            <span class="when">${{file.modified ? new Date(file.modified * 1000).toLocaleString() : ''}}</span>
+                        </label>`).join('')}}
+                </div>`).join('');
+            ['keepOldestBtn', 'keepNewestBtn', 'trashBtn'].forEach(id => document.getElementById(id).hidden = false);
+        }}
+
+        // Ticks every copy in each group except the oldest (or newest) one
+        function selectAllButOne(keepOldest) {{
+            const groups = new Map();
+            document.querySelectorAll('.select-file').forEach(box => {{
+                if (!groups.has(box.dataset.group)) groups.set(box.dataset.group, []);
+                groups.get(box.dataset.group).push(box);
+            }});
+            for (const boxes of groups.values()) {{
+                boxes.sort((a, b) => Number(a.dataset.modified) - Number(b.dataset.modified));
+                const keep = keepOldest ? boxes[0] : boxes[boxes.length - 1];
+                boxes.forEach(box => box.checked = box !== keep);
+            }}
+        }}
+
+        async function trashSelected() {{
+            const boxes = Array.from(document.querySelectorAll('.select-file:checked'));
+            if (!boxes.length) return;
+            // Refuse to trash every copy of a file by accident
+            const groups = new Set(boxes.map(box => box.dataset.group));
+            const removesEveryCopy = Array.from(groups).some(group =>
+                Array.from(document.querySelectorAll(`.select-file[data-group="${{group}}"]`)).every(box => box.checked));
+            if (removesEveryCopy
+                && !confirm('Every copy in at least one group is selected, so that file will be gone from the folder. Continue?')) return;
+            if (!confirm(`Move ${{boxes.length}} file(s) to the trash?`)) return;
+            const response = await fetch('/api/delete', {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify({{ paths: boxes.map(box => box.value) }})
+            }});
+            const result = await response.json().catch(() => ({{}}));
+            if (!response.ok || (result.errors && result.errors.length)) {{
+                alert('Some files could not be moved to the trash:\n' + (result.error || result.errors.join('\n')));
+            }} else {{
+                boxes.forEach(box => box.closest('.group-file').remove());
+            }}
+            setStatus(`Moved ${{result.trashed || 0}} file(s) to the trash. Scan again to refresh the report.`);
+        }}
+    </script>
+</body>
+</html>
+"#,
+        folder = escape_html(folder),
+        folder_url = folder.split('/').map(|part| urlencoding::encode(part).into_owned()).collect::<Vec<_>>().join("/"),
+        folder_json = serde_json::to_string(folder).unwrap_or_default().replace("</", "<\\/"),
+        app_title = escape_html(&CONFIG.branding.title),
+    )
+}
+
+lazy_static::lazy_static! {
+    pub static ref DUPLICATE_REPORTS: DuplicateReports = DuplicateReports::new();
+}
@@ -0,0 +1,139 @@
+use crate::config::CONFIG;
+use crate::utils::{escape_html, format_file_size};
+use crate::viewer::is_source_file;
+use sha2::{Digest, Sha256};
+
+pub fn is_editable_file(filename: &str) -> bool {
+    is_source_file(filename)
+}
+
+// Strong ETag derived from the file contents, so two saves within the same
+// mtime tick are still told apart
+pub fn content_etag(contents: &[u8]) -> String {
+    format!("\"{:x}\"", Sha256::digest(contents))
+}
+
+// `url_path` is the still-encoded path of the file relative to the served root
+pub fn generate_editor_html(file_name: &str, contents: &str, url_path: &str, etag: &str) -> String {
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Edit {name} - {app_title}</title>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <style>
+        body {{ font-family: Arial, sans-serif; margin: 0; padding: 20px; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; box-sizing: border-box; }}
+        .container {{ max-width: 1200px; margin: 0 auto; background: rgba(255, 255, 255, 0.95); border-radius: 16px; box-shadow: 0 8px 32px rgba(0,0,0,0.15); overflow: hidden; display: flex; flex-direction: column; height: calc(100vh - 40px); }}
+        .editor-header {{ display: flex; align-items: center; justify-content: space-between; gap: 16px; padding: 16px 24px; border-bottom: 1px solid #e5e7eb; }}
+        .editor-title {{ font-size: 18px; font-weight: 700; color: #1f2937; word-break: break-all; }}
+        .editor-status {{ font-size: 12px; color: #6b7280; margin-top: 4px; }}
+        .editor-status.dirty {{ color: #d97706; }}
+        .editor-status.error {{ color: #dc2626; }}
+        .editor-actions {{ display: flex; gap: 8px; flex-shrink: 0; }}
+        .action-btn {{ font-size: 13px; padding: 6px 12px; border: 1px solid #2563eb; border-radius: 6px; text-decoration: none; color: #2563eb; background: white; font-weight: 500; cursor: pointer; transition: all 0.2s ease; }}
+        .action-btn:hover {{ background: #2563eb; color: white; }}
+        .action-btn.primary {{ background: #2563eb; color: white; }}
+        .action-btn:disabled {{ opacity: 0.5; cursor: default; }}
+        textarea {{ flex: 1; width: 100%; box-sizing: border-box; border: none; outline: none; resize: none; padding: 16px 24px; font-family: Consolas, Menlo, monospace; font-size: 13px; line-height: 1.5; tab-size: 4; white-space: pre; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="editor-header">
+            <div>
+                <div class="editor-title">✏️ {name}</div>
+                <div class="editor-status" id="status">{size} · Saved</div>
+            </div>
+            <div class="editor-actions">
+                <a href="/browse{parent}" class="action-btn">← Back</a>
+                <a href="/view{path}" class="action-btn">View</a>
+                <a href="/versions{path}" class="action-btn">Versions</a>
+                <button class="action-btn primary" id="saveBtn" onclick="save(false)">Save</button>
+            </div>
+        </div>
+        <!-- The parser drops one newline right after the tag, so give it one to keep -->
+        <textarea id="editor" spellcheck="false">
+{contents}</textarea>
+    </div>
+    <script>
+        const editor = document.getElementById('editor');
+        const status = document.getElementById('status');
+        let etag = {etag_json};
+        let savedText = editor.value;
+        let saving = false;
+
+        function setStatus(text, kind) {{
+            status.textContent = text;
+            status.className = 'editor-status' + (kind ? ' ' + kind : '');
+        }}
+
+        editor.addEventListener('input', () => {{
+            if (editor.value !== savedText) setStatus('Unsaved changes', 'dirty');
+            else setStatus('Saved');
+        }});
+
+        // Tab inserts a tab instead of leaving the textarea
+        editor.addEventListener('keydown', e => {{
+            if (e.key === 'Tab' && !e.shiftKey) {{
+                e.preventDefault();
+                document.execCommand('insertText', false, '\t');
+            }}
+        }});
+
+        document.addEventListener('keydown', e => {{
+            if ((e.ctrlKey || e.metaKey) && e.key === 's') {{
+                e.preventDefault();
+                save(false);
+            }}
+        }});
+
+        window.addEventListener('beforeunload', e => {{
+            if (editor.value !== savedText) e.preventDefault();
+        }});
+
+        // `force` replaces whatever is on the server after a conflict
+        async function save(force) {{
+            if (saving) return;
+            saving = true;
+            document.getElementById('saveBtn').disabled = true;
+            setStatus('Saving...');
+            const text = editor.value;
+            let retry = false;
+            try {{
+                const response = await fetch('/api/file{path}', {{
+                    method: 'PUT',
+                    headers: {{ 'Content-Type': 'text/plain; charset=utf-8', 'If-Match': force ? '*' : etag }},
+                    body: text
+                }});
+                const result = await response.json().catch(() => ({{}}));
+                if (response.ok) {{
+                    etag = result.etag;
+                    savedText = text;
+                    setStatus(editor.value === savedText ? 'Saved' : 'Unsaved changes', editor.value === savedText ? '' : 'dirty');
+                }} else if (response.status === 409) {{
+                    setStatus('Changed on the server since you opened it', 'error');
+                    retry = confirm('Someone else changed this file since you opened it.\n\nOK replaces their version with yours. Cancel keeps your text here so you can copy it before reloading.');
+                }} else {{
+                    setStatus('Save failed: ' + (result.error || response.status), 'error');
+                }}
+            }} catch (err) {{
+                setStatus('Save failed: ' + err.message, 'error');
+            }} finally {{
+                saving = false;
+                document.getElementById('saveBtn').disabled = false;
+            }}
+            if (retry) save(true);
+        }}
+    </script>
+</body>
+</html>
+"#,
+        name = escape_html(file_name),
+        size = format_file_size(contents.len() as u64),
+        parent = url_path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or(""),
+        path = url_path,
+        contents = escape_html(contents),
+        etag_json = serde_json::to_string(etag).unwrap_or_default(),
+        app_title = escape_html(&CONFIG.branding.title),
+    )
+}
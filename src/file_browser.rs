@@ -1,13 +1,367 @@
-use crate::utils::format_file_size;
+use crate::archive::archive_kind;
+use crate::disk_usage::FOLDER_SIZES;
+use crate::config::CONFIG;
+use crate::editor::is_editable_file;
+use crate::thumbnails::{DEFAULT_THUMBNAIL_SIZE, is_thumbnailable};
+use crate::resumable_upload::UPLOAD_CHECKSUM_JS;
+use crate::utils::{escape_html, format_file_size};
+use crate::viewer::{is_source_file, MARKDOWN_CSS};
+use crate::handlers::utils::query_param;
+use crate::visibility::Access;
+use crate::volumes::VOLUMES;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
+#[derive(Debug, Clone, Default)]
 pub struct FileEntry {
     pub name: String,
     pub path: PathBuf,
     pub is_dir: bool,
     pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    // Permission bits only (e.g. 0o644); None off Unix and inside archives
+    pub mode: Option<u32>,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    pub is_symlink: bool,
+    pub symlink_target: Option<PathBuf>,
+    pub mime: Option<String>,
+}
+
+impl FileEntry {
+    // Reads the metadata of a file or folder on disk. Symlinks are followed for
+    // the type, size and times, and fall back to the link itself when broken.
+    pub fn from_path(path: &Path) -> Result<FileEntry, io::Error> {
+        let link_metadata = fs::symlink_metadata(path)?;
+        let is_symlink = link_metadata.file_type().is_symlink();
+        let metadata = if is_symlink {
+            fs::metadata(path).unwrap_or(link_metadata)
+        } else {
+            link_metadata
+        };
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let is_dir = metadata.is_dir();
+        let (owner, group) = owner_and_group(&metadata);
+
+        Ok(FileEntry {
+            mime: if is_dir { None } else { Some(guess_mime(&name)) },
+            name,
+            path: path.to_path_buf(),
+            is_dir,
+            size: if is_dir { None } else { Some(metadata.len()) },
+            modified: metadata.modified().ok(),
+            created: metadata.created().ok(),
+            accessed: metadata.accessed().ok(),
+            mode: unix_mode(&metadata),
+            owner,
+            group,
+            is_symlink,
+            symlink_target: if is_symlink { fs::read_link(path).ok() } else { None },
+        })
+    }
+
+    // Entries that only exist inside archives have no disk metadata
+    pub fn virtual_entry(name: String, path: PathBuf, is_dir: bool, size: Option<u64>) -> FileEntry {
+        FileEntry {
+            mime: if is_dir { None } else { Some(guess_mime(&name)) },
+            name,
+            path,
+            is_dir,
+            size,
+            ..FileEntry::default()
+        }
+    }
+}
+
+fn guess_mime(name: &str) -> String {
+    mime_guess::from_path(name).first_or_octet_stream().to_string()
+}
+
+pub const DEFAULT_PAGE_SIZE: usize = 200;
+pub const MAX_PAGE_SIZE: usize = 1000;
+// Drop everything rather than track usage once this many listings are cached
+const MAX_CACHED_LISTINGS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortKey {
+    Name,
+    Size,
+    Type,
+    Modified,
+}
+
+// Sorting, filtering and paging of a folder listing, read from the
+// `sort`, `order`, `q`, `offset` and `limit` query parameters
+#[derive(Debug, Clone)]
+pub struct ListingOptions {
+    pub sort: SortKey,
+    pub descending: bool,
+    pub filter: Option<String>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl ListingOptions {
+    // `default_limit` of None returns everything unless the client asks for a page
+    pub fn from_query(query: Option<&str>, default_limit: Option<usize>) -> Self {
+        let sort = match query_param(query, "sort").as_deref() {
+            Some("size") => SortKey::Size,
+            Some("type") => SortKey::Type,
+            Some("modified") => SortKey::Modified,
+            _ => SortKey::Name,
+        };
+        let limit = query_param(query, "limit")
+            .and_then(|limit| limit.parse::<usize>().ok())
+            .or(default_limit)
+            .map(|limit| limit.clamp(1, MAX_PAGE_SIZE));
+
+        ListingOptions {
+            sort,
+            descending: query_param(query, "order").as_deref() == Some("desc"),
+            filter: query_param(query, "q").map(|q| q.trim().to_lowercase()).filter(|q| !q.is_empty()),
+            offset: query_param(query, "offset").and_then(|offset| offset.parse().ok()).unwrap_or(0),
+            limit,
+        }
+    }
+}
+
+pub struct ListingPage {
+    pub entries: Vec<FileEntry>,
+    // Entries matching the filter, across all pages
+    pub total: usize,
+    pub next_offset: Option<usize>,
+}
+
+// Filters by name, sorts with folders first and cuts out the requested page
+pub fn paginate_entries(entries: Vec<FileEntry>, options: &ListingOptions) -> ListingPage {
+    page_of(&sort_entries(entries, options), options)
+}
+
+// Filters by name and sorts with folders first, leaving paging aside
+pub fn sort_entries(entries: Vec<FileEntry>, options: &ListingOptions) -> Vec<FileEntry> {
+    let mut entries: Vec<FileEntry> = match &options.filter {
+        Some(filter) => entries.into_iter().filter(|entry| entry.name.to_lowercase().contains(filter)).collect(),
+        None => entries,
+    };
+
+    entries.sort_by(|a, b| {
+        let order = match options.sort {
+            SortKey::Name => Ordering::Equal,
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Type => file_extension(&a.name).cmp(&file_extension(&b.name)),
+            SortKey::Modified => a.modified.cmp(&b.modified),
+        }
+        .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        .then_with(|| a.name.cmp(&b.name));
+
+        let order = if options.descending { order.reverse() } else { order };
+        b.is_dir.cmp(&a.is_dir).then(order)
+    });
+    entries
+}
+
+// Cuts the requested page out of a listing that is already sorted
+pub fn page_of(entries: &[FileEntry], options: &ListingOptions) -> ListingPage {
+    let total = entries.len();
+    let start = options.offset.min(total);
+    let end = match options.limit {
+        Some(limit) => start.saturating_add(limit).min(total),
+        None => total,
+    };
+
+    ListingPage {
+        entries: entries[start..end].to_vec(),
+        total,
+        next_offset: (end < total).then_some(end),
+    }
+}
+
+// A folder, the viewer's role and the order and filter it was listed with
+type ListingKey = (PathBuf, Access, SortKey, bool, Option<String>);
+// The folder's mtime when it was listed, and the sorted entries
+type Listing = (Option<SystemTime>, Arc<Vec<FileEntry>>);
+
+// Sorted folder listings, so the later pages of an infinite scroll are cut
+// out of the listing the first page was, rather than reading and sorting the
+// whole folder again. A listing is reused only while the folder's mtime is
+// unchanged; sizes and times of files changed in place show up on the next
+// first page, which always reads the folder afresh.
+pub struct ListingCache {
+    listings: Mutex<HashMap<ListingKey, Listing>>,
+}
+
+impl Default for ListingCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ListingCache {
+    pub fn new() -> Self {
+        ListingCache {
+            listings: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn key(dir: &Path, options: &ListingOptions, access: &Access) -> ListingKey {
+        (dir.to_path_buf(), access.clone(), options.sort, options.descending, options.filter.clone())
+    }
+
+    pub fn get(&self, dir: &Path, modified: Option<SystemTime>, options: &ListingOptions, access: &Access) -> Option<Arc<Vec<FileEntry>>> {
+        let listings = self.listings.lock().ok()?;
+        listings.get(&Self::key(dir, options, access))
+            .filter(|(listed_at, _)| modified.is_some() && *listed_at == modified)
+            .map(|(_, entries)| entries.clone())
+    }
+
+    pub fn insert(&self, dir: &Path, modified: Option<SystemTime>, options: &ListingOptions, access: &Access, entries: Arc<Vec<FileEntry>>) {
+        if let Ok(mut listings) = self.listings.lock() {
+            if listings.len() >= MAX_CACHED_LISTINGS {
+                listings.clear();
+            }
+            listings.insert(Self::key(dir, options, access), (modified, entries));
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref LISTINGS: ListingCache = ListingCache::new();
+}
+
+fn file_extension(name: &str) -> String {
+    name.rsplit_once('.').map(|(_, extension)| extension.to_lowercase()).unwrap_or_default()
+}
+
+// JSON shape of an entry for /api/list and /api/stat. Timestamps and
+// permissions are missing for members of archives.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInfo {
+    pub name: String,
+    // Relative to the served root, as used in /browse URLs
+    pub path: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub size: Option<u64>,
+    pub modified: Option<String>,
+    pub created: Option<String>,
+    pub accessed: Option<String>,
+    pub mime: Option<String>,
+    pub permissions: Option<FilePermissions>,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    pub symlink: bool,
+    pub symlink_target: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilePermissions {
+    pub readonly: bool,
+    // Unix mode bits, e.g. "0644"
+    pub mode: Option<String>,
+}
+
+impl FileInfo {
+    // `relative_path` is the decoded path of the entry itself
+    pub fn from_entry(entry: &FileEntry, relative_path: &str) -> Self {
+        // Archive members carry no permissions at all
+        let permissions = fs::metadata(&entry.path).ok().map(|m| FilePermissions {
+            readonly: m.permissions().readonly(),
+            mode: entry.mode.map(|mode| format!("{:04o}", mode)),
+        });
+
+        FileInfo {
+            name: entry.name.clone(),
+            path: relative_path.trim_matches('/').to_string(),
+            kind: if entry.is_dir { "directory" } else { "file" },
+            size: entry.size,
+            modified: entry.modified.map(rfc3339),
+            created: entry.created.map(rfc3339),
+            accessed: entry.accessed.map(rfc3339),
+            mime: entry.mime.clone(),
+            permissions,
+            owner: entry.owner.clone(),
+            group: entry.group.clone(),
+            symlink: entry.is_symlink,
+            symlink_target: entry.symlink_target.as_ref().map(|target| target.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+fn rfc3339(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339()
+}
+
+#[cfg(unix)]
+fn unix_mode(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn unix_mode(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+// ls-style permission string, e.g. "drwxr-xr-x"
+fn format_mode(entry: &FileEntry) -> Option<String> {
+    let mode = entry.mode?;
+    let kind = if entry.is_symlink { 'l' } else if entry.is_dir { 'd' } else { '-' };
+    let mut text = String::from(kind);
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        text.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    Some(text)
+}
+
+#[cfg(unix)]
+lazy_static::lazy_static! {
+    // uid/gid to name, read once from the local account databases
+    static ref USER_NAMES: std::collections::HashMap<u32, String> = read_id_names("/etc/passwd");
+    static ref GROUP_NAMES: std::collections::HashMap<u32, String> = read_id_names("/etc/group");
+}
+
+// Both files are `name:password:id:...` per line
+#[cfg(unix)]
+fn read_id_names(file: &str) -> std::collections::HashMap<u32, String> {
+    fs::read_to_string(file)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((id, name.to_string()))
+        })
+        .collect()
+}
+
+// Falls back to the numeric id for accounts that are not in the local files
+#[cfg(unix)]
+fn owner_and_group(metadata: &fs::Metadata) -> (Option<String>, Option<String>) {
+    use std::os::unix::fs::MetadataExt;
+    let (uid, gid) = (metadata.uid(), metadata.gid());
+    (
+        Some(USER_NAMES.get(&uid).cloned().unwrap_or_else(|| uid.to_string())),
+        Some(GROUP_NAMES.get(&gid).cloned().unwrap_or_else(|| gid.to_string())),
+    )
+}
+
+#[cfg(not(unix))]
+fn owner_and_group(_metadata: &fs::Metadata) -> (Option<String>, Option<String>) {
+    (None, None)
 }
 
 // Add URL encoding function
@@ -33,28 +387,10 @@ pub fn get_directory_entries(dir_path: &Path) -> Result<Vec<FileEntry>, io::Erro
     
     for entry in entries {
         let entry = entry?;
-        let metadata = entry.metadata()?;
-        let name = entry.file_name().to_string_lossy().to_string();
-        let path = entry.path();
-        let is_dir = metadata.is_dir();
-        let size = if is_dir { None } else { Some(metadata.len()) };
-        
-        file_entries.push(FileEntry {
-            name,
-            path,
-            is_dir,
-            size,
-        });
+        file_entries.push(FileEntry::from_path(&entry.path())?);
     }
     
-    file_entries.sort_by(|a, b| {
-        match (a.is_dir, b.is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.cmp(&b.name),
-        }
-    });
-    
+    // Callers order the listing with `paginate_entries`
     Ok(file_entries)
 }
 
@@ -85,11 +421,13 @@ fn is_viewable_file(filename: &str) -> bool {
 
 // Update the generate_directory_html function
 
-pub fn generate_directory_html(entries: &[FileEntry], url_path: &str) -> String {
+// Admins get a toolbar switch for hidden files, stored in the `show_hidden`
+// cookie so every page and API call sees the same tree
+pub fn generate_directory_html(page: &ListingPage, options: &ListingOptions, url_path: &str, readme_html: Option<&str>, access: &Access) -> String {
     let mut html = format!(r#"<!DOCTYPE html>
 <html>
 <head>
-    <title>FirxTTech Solutions - {}</title>
+    <title>{organization} - {}</title>
     <meta charset="UTF-8">
     <style>
         body {{ font-family: Arial, sans-serif; margin: 0; padding: 0; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; }}
@@ -443,6 +781,60 @@ pub fn generate_directory_html(entries: &[FileEntry], url_path: &str) -> String
         .text-file .file-icon::before {{ content: '📝'; background: linear-gradient(135deg, #fef3c7, #fde68a); }}
         .pdf-file .file-icon {{ background: linear-gradient(135deg, #dc2626, #b91c1c); }}
         .pdf-file .file-icon::before {{ content: '📄'; background: linear-gradient(135deg, #fecaca, #fca5a5); }}
+        .archive-file .file-icon {{ background: linear-gradient(135deg, #64748b, #475569); }}
+        .archive-file .file-icon::before {{ content: '🗜️'; background: linear-gradient(135deg, #e2e8f0, #cbd5e1); }}
+        
+        /* Background job progress toasts */
+        .job-toasts {{ position: fixed; right: 20px; bottom: 20px; z-index: 500; display: flex; flex-direction: column; gap: 10px; width: 320px; }}
+        .job-toast {{ background: rgba(255, 255, 255, 0.97); border-radius: 12px; padding: 14px 16px; box-shadow: 0 8px 25px rgba(0,0,0,0.2); font-size: 13px; color: #374151; }}
+        .job-toast.completed {{ border-left: 4px solid #10b981; }}
+        .job-toast.failed, .job-toast.cancelled {{ border-left: 4px solid #dc2626; }}
+        .job-label {{ font-weight: 600; margin-bottom: 8px; word-break: break-all; }}
+        .job-progress {{ height: 6px; background: #e5e7eb; border-radius: 3px; overflow: hidden; }}
+        .job-bar {{ height: 100%; width: 0; background: linear-gradient(90deg, #f97316, #2563eb); transition: width 0.3s ease; }}
+        .job-detail {{ margin-top: 6px; color: #6b7280; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }}
+        .job-cancel {{ margin-top: 8px; font-size: 11px; padding: 3px 8px; border: 1px solid #dc2626; border-radius: 6px; color: #dc2626; background: white; cursor: pointer; }}
+        
+        /* Lazy-loaded image thumbnails */
+        .thumbnail {{ display: block; width: 100%; max-width: 128px; height: 96px; object-fit: cover; margin: 0 auto 12px; border-radius: 8px; background: rgba(255, 255, 255, 0.6); box-shadow: 0 2px 8px rgba(16, 185, 129, 0.3); }}
+        
+        /* Sort, filter and paging controls */
+        .list-toolbar {{ display: flex; flex-wrap: wrap; align-items: center; gap: 10px; margin-bottom: 16px; }}
+        .list-toolbar input, .list-toolbar select {{ padding: 8px 12px; border: none; border-radius: 8px; font-size: 14px; background: rgba(255, 255, 255, 0.95); box-shadow: 0 2px 8px rgba(0,0,0,0.1); }}
+        .filter-input {{ flex: 1; min-width: 180px; max-width: 320px; }}
+        .list-count {{ color: white; font-size: 13px; font-weight: 500; text-shadow: 0 1px 2px rgba(0,0,0,0.3); }}
+        .load-more {{ text-align: center; padding: 24px; color: white; font-size: 14px; }}
+        .live-added {{ animation: liveAdded 1.5s ease-out; }}
+        @keyframes liveAdded {{ from {{ box-shadow: 0 0 0 3px rgba(255, 255, 255, 0.9); }} to {{ box-shadow: 0 2px 8px rgba(0,0,0,0.1); }} }}
+        
+        /* Details view: one row per item with metadata columns */
+        .view-toggle {{ padding: 8px 12px; border: none; border-radius: 8px; font-size: 14px; background: rgba(255, 255, 255, 0.95); box-shadow: 0 2px 8px rgba(0,0,0,0.1); cursor: pointer; }}
+        .item-details {{ display: none; }}
+        .grid-container.list-view {{ grid-template-columns: 1fr; gap: 4px; }}
+        .list-view .grid-item {{ display: flex; align-items: center; gap: 12px; padding: 6px 12px; text-align: left; border-radius: 8px; }}
+        .list-view .grid-item:hover {{ transform: none; }}
+        .list-view .folder-icon, .list-view .file-icon {{ width: 28px; height: 28px; margin: 0; flex-shrink: 0; }}
+        .list-view .folder-icon::before, .list-view .file-icon::before {{ top: 4px; left: 4px; right: 4px; bottom: 4px; font-size: 12px; }}
+        .list-view .thumbnail {{ width: 28px; height: 28px; margin: 0; flex-shrink: 0; }}
+        .list-view .item-name {{ flex: 1; min-width: 0; margin: 0; font-size: 14px; }}
+        .list-view .item-info {{ width: 120px; flex-shrink: 0; text-align: right; }}
+        .list-view .item-details {{ display: flex; gap: 12px; font-size: 12px; opacity: 0.85; flex-shrink: 0; }}
+        .list-view .item-details span {{ overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }}
+        .detail-modified {{ width: 120px; }}
+        .detail-mode {{ width: 84px; font-family: Consolas, Menlo, monospace; }}
+        .detail-owner {{ width: 110px; }}
+        .detail-kind {{ width: 160px; }}
+        .list-view .file-actions {{ margin: 0; flex-shrink: 0; }}
+        @media (max-width: 768px) {{ .list-view .detail-mode, .list-view .detail-owner, .list-view .detail-kind {{ display: none; }} }}
+        
+        /* Context menu and drag-and-drop */
+        .context-menu {{ position: fixed; z-index: 600; display: none; min-width: 180px; background: white; border-radius: 10px; box-shadow: 0 8px 25px rgba(0,0,0,0.2); padding: 6px; }}
+        .context-menu.show {{ display: block; }}
+        .context-menu button {{ display: block; width: 100%; text-align: left; padding: 8px 12px; border: none; background: none; border-radius: 6px; font-size: 14px; color: #374151; cursor: pointer; }}
+        .context-menu button:hover {{ background: #eff6ff; color: #2563eb; }}
+        .grid-item.dragging {{ opacity: 0.5; }}
+        .grid-item.drop-target {{ outline: 3px dashed #2563eb; outline-offset: 2px; }}
+        .grid-container.file-drop {{ outline: 3px dashed #f97316; outline-offset: 6px; border-radius: 12px; }}
     </style>
 </head>
 <body>
@@ -453,9 +845,9 @@ pub fn generate_directory_html(entries: &[FileEntry], url_path: &str) -> String
     <div class="sidebar" id="sidebar">
         <div class="sidebar-header">
             <h2 class="sidebar-title">
-                🦀 File Manager
+                🦀 {app_title}
             </h2>
-            <p class="sidebar-subtitle">FirxTTech Solutions</p>
+            <p class="sidebar-subtitle">{organization}</p>
         </div>
         
         <div class="nav-section">
@@ -484,6 +876,22 @@ pub fn generate_directory_html(entries: &[FileEntry], url_path: &str) -> String
                 New Folder
                 <span class="nav-shortcut">Ctrl+N</span>
             </button>
+            <button class="nav-item" onclick="openSearch()">
+                <div class="nav-icon">🔍</div>
+                Search
+            </button>
+            <button class="nav-item" onclick="location.href = location.pathname.replace(/^\/browse/, '/usage')">
+                <div class="nav-icon">📊</div>
+                Disk Usage
+            </button>
+            <button class="nav-item" onclick="location.href = '/duplicates?path=' + encodeURIComponent(currentFolder())">
+                <div class="nav-icon">🧬</div>
+                Find Duplicates
+            </button>
+            <button class="nav-item" onclick="location.href='/trash'">
+                <div class="nav-icon">🗑️</div>
+                Trash
+            </button>
         </div>
         
         <div class="nav-section">
@@ -524,7 +932,7 @@ pub fn generate_directory_html(entries: &[FileEntry], url_path: &str) -> String
         
         <!-- Center: Title and breadcrumb -->
         <div class="header-title">
-            <h1>File Manager</h1>
+            <h1>{app_title}</h1>
             <div class="breadcrumb">
                 {}
             </div>
@@ -532,12 +940,59 @@ pub fn generate_directory_html(entries: &[FileEntry], url_path: &str) -> String
         
         <!-- Right: Upload button -->
         <div class="header-actions">
-            <a href="/upload" class="upload-btn">Upload</a>
+            <a href="/upload" class="upload-btn" onclick="showUploadDialog(); return false;">Upload</a>
         </div>
     </div>
     
+    <div class="job-toasts" id="jobToasts"></div>
+    
+    <!-- Right-click menu for files and folders -->
+    <div class="context-menu" id="contextMenu">
+        <button onclick="contextAction('open')">📂 Open</button>
+        <button onclick="contextAction('rename')">✏️ Rename</button>
+        <button onclick="contextAction('move')">📦 Move to...</button>
+        <button onclick="contextAction('copy')">📋 Copy to...</button>
+        <button onclick="contextAction('compress')">🗜️ Compress</button>
+        <button onclick="contextAction('versions')" id="versionsAction">🕘 Versions</button>
+        <button onclick="contextAction('delete')">🗑️ Delete</button>
+    </div>
+    
     <div class="content-wrapper">
-        <div class="grid-container">
+        <form class="list-toolbar" method="get" onchange="this.submit()">
+            <input type="search" name="q" value="{filter}" placeholder="Filter by name..." class="filter-input">
+            <select name="sort">
+                <option value="name"{sort_name}>Name</option>
+                <option value="size"{sort_size}>Size</option>
+                <option value="type"{sort_type}>Type</option>
+                <option value="modified"{sort_modified}>Modified</option>
+            </select>
+            <select name="order">
+                <option value="asc">Ascending</option>
+                <option value="desc"{order_desc}>Descending</option>
+            </select>
+            <button type="button" class="view-toggle" id="viewToggle" onclick="toggleView()">☰ Details</button>
+            <button type="button" class="view-toggle" onclick="openSearch()">🔍 Search subfolders</button>
+            <button type="button" class="view-toggle" id="sizesBtn" onclick="showFolderSizes()">📊 Folder sizes</button>
+            {hidden_toggle}
+            <span class="list-count">{count}</span>
+        </form>
+        <div class="grid-container" id="grid" data-live="{live_updates}">
+        <script>
+            // Applied before the tiles render so the page does not flash the grid first
+            if (localStorage.getItem('browserView') === 'list') {{
+                document.getElementById('grid').classList.add('list-view');
+                document.getElementById('viewToggle').textContent = '▦ Grid';
+            }}
+            function toggleView() {{
+                const list = document.getElementById('grid').classList.toggle('list-view');
+                localStorage.setItem('browserView', list ? 'list' : 'grid');
+                document.getElementById('viewToggle').textContent = list ? '▦ Grid' : '☰ Details';
+            }}
+            function toggleHidden(show) {{
+                document.cookie = show ? 'show_hidden=1; path=/; SameSite=Strict' : 'show_hidden=; path=/; max-age=0';
+                location.reload();
+            }}
+        </script>
 
     <script>
         let isSidebarOpen = false;
@@ -583,17 +1038,63 @@ pub fn generate_directory_html(entries: &[FileEntry], url_path: &str) -> String
         }}
         
         function showUploadDialog() {{
-            window.location.href = '/upload';
+            window.location.href = '/upload?dir=' + encodeURIComponent(currentFolder());
             closeSidebar();
         }}
         
-        function createNewFolder() {{
+        async function createNewFolder() {{
+            closeSidebar();
             const folderName = prompt('Enter folder name:');
-            if (folderName) {{
-                // TODO: Implement create folder functionality
-                alert('Create folder functionality will be implemented soon!');
+            if (!folderName) {{
+                return;
+            }}
+            
+            const response = await fetch('/api/mkdir', {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify({{ path: currentFolder(), name: folderName.trim() }})
+            }});
+            if (response.ok) {{
+                window.location.reload();
+            }} else {{
+                const result = await response.json().catch(() => ({{}}));
+                alert('Could not create folder: ' + (result.error || response.status));
             }}
-            closeSidebar();
+        }}
+        
+        function openSearch() {{
+            window.location.href = '/search?path=' + encodeURIComponent(currentFolder());
+        }}
+        
+        // Fills in the recursive size of every folder tile on the page
+        async function showFolderSizes() {{
+            const button = document.getElementById('sizesBtn');
+            button.disabled = true;
+            button.textContent = '📊 Calculating...';
+            try {{
+                const response = await fetch(location.pathname.replace(/^\/browse/, '/api/usage'));
+                const result = await response.json();
+                if (!response.ok) throw new Error(result.error || response.status);
+                const sizes = new Map(result.entries.filter(e => e.type === 'directory').map(e => [e.name, e.size]));
+                document.querySelectorAll('.grid-item[data-folder][data-name]').forEach(tile => {{
+                    if (sizes.has(tile.dataset.name)) {{
+                        tile.querySelector('.item-info').textContent = 'Folder · ' + formatBytes(sizes.get(tile.dataset.name));
+                    }}
+                }});
+                button.textContent = '📊 ' + formatBytes(result.size) + ' in total';
+            }} catch (err) {{
+                alert('Could not calculate folder sizes: ' + err.message);
+                button.textContent = '📊 Folder sizes';
+            }} finally {{
+                button.disabled = false;
+            }}
+        }}
+        
+        function formatBytes(bytes) {{
+            const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+            let size = bytes, unit = 0;
+            while (size >= 1024 && unit < units.length - 1) {{ size /= 1024; unit++; }}
+            return (unit === 0 ? size : size.toFixed(1)) + ' ' + units[unit];
         }}
         
         function showSettings() {{
@@ -603,7 +1104,7 @@ pub fn generate_directory_html(entries: &[FileEntry], url_path: &str) -> String
         }}
         
         function showHelp() {{
-            const helpText = `Web-based File Manager v1.0
+            const helpText = `{app_title} v1.0
 
 Keyboard Shortcuts:
 • Ctrl+H: Go to Home
@@ -617,7 +1118,7 @@ Keyboard Shortcuts:
 Built with Rust 🦀 for maximum performance and security.
 System files are automatically filtered for your protection.
 
-FirxTTech Solutions © 2025`;
+{organization} © 2025`;
             alert(helpText);
             closeSidebar();
         }}
@@ -664,6 +1165,7 @@ FirxTTech Solutions © 2025`;
                 if (isSidebarOpen) {{
                     closeSidebar();
                 }}
+                hideContextMenu();
             }}
         }});
         
@@ -671,6 +1173,341 @@ FirxTTech Solutions © 2025`;
         document.getElementById('sidebar').addEventListener('click', function(e) {{
             e.stopPropagation();
         }});
+        
+        // Path of the folder being browsed, relative to the served root
+        function currentFolder() {{
+            return decodeURIComponent(location.pathname.replace(/^\/browse\/?/, '').replace(/\/$/, ''));
+        }}
+        
+        function joinPath(folder, name) {{
+            return folder ? folder + '/' + name : name;
+        }}
+        
+        async function extractArchive(name) {{
+            const response = await fetch('/api/extract', {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify({{ path: joinPath(currentFolder(), name) }})
+            }});
+            const result = await response.json().catch(() => ({{}}));
+            if (!response.ok) {{
+                alert('Extract failed: ' + (result.error || response.status));
+                return;
+            }}
+            trackJob(result.jobId, 'Extracting ' + name);
+        }}
+        
+        // Shows a progress toast for a background job and reloads when it succeeds
+        function trackJob(jobId, label) {{
+            const toast = document.createElement('div');
+            toast.className = 'job-toast';
+            toast.innerHTML = '<div class="job-label"></div><div class="job-progress"><div class="job-bar"></div></div><div class="job-detail">Starting...</div><button class="job-cancel">Cancel</button>';
+            toast.querySelector('.job-label').textContent = label;
+            toast.querySelector('.job-cancel').onclick = () => fetch('/api/jobs/' + jobId + '/cancel', {{ method: 'POST' }});
+            document.getElementById('jobToasts').appendChild(toast);
+            
+            const poll = async () => {{
+                const response = await fetch('/api/jobs/' + jobId);
+                if (!response.ok) {{
+                    toast.querySelector('.job-detail').textContent = 'Job status unavailable';
+                    return;
+                }}
+                const status = await response.json();
+                const percent = status.bytesTotal ? Math.min(100, status.bytesDone / status.bytesTotal * 100) : 0;
+                toast.querySelector('.job-bar').style.width = percent + '%';
+                
+                if (status.state === 'running') {{
+                    toast.querySelector('.job-detail').textContent = `${{status.filesDone}}/${{status.filesTotal}} files · ${{status.currentItem}}`;
+                    setTimeout(poll, 500);
+                }} else {{
+                    const problems = status.errors.length ? ` (${{status.errors.length}} problems)` : '';
+                    toast.querySelector('.job-detail').textContent = status.message + problems;
+                    toast.title = status.errors.join('\n');
+                    toast.classList.add(status.state);
+                    toast.querySelector('.job-cancel').remove();
+                    if (status.state === 'completed') {{
+                        setTimeout(() => location.reload(), 1200);
+                    }} else {{
+                        setTimeout(() => toast.remove(), 8000);
+                    }}
+                }}
+            }};
+            poll();
+        }}
+        
+        async function postJson(url, body) {{
+            const response = await fetch(url, {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify(body)
+            }});
+            const result = await response.json().catch(() => ({{}}));
+            return {{ response, result }};
+        }}
+        
+        function parentFolder() {{
+            const folder = currentFolder();
+            return folder.includes('/') ? folder.substring(0, folder.lastIndexOf('/')) : '';
+        }}
+        
+        async function renameItem(name) {{
+            const newName = prompt('Rename to:', name);
+            if (!newName || newName === name) return;
+            const path = joinPath(currentFolder(), name);
+            let {{ response, result }} = await postJson('/api/rename', {{ path, newName, overwrite: false }});
+            if (response.status === 409 && result.conflicts && confirm(`"${{newName}}" already exists. Replace it?`)) {{
+                ({{ response, result }} = await postJson('/api/rename', {{ path, newName, overwrite: true }}));
+            }}
+            if (response.ok) {{
+                location.reload();
+            }} else if (response.status !== 409 || !result.conflicts) {{
+                alert('Rename failed: ' + (result.error || response.status));
+            }}
+        }}
+        
+        async function moveItems(names, destination) {{
+            const paths = names.map(name => joinPath(currentFolder(), name));
+            let {{ response, result }} = await postJson('/api/move', {{ paths, destination, overwrite: false }});
+            if (response.status === 409 && result.conflicts &&
+                confirm('Already in the destination:\n' + result.conflicts.join('\n') + '\n\nReplace them?')) {{
+                ({{ response, result }} = await postJson('/api/move', {{ paths, destination, overwrite: true }}));
+            }}
+            if (!response.ok) {{
+                alert('Move failed: ' + (result.error || response.status));
+                return;
+            }}
+            if (result.errors && result.errors.length) {{
+                alert('Some items could not be moved:\n' + result.errors.join('\n'));
+            }}
+            location.reload();
+        }}
+        
+        function promptMove(name) {{
+            const destination = prompt('Move to folder (path from the root):', currentFolder());
+            if (destination === null) return;
+            moveItems([name], destination.replace(/^\/+|\/+$/g, ''));
+        }}
+        
+        async function promptCopy(name) {{
+            const destination = prompt('Copy to folder (path from the root):', currentFolder());
+            if (destination === null) return;
+            const body = {{
+                paths: [joinPath(currentFolder(), name)],
+                destination: destination.replace(/^\/+|\/+$/g, ''),
+                overwrite: false
+            }};
+            let {{ response, result }} = await postJson('/api/copy', body);
+            if (response.status === 409 && result.conflicts &&
+                confirm('Already in the destination:\n' + result.conflicts.join('\n') + '\n\nReplace existing files?')) {{
+                body.overwrite = true;
+                ({{ response, result }} = await postJson('/api/copy', body));
+            }}
+            if (response.ok) {{
+                trackJob(result.jobId, 'Copying ' + name);
+            }} else if (response.status !== 409 || !result.conflicts) {{
+                alert('Copy failed: ' + (result.error || response.status));
+            }}
+        }}
+        
+        async function compressItem(name) {{
+            const archiveName = prompt('Archive name:', name + '.zip');
+            if (!archiveName) return;
+            const {{ response, result }} = await postJson('/api/compress', {{
+                paths: [joinPath(currentFolder(), name)],
+                destination: joinPath(currentFolder(), archiveName)
+            }});
+            if (!response.ok) {{
+                alert('Compress failed: ' + (result.error || response.status));
+                return;
+            }}
+            trackJob(result.jobId, 'Compressing ' + name);
+        }}
+        
+        async function deleteItem(name) {{
+            if (!confirm(`Move "${{name}}" to the trash?`)) return;
+            const {{ response, result }} = await postJson('/api/delete', {{ paths: [joinPath(currentFolder(), name)] }});
+            if (!response.ok || (result.errors && result.errors.length)) {{
+                alert('Delete failed: ' + (result.error || (result.errors || []).join('\n') || response.status));
+            }}
+            location.reload();
+        }}
+        
+        // Context menu, bound on the document because tiles are rendered after this script
+        let contextTarget = null;
+        const contextMenu = document.getElementById('contextMenu');
+        
+        function hideContextMenu() {{
+            contextMenu.classList.remove('show');
+        }}
+        
+        function contextAction(action) {{
+            const item = contextTarget;
+            hideContextMenu();
+            if (!item) return;
+            const name = item.dataset.name;
+            switch (action) {{
+                case 'open': item.click(); break;
+                case 'rename': renameItem(name); break;
+                case 'move': promptMove(name); break;
+                case 'copy': promptCopy(name); break;
+                case 'compress': compressItem(name); break;
+                case 'delete': deleteItem(name); break;
+                case 'versions': location.href = '/versions/' + joinPath(currentFolder(), name).split('/').map(encodeURIComponent).join('/'); break;
+            }}
+        }}
+        
+        document.addEventListener('contextmenu', function(e) {{
+            const item = e.target.closest('.grid-item[data-name]');
+            if (!item) return;
+            e.preventDefault();
+            contextTarget = item;
+            document.getElementById('versionsAction').style.display = item.dataset.folder ? 'none' : '';
+            contextMenu.style.left = Math.min(e.clientX, window.innerWidth - 200) + 'px';
+            contextMenu.style.top = Math.min(e.clientY, window.innerHeight - 180) + 'px';
+            contextMenu.classList.add('show');
+        }});
+        document.addEventListener('click', hideContextMenu);
+        
+        // Drag tiles onto a folder (or the parent tile) to move them there
+        const DRAG_TYPE = 'application/x-file-manager-item';
+        
+        document.addEventListener('dragstart', function(e) {{
+            const item = e.target.closest && e.target.closest('.grid-item[data-name]');
+            if (!item) return;
+            e.dataTransfer.setData(DRAG_TYPE, item.dataset.name);
+            e.dataTransfer.effectAllowed = 'move';
+            item.classList.add('dragging');
+        }});
+        document.addEventListener('dragend', function(e) {{
+            const item = e.target.closest && e.target.closest('.grid-item');
+            if (item) item.classList.remove('dragging');
+        }});
+        document.addEventListener('dragover', function(e) {{
+            const folder = e.target.closest('.grid-item[data-folder]');
+            if (!folder || !e.dataTransfer.types.includes(DRAG_TYPE)) return;
+            e.preventDefault();
+            folder.classList.add('drop-target');
+        }});
+        document.addEventListener('dragleave', function(e) {{
+            const folder = e.target.closest('.grid-item[data-folder]');
+            if (folder && !folder.contains(e.relatedTarget)) folder.classList.remove('drop-target');
+        }});
+        document.addEventListener('drop', function(e) {{
+            const folder = e.target.closest('.grid-item[data-folder]');
+            if (!folder || !e.dataTransfer.types.includes(DRAG_TYPE)) return;
+            e.preventDefault();
+            folder.classList.remove('drop-target');
+            const name = e.dataTransfer.getData(DRAG_TYPE);
+            if (!name || name === folder.dataset.name) return;
+            const destination = folder.classList.contains('parent-item')
+                ? parentFolder()
+                : joinPath(currentFolder(), folder.dataset.name);
+            moveItems([name], destination);
+        }});
+        
+        // Drop files from the desktop onto the grid to upload them into this
+        // folder, or onto a folder tile to upload them there
+        const grid = document.getElementById('grid');
+        const isFileDrag = e => e.dataTransfer.types.includes('Files');
+        
+        grid.addEventListener('dragover', function(e) {{
+            if (!isFileDrag(e)) return;
+            e.preventDefault();
+            e.dataTransfer.dropEffect = 'copy';
+            const folder = e.target.closest('.grid-item[data-folder]');
+            grid.querySelectorAll('.drop-target').forEach(item => {{ if (item !== folder) item.classList.remove('drop-target'); }});
+            if (folder) folder.classList.add('drop-target');
+            grid.classList.toggle('file-drop', !folder);
+        }});
+        grid.addEventListener('dragleave', function(e) {{
+            if (grid.contains(e.relatedTarget)) return;
+            grid.classList.remove('file-drop');
+            grid.querySelectorAll('.drop-target').forEach(item => item.classList.remove('drop-target'));
+        }});
+        grid.addEventListener('drop', function(e) {{
+            if (!isFileDrag(e)) return;
+            e.preventDefault();
+            grid.classList.remove('file-drop');
+            const folder = e.target.closest('.grid-item[data-folder]');
+            let destination = currentFolder();
+            if (folder) {{
+                folder.classList.remove('drop-target');
+                destination = folder.classList.contains('parent-item') ? parentFolder() : joinPath(currentFolder(), folder.dataset.name);
+            }}
+            uploadDroppedFiles(Array.from(e.dataTransfer.files), destination);
+        }});
+        
+        {checksum_js}
+        
+        // Sends each file through the resumable upload API with a progress toast
+        async function uploadDroppedFiles(files, destination) {{
+            let failed = 0;
+            for (const file of files) {{
+                const toast = document.createElement('div');
+                toast.className = 'job-toast';
+                toast.innerHTML = '<div class="job-label"></div><div class="job-progress"><div class="job-bar"></div></div><div class="job-detail">Starting...</div>';
+                toast.querySelector('.job-label').textContent = 'Uploading ' + file.name;
+                document.getElementById('jobToasts').appendChild(toast);
+                try {{
+                    await uploadFile(file, destination, percent => {{
+                        toast.querySelector('.job-bar').style.width = percent + '%';
+                        toast.querySelector('.job-detail').textContent = Math.floor(percent) + '%';
+                    }});
+                    toast.querySelector('.job-detail').textContent = 'Uploaded';
+                    toast.classList.add('completed');
+                }} catch (error) {{
+                    failed++;
+                    toast.querySelector('.job-detail').textContent = error.message;
+                    toast.title = error.message;
+                    toast.classList.add('failed');
+                    setTimeout(() => toast.remove(), 8000);
+                }}
+            }}
+            if (failed < files.length) {{
+                setTimeout(() => location.reload(), failed ? 8000 : 1200);
+            }}
+        }}
+        
+        async function uploadFile(file, destination, onProgress) {{
+            const failure = async (response) => {{
+                const result = await response.json().catch(() => ({{}}));
+                return new Error(result.error || ('Upload failed: ' + response.status));
+            }};
+            const start = await fetch('/upload/start', {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify({{ filename: file.name, totalSize: file.size, targetDir: destination }})
+            }});
+            if (!start.ok) throw await failure(start);
+            const session = await start.json();
+            const fileHash = new Sha256();
+            
+            let offset = 0;
+            while (offset < file.size) {{
+                const end = Math.min(offset + session.chunkSize, file.size);
+                const bytes = new Uint8Array(await file.slice(offset, end).arrayBuffer());
+                const response = await fetch('/upload/chunk/' + session.sessionId, {{
+                    method: 'POST',
+                    headers: {{
+                        'Content-Range': `bytes ${{offset}}-${{end - 1}}/${{file.size}}`,
+                        'Content-Type': 'application/octet-stream',
+                        'X-Chunk-SHA256': await chunkChecksum(bytes)
+                    }},
+                    body: bytes
+                }});
+                if (!response.ok) throw await failure(response);
+                fileHash.update(bytes);
+                offset = (await response.json()).uploadedSize;
+                onProgress(offset / file.size * 100);
+            }}
+            
+            const complete = await fetch('/upload/complete/' + session.sessionId, {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify({{ sha256: fileHash.hex() }})
+            }});
+            if (!complete.ok) throw await failure(complete);
+        }}
     </script>
 "#, url_path, 
     // Generate breadcrumb content
@@ -686,12 +1523,30 @@ FirxTTech Solutions © 2025`;
         } else { 
             String::new() 
         }
-    });
+    },
+    filter = escape_html(options.filter.as_deref().unwrap_or("")),
+    sort_name = selected(options.sort == SortKey::Name),
+    sort_size = selected(options.sort == SortKey::Size),
+    sort_type = selected(options.sort == SortKey::Type),
+    sort_modified = selected(options.sort == SortKey::Modified),
+    order_desc = selected(options.descending),
+    count = if page.total == 1 { "1 item".to_string() } else { format!("{} items", page.total) },
+    app_title = escape_html(&CONFIG.branding.title),
+    organization = escape_html(&CONFIG.branding.organization),
+    checksum_js = UPLOAD_CHECKSUM_JS,
+    // The volume list is fixed by configuration, so there is nothing to watch
+    live_updates = !(VOLUMES.is_named() && url_path.trim_end_matches('/') == "/browse"),
+    hidden_toggle = match (access.is_admin(), access.show_hidden) {
+        (false, _) => "",
+        (true, false) => r#"<button type="button" class="view-toggle" onclick="toggleHidden(true)">👁️ Show hidden</button>"#,
+        (true, true) => r#"<button type="button" class="view-toggle" onclick="toggleHidden(false)">🙈 Hide hidden</button>"#,
+    },
+    );
     
     if url_path != "/browse" && url_path != "/browse/" {
         let parent_url = get_parent_url(url_path);
         html.push_str(&format!(r#"
-        <div class="grid-item parent-item" onclick="location.href='{}'">
+        <div class="grid-item parent-item" data-folder="true" onclick="location.href='{}'">
             <div class="folder-icon"></div>
             <div class="item-name">Parent Directory</div>
             <div class="item-info">Go up one level</div>
@@ -699,16 +1554,159 @@ FirxTTech Solutions © 2025`;
         "#, parent_url));
     }
     
+    html.push_str(&render_entry_tiles(&page.entries, url_path, access));
+    
+    html.push_str(r#"
+        </div>"#);
+    
+    // Further pages are fetched as the sentinel scrolls into view
+    if let Some(next_offset) = page.next_offset {
+        html.push_str(&format!(r#"
+        <div class="load-more" id="loadMore" data-next-offset="{}">Loading more...</div>"#, next_offset));
+    }
+    
+    // Folder README rendered below the grid, as code hosts do
+    if let Some(readme_html) = readme_html {
+        html.push_str(&format!(r#"
+        <style>
+        .readme {{ margin-top: 24px; background: rgba(255, 255, 255, 0.95); border-radius: 12px; box-shadow: 0 2px 8px rgba(0,0,0,0.1); overflow: hidden; }}
+        .readme-header {{ padding: 12px 20px; font-weight: 600; color: #374151; border-bottom: 1px solid #e5e7eb; }}
+        .readme .markdown-body {{ padding: 8px 32px 24px; }}
+        {}
+        </style>
+        <div class="readme">
+            <div class="readme-header">📖 README.md</div>
+            <div class="markdown-body">
+{}
+            </div>
+        </div>"#, MARKDOWN_CSS, readme_html));
+    }
+    
+    html.push_str(r#"
+    </div>
+    <script>
+        // Infinite scroll: fetch the next page of tiles when the sentinel comes into view
+        (function() {
+            const sentinel = document.getElementById('loadMore');
+            if (!sentinel) return;
+            let loading = false;
+            const observer = new IntersectionObserver(async (entries) => {
+                if (!entries[0].isIntersecting || loading) return;
+                loading = true;
+                const params = new URLSearchParams(location.search);
+                params.set('offset', sentinel.dataset.nextOffset);
+                params.set('fragment', '1');
+                try {
+                    const response = await fetch(location.pathname + '?' + params);
+                    if (!response.ok) throw new Error(response.status);
+                    document.getElementById('grid').insertAdjacentHTML('beforeend', await response.text());
+                    const next = response.headers.get('X-Next-Offset');
+                    if (next) {
+                        sentinel.dataset.nextOffset = next;
+                    } else {
+                        observer.disconnect();
+                        sentinel.remove();
+                    }
+                } catch (err) {
+                    sentinel.textContent = 'Could not load more items';
+                    observer.disconnect();
+                } finally {
+                    loading = false;
+                }
+            }, { rootMargin: '600px' });
+            observer.observe(sentinel);
+        })();
+        
+        // Live updates: the server pushes changes to this folder as they happen
+        (function() {
+            const grid = document.getElementById('grid');
+            if (!window.EventSource || grid.dataset.live !== 'true') return;
+            const params = new URLSearchParams(location.search);
+            const filter = (params.get('q') || '').trim().toLowerCase();
+            const nameOrder = (params.get('sort') || 'name') === 'name';
+            const descending = params.get('order') === 'desc';
+            
+            function tileFor(name) {
+                return grid.querySelector(`.grid-item[data-name="${CSS.escape(name)}"]:not(.parent-item)`);
+            }
+            
+            function removeTile(name) {
+                const tile = tileFor(name);
+                if (tile) tile.remove();
+            }
+            
+            // Where a new tile belongs: folders first, then by name like the server sorts
+            function insertionPoint(name, folder) {
+                const key = name.toLowerCase();
+                for (const tile of grid.querySelectorAll('.grid-item[data-name]')) {
+                    const tileFolder = tile.dataset.folder === 'true';
+                    if (folder !== tileFolder) {
+                        if (folder) return tile;
+                        continue;
+                    }
+                    const tileKey = tile.dataset.name.toLowerCase();
+                    if (descending ? tileKey < key : tileKey > key) return tile;
+                }
+                return null;
+            }
+            
+            function upsertTile(data) {
+                if (filter && !data.name.toLowerCase().includes(filter)) return;
+                const template = document.createElement('template');
+                template.innerHTML = data.html.trim();
+                const tile = template.content.firstElementChild;
+                if (!tile) return;
+                const existing = tileFor(data.name);
+                if (existing) {
+                    existing.replaceWith(tile);
+                    return;
+                }
+                const before = nameOrder ? insertionPoint(data.name, data.folder) : null;
+                if (before) {
+                    grid.insertBefore(tile, before);
+                } else if (document.getElementById('loadMore')) {
+                    return; // Belongs on a page that has not been loaded yet
+                } else {
+                    grid.appendChild(tile);
+                }
+                tile.classList.add('live-added');
+            }
+            
+            const events = new EventSource(location.pathname.replace(/^\/browse/, '/api/watch'));
+            events.addEventListener('add', e => upsertTile(JSON.parse(e.data)));
+            events.addEventListener('change', e => upsertTile(JSON.parse(e.data)));
+            events.addEventListener('remove', e => removeTile(JSON.parse(e.data).name));
+            events.addEventListener('rename', e => {
+                const data = JSON.parse(e.data);
+                removeTile(data.from);
+                upsertTile(data);
+            });
+            events.addEventListener('resync', () => location.reload());
+            window.addEventListener('pagehide', () => events.close());
+        })();
+    </script>
+</body>
+</html>
+    "#);
+    
+    html
+}
+
+// Tiles for one page of entries; also served on its own for infinite scroll
+pub fn render_entry_tiles(entries: &[FileEntry], url_path: &str, access: &Access) -> String {
+    let mut html = String::new();
+    
     for entry in entries {
         if entry.is_dir {
             let folder_url = format!("{}/{}", url_path.trim_end_matches('/'), url_encode(&entry.name));
             html.push_str(&format!(r#"
-            <div class="grid-item" onclick="location.href='{}'">
+            <div class="grid-item" data-name="{}" data-folder="true" draggable="true" onclick="location.href='{}'">
                 <div class="folder-icon"></div>
                 <div class="item-name">{}</div>
-                <div class="item-info">Folder</div>
+                <div class="item-info">{}</div>
+                {}
             </div>
-            "#, folder_url, entry.name));
+            "#, escape_html(&entry.name), folder_url, escape_html(&entry.name), folder_info(entry, access), entry_details_html(entry)));
         } else {
             let file_url = {
                 let path_part = url_path.trim_start_matches("/browse");
@@ -730,42 +1728,107 @@ FirxTTech Solutions © 2025`;
             let size_str = entry.size.map(format_file_size).unwrap_or_else(|| "Unknown".to_string());
             let file_type_class = get_file_type_class(&entry.name);
             
-            if is_viewable_file(&entry.name) {
+            // Images get a lazily loaded thumbnail, falling back to the generic icon
+            let icon_html = if is_thumbnailable(&entry.name) {
+                format!(r#"<img class="thumbnail" src="{}?size={}" alt="" loading="lazy" onerror="this.outerHTML='<div class=&quot;file-icon&quot;></div>'">"#,
+                    file_url.replacen("/file", "/thumb", 1), DEFAULT_THUMBNAIL_SIZE)
+            } else {
+                r#"<div class="file-icon"></div>"#.to_string()
+            };
+            
+            // Text and source files open in the highlighted viewer
+            let view_url = if is_source_file(&entry.name) {
+                file_url.replacen("/file", "/view", 1)
+            } else {
+                file_url.clone()
+            };
+            
+            // Small text files on disk get an Edit button
+            let edit_button = if is_editable_file(&entry.name) && entry.size.is_some_and(|size| size <= CONFIG.limits.max_edit_size) && entry.path.is_file() {
+                format!(r#"<a href="{}" class="action-btn">Edit</a>"#, file_url.replacen("/file", "/edit", 1))
+            } else {
+                String::new()
+            };
+            
+            // Archives on disk open as virtual folders
+            if archive_kind(&entry.name).is_some() && entry.path.is_file() {
+                let archive_url = format!("{}/{}", url_path.trim_end_matches('/'), url_encode(&entry.name));
                 html.push_str(&format!(r#"
-                <div class="grid-item {}">
+                <div class="grid-item archive-file" data-name="{}" draggable="true" onclick="location.href='{}'">
                     <div class="file-icon"></div>
                     <div class="item-name">{}</div>
+                    <div class="item-info">{} · Archive</div>
+                    {}
+                    <div class="file-actions">
+                        <a href="{}" class="action-btn">Open</a>
+                        <a href="javascript:void(0)" class="action-btn" onclick="event.stopPropagation(); extractArchive(this.closest('.grid-item').dataset.name)">Extract</a>
+                        <a href="{}" class="action-btn download-btn" onclick="event.stopPropagation()">Download</a>
+                    </div>
+                </div>
+                "#, escape_html(&entry.name), archive_url, escape_html(&entry.name), size_str, entry_details_html(entry), archive_url, download_url));
+            } else if is_viewable_file(&entry.name) {
+                html.push_str(&format!(r#"
+                <div class="grid-item {}" data-name="{}" draggable="true">
+                    {}
+                    <div class="item-name">{}</div>
                     <div class="item-info">{}</div>
+                    {}
                     <div class="file-actions">
                         <a href="{}" class="action-btn">View</a>
+                        {}
                         <a href="{}" class="action-btn download-btn">Download</a>
                     </div>
                 </div>
-                "#, file_type_class, entry.name, size_str, file_url, download_url));
+                "#, file_type_class, escape_html(&entry.name), icon_html, escape_html(&entry.name), size_str, entry_details_html(entry), view_url, edit_button, download_url));
             } else {
                 html.push_str(&format!(r#"
-                <div class="grid-item {}" onclick="location.href='{}'">
-                    <div class="file-icon"></div>
+                <div class="grid-item {}" data-name="{}" draggable="true" onclick="location.href='{}'">
+                    {}
                     <div class="item-name">{}</div>
                     <div class="item-info">{}</div>
+                    {}
                 </div>
-                "#, file_type_class, download_url, entry.name, size_str));
+                "#, file_type_class, escape_html(&entry.name), download_url, icon_html, escape_html(&entry.name), size_str, entry_details_html(entry)));
             }
         }
     }
     
-    html.push_str(r#"
-        </div>
-    </div>
-</body>
-</html>
-    "#);
-    
     html
 }
 
+// Folder sizes are only shown once something has computed them for the
+// viewer's role
+fn folder_info(entry: &FileEntry, access: &Access) -> String {
+    match FOLDER_SIZES.cached(&entry.path, access) {
+        Some(size) => format!("Folder · {}", format_file_size(size.size)),
+        None => "Folder".to_string(),
+    }
+}
+
+// Extra columns for the details view; hidden while the grid view is active
+fn entry_details_html(entry: &FileEntry) -> String {
+    let format_time = |time: SystemTime| chrono::DateTime::<chrono::Local>::from(time).format("%Y-%m-%d %H:%M").to_string();
+    let modified = entry.modified.map(format_time).unwrap_or_default();
+    let times_title = [("Created", entry.created), ("Accessed", entry.accessed)].iter()
+        .filter_map(|(label, time)| time.map(|time| format!("{}: {}", label, format_time(time))))
+        .collect::<Vec<_>>()
+        .join("&#10;");
+    let owner = match (&entry.owner, &entry.group) {
+        (Some(owner), Some(group)) => format!("{}:{}", owner, group),
+        _ => String::new(),
+    };
+    let kind = match &entry.symlink_target {
+        Some(target) => format!("→ {}", target.display()),
+        None if entry.is_dir => "Folder".to_string(),
+        None => entry.mime.clone().unwrap_or_default(),
+    };
+
+    format!(r#"<div class="item-details"><span class="detail-modified" title="{}">{}</span><span class="detail-mode">{}</span><span class="detail-owner">{}</span><span class="detail-kind">{}</span></div>"#,
+        times_title, modified, format_mode(entry).unwrap_or_default(), escape_html(&owner), escape_html(&kind))
+}
+
 // Add this function to determine file type class for styling
-fn get_file_type_class(filename: &str) -> &'static str {
+pub(crate) fn get_file_type_class(filename: &str) -> &'static str {
     let lower = filename.to_lowercase();
     if lower.ends_with(".jpg") || lower.ends_with(".jpeg") || 
        lower.ends_with(".png") || lower.ends_with(".gif") || 
@@ -789,12 +1852,12 @@ fn get_file_type_class(filename: &str) -> &'static str {
     }
 }
 
+fn selected(is_selected: bool) -> &'static str {
+    if is_selected { " selected" } else { "" }
+}
+
 fn get_parent_url(url_path: &str) -> &str {
-    let parent_path = if url_path.ends_with('/') {
-        &url_path[..url_path.len()-1]
-    } else {
-        url_path
-    };
+    let parent_path = url_path.strip_suffix('/').unwrap_or(url_path);
     
     if let Some(pos) = parent_path.rfind('/') {
         if pos == 0 || parent_path[..pos].ends_with("/browse") {
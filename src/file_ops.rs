@@ -0,0 +1,196 @@
+use crate::handlers::utils::is_excluded_entry;
+use crate::jobs::{JobHandle, ProgressReader};
+use crate::visibility::Access;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Renames `source` to `target`, falling back to copy-then-delete when they
+// live on different filesystems. A copy that fails part way is removed, so
+// the move can simply be tried again.
+pub fn move_path(source: &Path, target: &Path) -> io::Result<()> {
+    match fs::rename(source, target) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            let existed = fs::symlink_metadata(target).is_ok();
+            if let Err(e) = copy_recursive(source, target) {
+                if !existed {
+                    let _ = remove_path(target);
+                }
+                return Err(e);
+            }
+            remove_path(source)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// Puts the file `source` in place of `target` with a single rename, so
+// `target` is never missing or half written. The file is first moved to a
+// hidden sibling of `target`; a copy across filesystems lands there too,
+// never in a file an earlier version may share through a hard link.
+pub fn replace_file(source: &Path, target: &Path) -> io::Result<()> {
+    let file_name = target.file_name().and_then(|name| name.to_str()).unwrap_or("file");
+    let partial_path = target.with_file_name(format!(".{}.partial", file_name));
+    let result = move_path(source, &partial_path).and_then(|_| fs::rename(&partial_path, target));
+    if result.is_err() {
+        let _ = fs::remove_file(&partial_path);
+    }
+    result
+}
+
+pub fn copy_recursive(source: &Path, target: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+
+    if metadata.is_dir() {
+        fs::create_dir_all(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+        }
+    } else if metadata.is_file() {
+        fs::copy(source, target)?;
+    }
+    // Symlinks and special files are left behind rather than followed
+
+    Ok(())
+}
+
+pub fn remove_path(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+// Total size of the regular files below `path`, not following symlinks
+pub fn total_size(path: &Path) -> u64 {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
+    };
+
+    if metadata.is_dir() {
+        fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|entry| total_size(&entry.path())).sum())
+            .unwrap_or(0)
+    } else if metadata.is_file() {
+        metadata.len()
+    } else {
+        0
+    }
+}
+
+enum CopyItem {
+    Dir { source: PathBuf, target: PathBuf, modified: Option<SystemTime> },
+    File { source: PathBuf, target: PathBuf, size: u64, modified: Option<SystemTime> },
+}
+
+// Copies each `(source, target)` pair, recursing into folders, as a job body.
+// Existing files are only replaced when `overwrite` is set; folders are merged.
+// Failures on individual files are recorded on the job and do not stop the copy.
+// Entries hidden from `access` are not copied.
+pub fn copy_items(items: &[(PathBuf, PathBuf)], overwrite: bool, access: &Access, job: &JobHandle) -> Result<String, String> {
+    let mut plan = Vec::new();
+    for (source, target) in items {
+        plan_copy(source, target, &mut plan, access, job)?;
+    }
+
+    let (files_total, bytes_total) = plan.iter().fold((0, 0), |(files, bytes), item| match item {
+        CopyItem::File { size, .. } => (files + 1, bytes + size),
+        CopyItem::Dir { .. } => (files, bytes),
+    });
+    job.set_totals(files_total, bytes_total);
+
+    let mut copied = 0;
+    for item in &plan {
+        if job.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        match item {
+            CopyItem::Dir { source, target, .. } => {
+                if let Err(e) = fs::create_dir_all(target) {
+                    job.add_error(format!("{}: {}", source.display(), e));
+                }
+            }
+            CopyItem::File { source, target, size, modified } => {
+                job.set_current(&source.file_name().unwrap_or_default().to_string_lossy());
+                if !overwrite && fs::symlink_metadata(target).is_ok() {
+                    job.add_error(format!("{}: already exists", target.display()));
+                    job.add_bytes(*size);
+                } else {
+                    match copy_file(source, target, *modified, job) {
+                        Ok(()) => copied += 1,
+                        Err(_) if job.is_cancelled() => return Err("Cancelled".to_string()),
+                        Err(e) => job.add_error(format!("{}: {}", source.display(), e)),
+                    }
+                }
+                job.file_done();
+            }
+        }
+    }
+
+    // Folder mtimes change as their contents are written, so set them last,
+    // deepest first
+    for item in plan.iter().rev() {
+        if let CopyItem::Dir { target, modified: Some(modified), .. } = item {
+            let _ = File::open(target).and_then(|dir| dir.set_modified(*modified));
+        }
+    }
+
+    Ok(format!("Copied {} of {} files", copied, files_total))
+}
+
+fn plan_copy(source: &Path, target: &Path, plan: &mut Vec<CopyItem>, access: &Access, job: &JobHandle) -> Result<(), String> {
+    if job.is_cancelled() {
+        return Err("Cancelled".to_string());
+    }
+    let metadata = fs::symlink_metadata(source).map_err(|e| format!("{}: {}", source.display(), e))?;
+    let modified = metadata.modified().ok();
+
+    if metadata.is_dir() {
+        plan.push(CopyItem::Dir { source: source.to_path_buf(), target: target.to_path_buf(), modified });
+        let entries = fs::read_dir(source).map_err(|e| format!("{}: {}", source.display(), e))?;
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if is_excluded_entry(&entry.path(), is_dir, access) {
+                continue;
+            }
+            if let Err(e) = plan_copy(&entry.path(), &target.join(&name), plan, access, job) {
+                job.add_error(e);
+            }
+        }
+    } else if metadata.is_file() {
+        plan.push(CopyItem::File { source: source.to_path_buf(), target: target.to_path_buf(), size: metadata.len(), modified });
+    }
+    // Symlinks and special files are skipped, as in the archive jobs
+
+    Ok(())
+}
+
+// Writes next to the target and renames into place, so a cancelled or failed
+// copy never leaves a truncated file under the real name
+fn copy_file(source: &Path, target: &Path, modified: Option<SystemTime>, job: &JobHandle) -> io::Result<()> {
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let partial_path = target.with_file_name(format!(".{}.partial", file_name));
+
+    let result = (|| {
+        let mut reader = ProgressReader::new(File::open(source)?, job);
+        let mut writer = BufWriter::new(File::create(&partial_path)?);
+        io::copy(&mut reader, &mut writer)?;
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        if let Some(modified) = modified {
+            let _ = file.set_modified(modified);
+        }
+        drop(file);
+        fs::rename(&partial_path, target)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&partial_path);
+    }
+    result
+}
@@ -0,0 +1,97 @@
+use crate::archive::{find_member, list_archive_directory, split_archive_path};
+use crate::file_browser::{paginate_entries, FileEntry, FileInfo, ListingOptions};
+use crate::visibility::Access;
+use hyper::{Response, StatusCode};
+
+use super::file_handlers::{directory_page, filter_system_entries, is_volume_list, volume_entries};
+use super::utils::{BoxBody, json_error, json_response, resolve_path};
+
+// JSON counterpart of /browse: the same entries, after the same filtering
+pub async fn list_directory_json(path: &str, query: Option<&str>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/api/list").unwrap_or("/");
+    let decoded = urlencoding::decode(relative_path)?.trim_matches('/').to_string();
+
+    // Same query parameters as /browse, but unpaged unless `limit` is given
+    let options = ListingOptions::from_query(query, None);
+    let page = if is_volume_list(relative_path) {
+        paginate_entries(volume_entries(), &options)
+    } else {
+        let dir_path = match resolve_path(relative_path, access) {
+            Some(dir_path) => dir_path,
+            None => return Ok(json_error(StatusCode::FORBIDDEN, "Access denied")),
+        };
+
+        if dir_path.is_dir() {
+            match directory_page(&dir_path, &options, access) {
+                Ok(page) => page,
+                Err(_) => return Ok(json_error(StatusCode::FORBIDDEN, "Access denied")),
+            }
+        } else if let Some((archive_path, inner)) = split_archive_path(&dir_path) {
+            match tokio::task::spawn_blocking(move || list_archive_directory(&archive_path, &inner)).await? {
+                Ok(Some(entries)) => paginate_entries(filter_system_entries(entries, access), &options),
+                Ok(None) => return Ok(json_error(StatusCode::NOT_FOUND, "Directory not found")),
+                Err(_) => return Ok(json_error(StatusCode::UNPROCESSABLE_ENTITY, "Archive could not be read")),
+            }
+        } else if dir_path.exists() {
+            return Ok(json_error(StatusCode::BAD_REQUEST, "Not a directory"));
+        } else {
+            return Ok(json_error(StatusCode::NOT_FOUND, "Directory not found"));
+        }
+    };
+    let items: Vec<FileInfo> = page.entries.iter()
+        .map(|entry| FileInfo::from_entry(entry, &join_relative(&decoded, &entry.name)))
+        .collect();
+
+    Ok(json_response(StatusCode::OK, serde_json::json!({
+        "status": "success",
+        "path": decoded,
+        "total": page.total,
+        "nextOffset": page.next_offset,
+        "entries": items
+    })))
+}
+
+pub async fn stat_path_json(path: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/api/stat").unwrap_or("/");
+    let decoded = urlencoding::decode(relative_path)?.trim_matches('/').to_string();
+
+    let item_path = match resolve_path(relative_path, access) {
+        Some(item_path) => item_path,
+        None => return Ok(json_error(StatusCode::FORBIDDEN, "Access denied")),
+    };
+    let name = item_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+
+    let entry = if let Ok(entry) = FileEntry::from_path(&item_path) {
+        entry
+    } else if let Some((archive_path, inner)) = split_archive_path(&item_path) {
+        let member = {
+            let (archive_path, inner) = (archive_path.clone(), inner.clone());
+            tokio::task::spawn_blocking(move || find_member(&archive_path, &inner)).await??
+        };
+        match member {
+            Some(member) => FileEntry::virtual_entry(name, item_path, false, Some(member.size)),
+            None => {
+                // Folders inside archives are often implied rather than stored
+                match tokio::task::spawn_blocking(move || list_archive_directory(&archive_path, &inner)).await? {
+                    Ok(Some(_)) => FileEntry::virtual_entry(name, item_path, true, None),
+                    _ => return Ok(json_error(StatusCode::NOT_FOUND, "Not found")),
+                }
+            }
+        }
+    } else {
+        return Ok(json_error(StatusCode::NOT_FOUND, "Not found"));
+    };
+
+    Ok(json_response(StatusCode::OK, serde_json::json!({
+        "status": "success",
+        "entry": FileInfo::from_entry(&entry, &decoded)
+    })))
+}
+
+fn join_relative(folder: &str, name: &str) -> String {
+    if folder.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", folder, name)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::archive::{archive_kind, create_archive, extract_archive};
+use crate::jobs::JOB_MANAGER;
+use crate::utils::collect_body_bytes;
+use crate::visibility::Access;
+use crate::volumes::{READ_ONLY_MESSAGE, VOLUMES};
+use hyper::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+
+use super::utils::{BoxBody, json_error, json_response, resolve_relative_path};
+
+pub async fn extract_archive_request(req: Request<Incoming>, username: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let archive_rel = request["path"].as_str().ok_or("Missing path")?;
+
+    let archive_path = match resolve_relative_path(archive_rel, access) {
+        Some(path) if path.is_file() => path,
+        _ => return Ok(json_error(StatusCode::NOT_FOUND, "Archive not found")),
+    };
+    let archive_name = archive_path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if archive_kind(&archive_name).is_none() {
+        return Ok(json_error(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Only .zip, .tar, .tar.gz and .tgz archives can be extracted"));
+    }
+
+    // Default to a folder named after the archive, next to it
+    let destination = match request["destination"].as_str() {
+        Some(destination_rel) => match resolve_relative_path(destination_rel, access) {
+            Some(path) => path,
+            None => return Ok(json_error(StatusCode::FORBIDDEN, "Invalid destination")),
+        },
+        None => {
+            let lower = archive_name.to_lowercase();
+            let stem_len = [".tar.gz", ".tgz", ".tar", ".zip"].iter()
+                .find(|ext| lower.ends_with(*ext))
+                .map(|ext| archive_name.len() - ext.len())
+                .unwrap_or(archive_name.len());
+            archive_path.with_file_name(&archive_name[..stem_len])
+        }
+    };
+    if VOLUMES.is_read_only(&destination) {
+        return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE));
+    }
+    if destination.exists() && !destination.is_dir() {
+        return Ok(json_error(StatusCode::CONFLICT, "Destination exists and is not a folder"));
+    }
+
+    let access = access.clone();
+    let job_id = JOB_MANAGER.start("extract", username, move |job| extract_archive(&archive_path, &destination, &access, job));
+
+    Ok(json_response(StatusCode::ACCEPTED, serde_json::json!({
+        "status": "success",
+        "jobId": job_id
+    })))
+}
+
+pub async fn compress_request(req: Request<Incoming>, username: &str, access: Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let paths = request["paths"].as_array().ok_or("Missing paths")?;
+    let destination_rel = request["destination"].as_str().ok_or("Missing destination")?;
+
+    if paths.is_empty() {
+        return Ok(json_error(StatusCode::BAD_REQUEST, "Nothing selected to compress"));
+    }
+
+    let mut sources = Vec::new();
+    for path in paths {
+        match path.as_str().and_then(|path| resolve_relative_path(path, &access)) {
+            Some(source) if source.exists() => sources.push(source),
+            _ => return Ok(json_error(StatusCode::NOT_FOUND, &format!("Not found: {}", path))),
+        }
+    }
+
+    let destination = match resolve_relative_path(destination_rel, &access) {
+        Some(path) => path,
+        None => return Ok(json_error(StatusCode::FORBIDDEN, "Invalid destination")),
+    };
+    let destination_name = destination.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if archive_kind(&destination_name).is_none() {
+        return Ok(json_error(StatusCode::BAD_REQUEST, "Destination must end in .zip, .tar, .tar.gz or .tgz"));
+    }
+    if VOLUMES.is_read_only(&destination) {
+        return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE));
+    }
+    if destination.exists() {
+        return Ok(json_error(StatusCode::CONFLICT, "Destination already exists"));
+    }
+    if !destination.parent().is_some_and(|parent| parent.is_dir()) {
+        return Ok(json_error(StatusCode::NOT_FOUND, "Destination folder not found"));
+    }
+
+    let job_id = JOB_MANAGER.start("compress", username, move |job| create_archive(&sources, &destination, &access, job));
+
+    Ok(json_response(StatusCode::ACCEPTED, serde_json::json!({
+        "status": "success",
+        "jobId": job_id
+    })))
+}
@@ -4,7 +4,7 @@ use hyper::{Request, Response};
 use hyper::body::Incoming;
 use std::sync::Arc;
 
-use super::utils::{BoxBody, html_response};
+use super::utils::{BoxBody, html_response, session_token};
 use crate::bodies::StringBody;
 
 pub async fn handle_login(
@@ -54,17 +54,8 @@ pub async fn handle_logout(
     auth_manager: Arc<AuthManager>,
 ) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
     // Extract session ID and logout
-    if let Some(cookie_header) = req.headers().get("cookie") {
-        if let Ok(cookie_str) = cookie_header.to_str() {
-            for cookie in cookie_str.split(';') {
-                let cookie = cookie.trim();
-                if cookie.starts_with("session_id=") {
-                    let session_id = &cookie[11..];
-                    auth_manager.logout(session_id);
-                    break;
-                }
-            }
-        }
+    if let Some(session_id) = session_token(&req) {
+        auth_manager.logout(session_id);
     }
     
     let response = Response::builder()
@@ -0,0 +1,53 @@
+use crate::duplicates::{find_duplicates, generate_duplicates_html, DUPLICATE_REPORTS};
+use crate::jobs::JOB_MANAGER;
+use crate::utils::{collect_body_bytes, format_file_size};
+use crate::visibility::Access;
+use hyper::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+
+use super::file_handlers::scan_roots;
+use super::utils::{BoxBody, html_response, json_error, json_response, query_param};
+
+pub async fn duplicates_page(query: Option<&str>) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let folder = query_param(query, "path").unwrap_or_default();
+    Ok(html_response(generate_duplicates_html(&folder)))
+}
+
+// Scans in the background; the report is fetched from /api/duplicates/<jobId>
+// once the job has completed
+pub async fn start_duplicate_scan(req: Request<Incoming>, username: String, access: Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let folder = request["path"].as_str().unwrap_or("").to_string();
+
+    let roots = match scan_roots(&folder, &access) {
+        Some(roots) if roots.iter().all(|(root, _)| root.is_dir()) => roots,
+        Some(_) => return Ok(json_error(StatusCode::NOT_FOUND, "Folder not found")),
+        None => return Ok(json_error(StatusCode::FORBIDDEN, "Access denied")),
+    };
+
+    let owner = username.clone();
+    let job_id = JOB_MANAGER.start("duplicates", &username, move |job| {
+        let report = find_duplicates(&roots, &folder, &access, job)?;
+        let message = format!("Found {} group(s) of duplicates, {} reclaimable",
+            report.groups.len(), format_file_size(report.reclaimable));
+        DUPLICATE_REPORTS.insert(job.id(), owner, report);
+        Ok(message)
+    });
+
+    Ok(json_response(StatusCode::ACCEPTED, serde_json::json!({
+        "status": "success",
+        "jobId": job_id
+    })))
+}
+
+pub async fn get_duplicate_report(path: &str, username: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let job_id = path.strip_prefix("/api/duplicates/").unwrap_or("");
+    match DUPLICATE_REPORTS.get(job_id, username) {
+        Some(report) => Ok(json_response(StatusCode::OK, serde_json::json!({
+            "status": "success",
+            "report": report
+        }))),
+        None => Ok(json_error(StatusCode::NOT_FOUND, "Report not found")),
+    }
+}
@@ -1,10 +1,18 @@
-use crate::bodies::{BytesBody, StringBody};
-use crate::file_browser::{generate_directory_html, get_directory_entries};
+use crate::archive::{split_archive_path, list_archive_directory, find_member, stream_member};
+use crate::bodies::{BytesBody, ChannelBody, StringBody};
+use crate::config::CONFIG;
+use crate::utils::escape_html;
+use crate::file_browser::{FileEntry, ListingOptions, ListingPage, DEFAULT_PAGE_SIZE, LISTINGS, generate_directory_html, get_directory_entries, page_of, paginate_entries, render_entry_tiles, sort_entries};
 use hyper::{Response, StatusCode};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
 
-use super::utils::{BoxBody, is_system_file_or_folder, get_home_directory, url_decode};
+use crate::editor::{content_etag, generate_editor_html, is_editable_file};
+use crate::viewer::{generate_source_view_html, generate_markdown_view_html, render_markdown, MAX_HIGHLIGHT_BYTES, MAX_README_BYTES};
+use crate::thumbnails::{THUMBNAIL_CACHE, DEFAULT_THUMBNAIL_SIZE, is_thumbnailable};
+use crate::visibility::Access;
+use crate::volumes::VOLUMES;
+use super::utils::{BoxBody, html_response, is_excluded_entry, resolve_path, resolve_relative_path, query_param};
 
 // Replace the home_page function:
 
@@ -13,7 +21,7 @@ pub fn home_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send
     <!DOCTYPE html>
     <html>
     <head>
-        <title>Rust Web Server</title>
+        <title>{app_title}</title>
         <meta charset="UTF-8">
         <style>
             body { font-family: Arial, sans-serif; margin: 0; padding: 20px; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; }
@@ -95,7 +103,7 @@ pub fn home_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send
             
             <div class="menu-container" id="menuContainer">
                 <div class="menu-header">
-                    <h2 class="menu-title">🦀 File Manager</h2>
+                    <h2 class="menu-title">🦀 {app_title}</h2>
                     <p class="menu-subtitle">Choose an action</p>
                 </div>
                 
@@ -225,7 +233,7 @@ pub fn home_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send
             function showUploadDialog() { window.location.href = '/upload'; closeMenu(); }
             function browseFolders() { window.location.href = '/browse'; closeMenu(); }
             function showSettings() { alert('Settings panel coming soon!\n\nPlanned features:\n• Theme customization\n• File view preferences\n• Upload settings\n• Security options'); closeMenu(); }
-            function showHelp() { alert('🦀 Web-based File Manager v1.0\n\nKeyboard Shortcuts:\n• Ctrl+B: Browse Files\n• Ctrl+U: Upload Files\n• F5: Refresh\n• Ctrl+,: Settings\n• F1: Help\n• Ctrl+L: Logout\n• Esc: Close Menu\n\nBuilt with Rust for maximum performance and security.\nSystem files are automatically filtered for your protection.\n\nFirxTTech Solutions © 2025'); closeMenu(); }
+            function showHelp() { alert('🦀 {app_title} v1.0\n\nKeyboard Shortcuts:\n• Ctrl+B: Browse Files\n• Ctrl+U: Upload Files\n• F5: Refresh\n• Ctrl+,: Settings\n• F1: Help\n• Ctrl+L: Logout\n• Esc: Close Menu\n\nBuilt with Rust for maximum performance and security.\nSystem files are automatically filtered for your protection.\n\n{organization} © 2025'); closeMenu(); }
             function logout() { if (confirm('Are you sure you want to logout?\nThis will end your current session.')) window.location.href = '/logout'; closeMenu(); }
             
             document.addEventListener('keydown', function(e) {
@@ -252,59 +260,55 @@ pub fn home_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send
         </script>
     </body>
     </html>
-    "#;
+    "#
+    .replace("{app_title}", &escape_html(&CONFIG.branding.title))
+    .replace("{organization}", &escape_html(&CONFIG.branding.organization));
 
     Ok(Response::builder()
         .status(StatusCode::OK)
         .header("Content-Type", "text/html; charset=utf-8")
-        .body(Box::new(StringBody::new(html.to_string())) as BoxBody)
+        .body(Box::new(StringBody::new(html)) as BoxBody)
         .unwrap())
 }
 
-pub async fn browse_directory(path: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
-    let home_dir = get_home_directory();
-    let fs_path = path.strip_prefix("/browse").unwrap_or("/");
+pub async fn browse_directory(path: &str, query: Option<&str>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/browse").unwrap_or("/");
     
-    let fs_path = if fs_path.is_empty() || fs_path == "/" {
-        // Start from home directory
-        home_dir.clone()
-    } else {
-        // Decode the URL-encoded path and construct path relative to home directory
-        let relative_path = &fs_path[1..]; // Remove leading slash
-        let decoded_relative_path = url_decode(relative_path);
-        format!("{}/{}", home_dir, decoded_relative_path.replace("/", "\\"))
-    };
+    let options = ListingOptions::from_query(query, Some(DEFAULT_PAGE_SIZE));
+    // Infinite scroll asks for just the tiles of the next page
+    let fragment = query_param(query, "fragment").is_some();
     
-    let dir_path = Path::new(&fs_path);
+    if is_volume_list(relative_path) {
+        let page = paginate_entries(volume_entries(), &options);
+        if fragment {
+            return Ok(listing_fragment(&page, path, access));
+        }
+        return Ok(html_response(generate_directory_html(&page, &options, path, None, access)));
+    }
     
-    if dir_path.exists() && dir_path.is_dir() {
-        match get_directory_entries(dir_path) {
-            Ok(entries) => {
-                // Filter out system files and folders
-                let filtered_entries: Vec<_> = entries.into_iter()
-                    .filter(|entry| {
-                        // Check if file has hidden attribute on Windows
-                        #[cfg(windows)]
-                        let is_hidden = {
-                            use std::os::windows::fs::MetadataExt;
-                            if let Ok(metadata) = entry.path.metadata() {
-                                const FILE_ATTRIBUTE_HIDDEN: u32 = 0x02;
-                                const FILE_ATTRIBUTE_SYSTEM: u32 = 0x04;
-                                let attrs = metadata.file_attributes();
-                                (attrs & FILE_ATTRIBUTE_HIDDEN) != 0 || (attrs & FILE_ATTRIBUTE_SYSTEM) != 0
-                            } else {
-                                false
-                            }
-                        };
-                        
-                        #[cfg(not(windows))]
-                        let is_hidden = false;
-                        
-                        !is_system_file_or_folder(&entry.name, is_hidden)
-                    })
-                    .collect();
-                
-                let html = generate_directory_html(&filtered_entries, path);
+    let dir_path = match resolve_path(relative_path, access) {
+        Some(dir_path) => dir_path,
+        None => {
+            return Ok(Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(Box::new(StringBody::new("Access denied".to_string())) as BoxBody)
+                .unwrap());
+        }
+    };
+    
+    if dir_path.is_dir() {
+        match directory_page(&dir_path, &options, access) {
+            Ok(page) => {
+                if fragment {
+                    return Ok(listing_fragment(&page, path, access));
+                }
+                // Only the first page carries the README
+                let readme_html = if options.offset == 0 {
+                    render_folder_readme(&dir_path, relative_path.trim_end_matches('/'), access)
+                } else {
+                    None
+                };
+                let html = generate_directory_html(&page, &options, path, readme_html.as_deref(), access);
                 Ok(Response::builder()
                     .status(StatusCode::OK)
                     .header("Content-Type", "text/html")
@@ -318,6 +322,35 @@ pub async fn browse_directory(path: &str) -> Result<Response<BoxBody>, Box<dyn s
                     .unwrap())
             }
         }
+    } else if let Some((archive_path, inner)) = split_archive_path(&dir_path) {
+        // Archives are browsed as read-only virtual folders
+        let listing = tokio::task::spawn_blocking(move || list_archive_directory(&archive_path, &inner)).await?;
+        match listing {
+            Ok(Some(entries)) => {
+                let page = paginate_entries(filter_system_entries(entries, access), &options);
+                if fragment {
+                    return Ok(listing_fragment(&page, path, access));
+                }
+                let html = generate_directory_html(&page, &options, path, None, access);
+                Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("Content-Type", "text/html")
+                    .body(Box::new(StringBody::new(html)) as BoxBody)
+                    .unwrap())
+            }
+            Ok(None) => {
+                Ok(Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Box::new(StringBody::new("Directory not found".to_string())) as BoxBody)
+                    .unwrap())
+            }
+            Err(e) => {
+                Ok(Response::builder()
+                    .status(StatusCode::UNPROCESSABLE_ENTITY)
+                    .body(Box::new(StringBody::new(format!("Unable to read archive: {}", e))) as BoxBody)
+                    .unwrap())
+            }
+        }
     } else {
         Ok(Response::builder()
             .status(StatusCode::NOT_FOUND)
@@ -326,10 +359,87 @@ pub async fn browse_directory(path: &str) -> Result<Response<BoxBody>, Box<dyn s
     }
 }
 
-pub async fn serve_file(path: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
-    let home_dir = get_home_directory();
+// Tiles for one page, with the offset of the next page (if any) in a header
+fn listing_fragment(page: &ListingPage, url_path: &str, access: &Access) -> Response<BoxBody> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/html; charset=utf-8")
+        .header("X-Total-Count", page.total)
+        .header("X-Next-Offset", page.next_offset.map(|offset| offset.to_string()).unwrap_or_default())
+        .body(Box::new(StringBody::new(render_entry_tiles(&page.entries, url_path, access))) as BoxBody)
+        .unwrap()
+}
+
+// One page of a folder on disk. Later pages come from the listing the first
+// page sorted, as long as the folder has not changed since.
+pub(super) fn directory_page(dir_path: &Path, options: &ListingOptions, access: &Access) -> std::io::Result<ListingPage> {
+    let modified = fs::metadata(dir_path)?.modified().ok();
+    if options.offset > 0
+        && let Some(entries) = LISTINGS.get(dir_path, modified, options, access)
+    {
+        return Ok(page_of(&entries, options));
+    }
+
+    let entries = sort_entries(filter_system_entries(get_directory_entries(dir_path)?, access), options);
+    let page = page_of(&entries, options);
+    LISTINGS.insert(dir_path, modified, options, access, std::sync::Arc::new(entries));
+    Ok(page)
+}
+
+pub(super) fn filter_system_entries(entries: Vec<FileEntry>, access: &Access) -> Vec<FileEntry> {
+    entries.into_iter()
+        .filter(|entry| !is_excluded_entry(&entry.path, entry.is_dir, access))
+        .collect()
+}
+
+// With named volumes the top level lists the volumes themselves
+pub(super) fn is_volume_list(relative_path: &str) -> bool {
+    VOLUMES.is_named() && relative_path.split('/').all(|component| component.is_empty())
+}
+
+// Where a recursive scan of `relative_path` starts, each folder paired with
+// its path as clients see it. On the volume list that is every volume.
+pub(super) fn scan_roots(relative_path: &str, access: &Access) -> Option<Vec<(PathBuf, String)>> {
+    if is_volume_list(relative_path) {
+        return Some(VOLUMES.list().iter()
+            .map(|volume| (volume.path.clone(), volume.name.clone()))
+            .collect());
+    }
+    resolve_relative_path(relative_path, access)
+        .map(|path| vec![(path, relative_path.trim_matches('/').to_string())])
+}
+
+pub(super) fn volume_entries() -> Vec<FileEntry> {
+    VOLUMES.list().iter()
+        .map(|volume| {
+            let mut entry = FileEntry::from_path(&volume.path)
+                .unwrap_or_else(|_| FileEntry::virtual_entry(String::new(), volume.path.clone(), true, None));
+            entry.name = volume.name.clone();
+            entry
+        })
+        .collect()
+}
+
+// Renders the folder's README.md, if any, for display under the grid. Like
+// opening it by path, a README hidden from `access` or a symlink, which
+// could point anywhere, is not shown.
+fn render_folder_readme(dir_path: &Path, url_path: &str, access: &Access) -> Option<String> {
+    let readme_path = fs::read_dir(dir_path).ok()?
+        .filter_map(|entry| entry.ok())
+        .find(|entry| entry.file_name().to_string_lossy().eq_ignore_ascii_case("README.md"))?
+        .path();
+
+    let metadata = fs::symlink_metadata(&readme_path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_README_BYTES || is_excluded_entry(&readme_path, false, access) {
+        return None;
+    }
+
+    let contents = fs::read_to_string(&readme_path).ok()?;
+    Some(render_markdown(&contents, url_path))
+}
+
+pub async fn serve_file(path: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
     let fs_path = path.strip_prefix("/file").unwrap_or("/");
-    let fs_path = if fs_path.starts_with('/') { &fs_path[1..] } else { fs_path };
     
     // Check if download parameter is present
     let (fs_path, force_download) = if let Some(pos) = fs_path.find('?') {
@@ -340,61 +450,69 @@ pub async fn serve_file(path: &str) -> Result<Response<BoxBody>, Box<dyn std::er
         (fs_path, false)
     };
     
-    // Decode the URL-encoded path
-    let decoded_fs_path = url_decode(fs_path);
-    
-    let full_path = format!("{}/{}", home_dir, decoded_fs_path.replace("/", "\\"));
-    let file_path = Path::new(&full_path);
+    send_file(fs_path, force_download, access).await
+}
+
+pub async fn serve_download(path: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let fs_path = path.strip_prefix("/download").unwrap_or("/");
     
+    // Always add download headers for this route
+    send_file(fs_path, true, access).await
+}
+
+async fn send_file(relative_path: &str, force_download: bool, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
     // Check if trying to access system file
-    if let Some(file_name) = file_path.file_name() {
-        if let Some(name_str) = file_name.to_str() {
-            #[cfg(windows)]
-            let is_hidden = {
-                use std::os::windows::fs::MetadataExt;
-                if let Ok(metadata) = file_path.metadata() {
-                    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x02;
-                    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x04;
-                    let attrs = metadata.file_attributes();
-                    (attrs & FILE_ATTRIBUTE_HIDDEN) != 0 || (attrs & FILE_ATTRIBUTE_SYSTEM) != 0
-                } else {
-                    false
-                }
-            };
-            
-            #[cfg(not(windows))]
-            let is_hidden = false;
-            
-            if is_system_file_or_folder(name_str, is_hidden) {
-                return Ok(Response::builder()
-                    .status(StatusCode::FORBIDDEN)
-                    .body(Box::new(StringBody::new("Access to system files is not allowed".to_string())) as BoxBody)
-                    .unwrap());
-            }
+    let file_path = match resolve_path(relative_path, access) {
+        Some(file_path) => file_path,
+        _ => {
+            return Ok(Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(Box::new(StringBody::new("Access to system files is not allowed".to_string())) as BoxBody)
+                .unwrap());
         }
+    };
+    
+    let file_name = file_path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mime_type = mime_guess::from_path(&file_path)
+        .first_or_octet_stream();
+    
+    let mut response_builder = Response::builder()
+        .header("Content-Type", mime_type.as_ref());
+    
+    // Add download headers if requested
+    if force_download {
+        response_builder = response_builder
+            .header("Content-Disposition", format!("attachment; filename=\"{}\"", file_name.replace('"', "")));
     }
     
-    if file_path.exists() && file_path.is_file() {
-        let contents = fs::read(file_path)?;
-        let mime_type = mime_guess::from_path(file_path)
-            .first_or_octet_stream();
-        
-        let mut response_builder = Response::builder()
-            .header("Content-Type", mime_type.as_ref());
-        
-        // Add download headers if requested
-        if force_download {
-            if let Some(filename) = file_path.file_name() {
-                if let Some(filename_str) = filename.to_str() {
-                    response_builder = response_builder
-                        .header("Content-Disposition", format!("attachment; filename=\"{}\"", filename_str));
-                }
-            }
-        }
+    if file_path.is_file() {
+        let contents = fs::read(&file_path)?;
         
         Ok(response_builder
             .body(Box::new(BytesBody::new(contents)) as BoxBody)
             .unwrap())
+    } else if let Some((archive_path, member)) = split_archive_path(&file_path) {
+        // Members of an archive are streamed straight out of it
+        let lookup_path = archive_path.clone();
+        let lookup_member = member.clone();
+        let found = tokio::task::spawn_blocking(move || find_member(&lookup_path, &lookup_member)).await??;
+        
+        match found {
+            Some(entry) => {
+                Ok(response_builder
+                    .header("Content-Length", entry.size)
+                    .body(Box::new(ChannelBody::new(stream_member(archive_path, member))) as BoxBody)
+                    .unwrap())
+            }
+            None => {
+                Ok(Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Box::new(StringBody::new("File not found".to_string())) as BoxBody)
+                    .unwrap())
+            }
+        }
     } else {
         Ok(Response::builder()
             .status(StatusCode::NOT_FOUND)
@@ -403,68 +521,149 @@ pub async fn serve_file(path: &str) -> Result<Response<BoxBody>, Box<dyn std::er
     }
 }
 
-pub async fn serve_download(path: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
-    let home_dir = get_home_directory();
-    let fs_path = path.strip_prefix("/download").unwrap_or("/");
-    let fs_path = if fs_path.starts_with('/') { &fs_path[1..] } else { fs_path };
-    
-    // Decode the URL-encoded path
-    let decoded_fs_path = url_decode(fs_path);
-    
-    let full_path = format!("{}/{}", home_dir, decoded_fs_path.replace("/", "\\"));
-    let file_path = Path::new(&full_path);
-    
-    // Check if trying to access system file
-    if let Some(file_name) = file_path.file_name() {
-        if let Some(name_str) = file_name.to_str() {
-            #[cfg(windows)]
-            let is_hidden = {
-                use std::os::windows::fs::MetadataExt;
-                if let Ok(metadata) = file_path.metadata() {
-                    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x02;
-                    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x04;
-                    let attrs = metadata.file_attributes();
-                    (attrs & FILE_ATTRIBUTE_HIDDEN) != 0 || (attrs & FILE_ATTRIBUTE_SYSTEM) != 0
-                } else {
-                    false
-                }
-            };
-            
-            #[cfg(not(windows))]
-            let is_hidden = false;
-            
-            if is_system_file_or_folder(name_str, is_hidden) {
-                return Ok(Response::builder()
-                    .status(StatusCode::FORBIDDEN)
-                    .body(Box::new(StringBody::new("Access to system files is not allowed".to_string())) as BoxBody)
-                    .unwrap());
-            }
+pub async fn serve_thumbnail(path: &str, query: Option<&str>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/thumb").unwrap_or("/");
+    let size = query_param(query, "size")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_THUMBNAIL_SIZE);
+
+    let file_path = match resolve_path(relative_path, access) {
+        Some(file_path) if file_path.is_file() => file_path,
+        _ => {
+            return Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Box::new(StringBody::new("File not found".to_string())) as BoxBody)
+                .unwrap());
         }
+    };
+
+    let is_supported = file_path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(is_thumbnailable);
+    if !is_supported {
+        return Ok(Response::builder()
+            .status(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+            .body(Box::new(StringBody::new("Thumbnails are not available for this file type".to_string())) as BoxBody)
+            .unwrap());
     }
-    
-    if file_path.exists() && file_path.is_file() {
-        let contents = fs::read(file_path)?;
-        let mime_type = mime_guess::from_path(file_path)
-            .first_or_octet_stream();
-        
-        let mut response_builder = Response::builder()
-            .header("Content-Type", mime_type.as_ref());
-        
-        // Always add download headers for this route
-        if let Some(filename) = file_path.file_name() {
-            if let Some(filename_str) = filename.to_str() {
-                response_builder = response_builder
-                    .header("Content-Disposition", format!("attachment; filename=\"{}\"", filename_str));
-            }
+
+    match THUMBNAIL_CACHE.get_or_create(&file_path, size).await {
+        Ok(thumbnail) => {
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "image/jpeg")
+                .header("Cache-Control", "private, max-age=300")
+                .header("ETag", format!("\"{}\"", thumbnail.etag))
+                .body(Box::new(BytesBody::new(thumbnail.data)) as BoxBody)
+                .unwrap())
+        }
+        Err(e) => {
+            eprintln!("❌ Thumbnail error for {}: {}", file_path.display(), e);
+            Ok(Response::builder()
+                .status(StatusCode::UNPROCESSABLE_ENTITY)
+                .body(Box::new(StringBody::new(e)) as BoxBody)
+                .unwrap())
         }
-        
-        Ok(response_builder
-            .body(Box::new(BytesBody::new(contents)) as BoxBody)
-            .unwrap())
-    } else {
-        Ok(Response::builder()
-            .status(StatusCode::NOT_FOUND)
-            .body(Box::new(StringBody::new("File not found".to_string())) as BoxBody)
-            .unwrap())
     }
-}
\ No newline at end of file
+}
+
+pub async fn view_file(path: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/view").unwrap_or("/");
+
+    // Oversized, binary or archived files fall back to the raw view
+    let raw_redirect = Response::builder()
+        .status(StatusCode::FOUND)
+        .header("Location", format!("/file{}", relative_path))
+        .body(Box::new(StringBody::new(String::new())) as BoxBody)
+        .unwrap();
+
+    let file_path = match resolve_path(relative_path, access) {
+        Some(file_path) if file_path.is_file() => file_path,
+        Some(file_path) if split_archive_path(&file_path).is_some() => return Ok(raw_redirect),
+        _ => {
+            return Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Box::new(StringBody::new("File not found".to_string())) as BoxBody)
+                .unwrap());
+        }
+    };
+
+    if fs::metadata(&file_path)?.len() > MAX_HIGHLIGHT_BYTES {
+        return Ok(raw_redirect);
+    }
+
+    let contents = match String::from_utf8(fs::read(&file_path)?) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(raw_redirect),
+    };
+
+    let file_name = file_path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let url_path = relative_path.to_string();
+
+    // Rendering is CPU bound, keep it off the async workers
+    let html = tokio::task::spawn_blocking(move || {
+        if file_name.to_lowercase().ends_with(".md") {
+            generate_markdown_view_html(&file_name, &contents, &url_path)
+        } else {
+            generate_source_view_html(&file_name, &contents, &url_path)
+        }
+    }).await?;
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/html; charset=utf-8")
+        .body(Box::new(StringBody::new(html)) as BoxBody)
+        .unwrap())
+}
+
+pub async fn edit_file(path: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/edit").unwrap_or("/");
+
+    let file_path = match resolve_path(relative_path, access) {
+        Some(file_path) if file_path.is_file() => file_path,
+        _ => {
+            return Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Box::new(StringBody::new("File not found".to_string())) as BoxBody)
+                .unwrap());
+        }
+    };
+
+    let file_name = file_path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if !is_editable_file(&file_name) {
+        return Ok(Response::builder()
+            .status(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+            .body(Box::new(StringBody::new("Only text files can be edited".to_string())) as BoxBody)
+            .unwrap());
+    }
+    if fs::metadata(&file_path)?.len() > CONFIG.limits.max_edit_size {
+        return Ok(Response::builder()
+            .status(StatusCode::PAYLOAD_TOO_LARGE)
+            .body(Box::new(StringBody::new("File is too large to edit in the browser".to_string())) as BoxBody)
+            .unwrap());
+    }
+
+    let bytes = fs::read(&file_path)?;
+    let etag = content_etag(&bytes);
+    let contents = match String::from_utf8(bytes) {
+        Ok(contents) => contents,
+        Err(_) => {
+            return Ok(Response::builder()
+                .status(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+                .body(Box::new(StringBody::new("File is not valid UTF-8 text".to_string())) as BoxBody)
+                .unwrap());
+        }
+    };
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/html; charset=utf-8")
+        .header("Cache-Control", "no-store")
+        .header("ETag", etag.as_str())
+        .body(Box::new(StringBody::new(generate_editor_html(&file_name, &contents, relative_path, &etag))) as BoxBody)
+        .unwrap())
+}
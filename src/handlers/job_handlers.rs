@@ -0,0 +1,28 @@
+use crate::jobs::JOB_MANAGER;
+use hyper::{Response, StatusCode};
+
+use super::utils::{BoxBody, json_error, json_response};
+
+pub async fn get_job_status(path: &str, username: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let job_id = path.strip_prefix("/api/jobs/").unwrap_or("");
+
+    match JOB_MANAGER.get_status(job_id, username) {
+        Some(status) => Ok(json_response(StatusCode::OK, serde_json::to_value(status)?)),
+        None => Ok(json_error(StatusCode::NOT_FOUND, "Job not found")),
+    }
+}
+
+pub async fn cancel_job(path: &str, username: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let job_id = path.strip_prefix("/api/jobs/")
+        .and_then(|rest| rest.strip_suffix("/cancel"))
+        .unwrap_or("");
+
+    if JOB_MANAGER.cancel(job_id, username) {
+        Ok(json_response(StatusCode::OK, serde_json::json!({
+            "status": "success",
+            "jobId": job_id
+        })))
+    } else {
+        Ok(json_error(StatusCode::NOT_FOUND, "Job not found"))
+    }
+}
@@ -3,10 +3,20 @@ use hyper::{Request, Response, Method, StatusCode};
 use std::sync::Arc;
 use std::convert::Infallible;
 
-mod utils;
+pub(crate) mod utils;
 mod auth_handlers;
 mod file_handlers;
 mod upload_handlers;
+mod archive_handlers;
+mod job_handlers;
+mod operation_handlers;
+mod trash_handlers;
+mod version_handlers;
+mod api_handlers;
+mod search_handlers;
+mod usage_handlers;
+mod duplicate_handlers;
+mod watch_handlers;
 
 use utils::{
     BoxBody, 
@@ -15,10 +25,22 @@ use utils::{
     html_response,
     not_found,
     is_authenticated,
+    current_username,
+    current_access,
 };
 
 use auth_handlers::{handle_login, handle_logout};
-use file_handlers::{home_page, browse_directory, serve_file, serve_download};
+use file_handlers::{home_page, browse_directory, serve_file, serve_download, serve_thumbnail, view_file, edit_file};
+use archive_handlers::{extract_archive_request, compress_request};
+use job_handlers::{get_job_status, cancel_job};
+use operation_handlers::{create_folder, rename_item, move_items, copy_items_request, delete_items, save_file};
+use api_handlers::{list_directory_json, stat_path_json};
+use search_handlers::{search_page, search_request, content_search_request};
+use usage_handlers::{usage_page, usage_json};
+use duplicate_handlers::{duplicates_page, start_duplicate_scan, get_duplicate_report};
+use watch_handlers::watch_directory;
+use version_handlers::{versions_page, get_versions, restore_version};
+use trash_handlers::{trash_page, list_trash, restore_trash_items, delete_trash_items, empty_trash};
 use upload_handlers::{
     upload_page, 
     handle_upload_request, 
@@ -45,12 +67,15 @@ pub async fn handle_request(
     let start_time = std::time::Instant::now();
     
     // Check if user is authenticated (except for login routes)
-    if uri.path() != "/login" && uri.path() != "/static" && !uri.path().starts_with("/static/") {
-        if !is_authenticated(&req, &auth_manager) {
-            return Ok(redirect_to_login());
-        }
+    if uri.path() != "/login" && uri.path() != "/static" && !uri.path().starts_with("/static/")
+        && !is_authenticated(&req, &auth_manager)
+    {
+        return Ok(redirect_to_login());
     }
     
+    let username = current_username(&req, &auth_manager).unwrap_or_default();
+    let access = current_access(&req, &auth_manager);
+    
     let result = match (method.clone(), uri.path()) {
         (Method::GET, "/login") => {
             html_response(crate::auth::generate_login_html())
@@ -83,7 +108,7 @@ pub async fn handle_request(
             }
         }
         (Method::GET, path) if path.starts_with("/browse") => {
-            match browse_directory(path).await {
+            match browse_directory(path, uri.query(), &access).await {
                 Ok(response) => response,
                 Err(e) => {
                     eprintln!("❌ Browse error for {}: {:?}", path, e);
@@ -92,7 +117,7 @@ pub async fn handle_request(
             }
         }
         (Method::GET, path) if path.starts_with("/file") => {
-            match serve_file(path).await {
+            match serve_file(path, &access).await {
                 Ok(response) => response,
                 Err(e) => {
                     eprintln!("❌ File serve error for {}: {:?}", path, e);
@@ -100,8 +125,89 @@ pub async fn handle_request(
                 }
             }
         }
+        (Method::GET, path) if path.starts_with("/view/") => {
+            match view_file(path, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ View error for {}: {:?}", path, e);
+                    create_error_response(StatusCode::NOT_FOUND, "File Not Found")
+                }
+            }
+        }
+        (Method::GET, path) if path.starts_with("/edit/") => {
+            match edit_file(path, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Edit page error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::PUT, path) if path.starts_with("/api/file/") => {
+            match save_file(req, path, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Save error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Save Failed")
+                }
+            }
+        }
+        (Method::GET, path) if path == "/api/list" || path.starts_with("/api/list/") => {
+            match list_directory_json(path, uri.query(), &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ List error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, path) if path.starts_with("/api/stat/") => {
+            match stat_path_json(path, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Stat error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, path) if path.starts_with("/versions/") => {
+            match versions_page(path, uri.query(), &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Versions page error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, path) if path.starts_with("/api/versions/") => {
+            match get_versions(path, uri.query(), &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Versions error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::POST, "/api/versions/restore") => {
+            match restore_version(req, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Restore version error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Restore Failed")
+                }
+            }
+        }
+        (Method::GET, path) if path.starts_with("/thumb/") => {
+            match serve_thumbnail(path, uri.query(), &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Thumbnail error for {}: {:?}", path, e);
+                    create_error_response(StatusCode::NOT_FOUND, "File Not Found")
+                }
+            }
+        }
         (Method::GET, path) if path.starts_with("/download") => {
-            match serve_download(path).await {
+            match serve_download(path, &access).await {
                 Ok(response) => response,
                 Err(e) => {
                     eprintln!("❌ Download error for {}: {:?}", path, e);
@@ -110,7 +216,7 @@ pub async fn handle_request(
             }
         }
         (Method::GET, "/upload") => {
-            match upload_page() {
+            match upload_page(uri.query(), &access) {
                 Ok(response) => response,
                 Err(e) => {
                     eprintln!("❌ Upload page error: {:?}", e);
@@ -119,7 +225,7 @@ pub async fn handle_request(
             }
         }
         (Method::POST, "/upload") => {
-            match handle_upload_request(req).await {
+            match handle_upload_request(req, &access).await {
                 Ok(response) => response,
                 Err(e) => {
                     eprintln!("❌ Upload error: {:?}", e);
@@ -128,7 +234,7 @@ pub async fn handle_request(
             }
         }
         (Method::POST, "/upload/start") => {
-            match start_resumable_upload(req).await {
+            match start_resumable_upload(req, &username, &access).await {
                 Ok(response) => response,
                 Err(e) => {
                     eprintln!("❌ Upload start error: {:?}", e);
@@ -137,7 +243,7 @@ pub async fn handle_request(
             }
         }
         (Method::POST, path) if path.starts_with("/upload/chunk/") => {
-            match upload_chunk(req, path).await {
+            match upload_chunk(req, path, &username).await {
                 Ok(response) => response,
                 Err(e) => {
                     eprintln!("❌ Upload chunk error for {}: {:?}", path, e);
@@ -146,7 +252,7 @@ pub async fn handle_request(
             }
         }
         (Method::POST, path) if path.starts_with("/upload/complete/") => {
-            match complete_upload(req, path).await {
+            match complete_upload(req, path, &username, &access).await {
                 Ok(response) => response,
                 Err(e) => {
                     eprintln!("❌ Upload complete error for {}: {:?}", path, e);
@@ -155,7 +261,7 @@ pub async fn handle_request(
             }
         }
         (Method::GET, path) if path.starts_with("/upload/status/") => {
-            match get_upload_status(req, path).await {
+            match get_upload_status(req, path, &username).await {
                 Ok(response) => response,
                 Err(e) => {
                     eprintln!("❌ Upload status error for {}: {:?}", path, e);
@@ -163,6 +269,213 @@ pub async fn handle_request(
                 }
             }
         }
+        (Method::POST, "/api/mkdir") => {
+            match create_folder(req, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Create folder error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Create Folder Failed")
+                }
+            }
+        }
+        (Method::POST, "/api/rename") => {
+            match rename_item(req, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Rename error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Rename Failed")
+                }
+            }
+        }
+        (Method::POST, "/api/copy") => {
+            match copy_items_request(req, &username, access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Copy error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Copy Failed")
+                }
+            }
+        }
+        (Method::POST, "/api/duplicates/scan") => {
+            match start_duplicate_scan(req, username, access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Duplicate scan error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Duplicate Scan Failed")
+                }
+            }
+        }
+        (Method::GET, path) if path.starts_with("/api/duplicates/") => {
+            match get_duplicate_report(path, &username).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Duplicate report error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, "/duplicates") => {
+            match duplicates_page(uri.query()).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Duplicates page error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::POST, "/api/delete") => {
+            match delete_items(req, username, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Delete error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Delete Failed")
+                }
+            }
+        }
+        (Method::GET, "/search") => {
+            match search_page(uri.query()).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Search page error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, "/api/search") => {
+            match search_request(uri.query(), access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Search error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, "/api/content-search") => {
+            match content_search_request(uri.query(), &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Content search error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, path) if path == "/usage" || path.starts_with("/usage/") => {
+            match usage_page(path, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Disk usage page error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, path) if path == "/api/usage" || path.starts_with("/api/usage/") => {
+            match usage_json(path, uri.query(), &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Disk usage error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, path) if path == "/api/watch" || path.starts_with("/api/watch/") => {
+            match watch_directory(path, access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Watch error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, "/trash") => {
+            match trash_page(username).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Trash page error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::GET, "/api/trash") => {
+            match list_trash(username).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Trash list error: {:?}", e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
+                }
+            }
+        }
+        (Method::POST, "/api/trash/restore") => {
+            match restore_trash_items(req, username, access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Restore error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Restore Failed")
+                }
+            }
+        }
+        (Method::POST, "/api/trash/delete") => {
+            match delete_trash_items(req, username).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Trash delete error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Delete Failed")
+                }
+            }
+        }
+        (Method::POST, "/api/trash/empty") => {
+            match empty_trash(username).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Empty trash error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Empty Trash Failed")
+                }
+            }
+        }
+        (Method::POST, "/api/move") => {
+            match move_items(req, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Move error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Move Failed")
+                }
+            }
+        }
+        (Method::POST, "/api/extract") => {
+            match extract_archive_request(req, &username, &access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Extract error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Extract Failed")
+                }
+            }
+        }
+        (Method::POST, "/api/compress") => {
+            match compress_request(req, &username, access).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Compress error: {:?}", e);
+                    create_error_response(StatusCode::BAD_REQUEST, "Compress Failed")
+                }
+            }
+        }
+        (Method::GET, path) if path.starts_with("/api/jobs/") => {
+            match get_job_status(path, &username).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Job status error for {}: {:?}", path, e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Status Check Failed")
+                }
+            }
+        }
+        (Method::POST, path) if path.starts_with("/api/jobs/") && path.ends_with("/cancel") => {
+            match cancel_job(path, &username).await {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("❌ Job cancel error for {}: {:?}", path, e);
+                    create_error_response(StatusCode::INTERNAL_SERVER_ERROR, "Cancel Failed")
+                }
+            }
+        }
         _ => {
             match not_found() {
                 Ok(response) => response,
@@ -0,0 +1,428 @@
+use crate::config::CONFIG;
+use crate::editor::{content_etag, is_editable_file};
+use crate::file_ops::{copy_items, move_path};
+use crate::jobs::JOB_MANAGER;
+use crate::trash::TRASH;
+use crate::content_index::reindex_in_background;
+use crate::versions::VERSIONS;
+use crate::utils::{collect_body_bytes, collect_body_bytes_limited};
+use crate::visibility::Access;
+use crate::volumes::{HIDDEN_NAME_MESSAGE, READ_ONLY_MESSAGE, VOLUMES};
+use hyper::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::utils::{BoxBody, json_error, json_response, resolve_path, resolve_relative_path, validate_file_name};
+
+pub async fn create_folder(req: Request<Incoming>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let parent_rel = request["path"].as_str().unwrap_or("");
+    let name = request["name"].as_str().ok_or("Missing name")?.trim();
+
+    if let Err(e) = validate_file_name(name) {
+        return Ok(json_error(StatusCode::BAD_REQUEST, e));
+    }
+
+    let parent = match resolve_relative_path(parent_rel, access) {
+        Some(parent) if parent.is_dir() => parent,
+        _ => return Ok(json_error(StatusCode::NOT_FOUND, "Folder not found")),
+    };
+    if VOLUMES.is_read_only(&parent) {
+        return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE));
+    }
+
+    let new_folder = parent.join(name);
+    if !VOLUMES.is_visible(&new_folder, true, access) {
+        return Ok(json_error(StatusCode::BAD_REQUEST, HIDDEN_NAME_MESSAGE));
+    }
+    match fs::create_dir(&new_folder) {
+        Ok(()) => {
+            println!("📁 Created folder {}", new_folder.display());
+            Ok(json_response(StatusCode::CREATED, serde_json::json!({
+                "status": "success",
+                "name": name
+            })))
+        }
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            Ok(json_error(StatusCode::CONFLICT, "A file or folder with that name already exists"))
+        }
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            Ok(json_error(StatusCode::FORBIDDEN, "Permission denied"))
+        }
+        Err(e) => Ok(json_error(StatusCode::INTERNAL_SERVER_ERROR, &format!("Failed to create folder: {}", e))),
+    }
+}
+
+pub async fn rename_item(req: Request<Incoming>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let source_rel = request["path"].as_str().ok_or("Missing path")?;
+    let new_name = request["newName"].as_str().ok_or("Missing newName")?.trim();
+    let overwrite = request["overwrite"].as_bool().unwrap_or(false);
+
+    if let Err(e) = validate_file_name(new_name) {
+        return Ok(json_error(StatusCode::BAD_REQUEST, e));
+    }
+
+    let source = match resolve_item(source_rel, access) {
+        Some(source) => source,
+        None => return Ok(json_error(StatusCode::NOT_FOUND, "File or folder not found")),
+    };
+    if VOLUMES.is_read_only(&source) {
+        return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE));
+    }
+    let target = source.with_file_name(new_name);
+    if !VOLUMES.is_visible(&target, source.is_dir(), access) {
+        return Ok(json_error(StatusCode::BAD_REQUEST, HIDDEN_NAME_MESSAGE));
+    }
+
+    if let Some(response) = check_target(&source, &target, overwrite) {
+        return Ok(response);
+    }
+
+    match move_path(&source, &target) {
+        Ok(()) => {
+            println!("✏️ Renamed {} to {}", source.display(), target.display());
+            Ok(json_response(StatusCode::OK, serde_json::json!({
+                "status": "success",
+                "name": new_name
+            })))
+        }
+        Err(e) => Ok(io_error_response("Failed to rename", e)),
+    }
+}
+
+pub async fn move_items(req: Request<Incoming>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let paths = request["paths"].as_array().ok_or("Missing paths")?;
+    let destination_rel = request["destination"].as_str().ok_or("Missing destination")?;
+    let overwrite = request["overwrite"].as_bool().unwrap_or(false);
+
+    let destination = match resolve_relative_path(destination_rel, access) {
+        Some(destination) if destination.is_dir() => destination,
+        _ => return Ok(json_error(StatusCode::NOT_FOUND, "Destination folder not found")),
+    };
+    if VOLUMES.is_read_only(&destination) {
+        return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE));
+    }
+
+    // Resolve and check everything up front so a bad entry does not leave
+    // the selection half moved
+    let mut moves = Vec::new();
+    let mut conflicts = Vec::new();
+    for path in paths {
+        let source = match path.as_str().and_then(|path| resolve_item(path, access)) {
+            Some(source) => source,
+            None => return Ok(json_error(StatusCode::NOT_FOUND, "File or folder not found")),
+        };
+        if VOLUMES.is_read_only(&source) {
+            return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE));
+        }
+        if destination.starts_with(&source) {
+            return Ok(json_error(StatusCode::BAD_REQUEST, "Cannot move a folder into itself"));
+        }
+
+        let name = source.file_name().ok_or("Invalid path")?.to_owned();
+        let target = destination.join(&name);
+        if target == source {
+            continue;
+        }
+        if fs::symlink_metadata(&target).is_ok() && (!overwrite || target.is_dir() || source.is_dir()) {
+            conflicts.push(name.to_string_lossy().into_owned());
+            continue;
+        }
+        moves.push((source, target));
+    }
+
+    if !conflicts.is_empty() {
+        let message = if overwrite { "Folders cannot be overwritten" } else { "Some items already exist in the destination" };
+        return Ok(json_response(StatusCode::CONFLICT, serde_json::json!({
+            "status": "error",
+            "error": message,
+            "conflicts": conflicts
+        })));
+    }
+
+    let mut moved = 0;
+    let mut errors = Vec::new();
+    for (source, target) in moves {
+        match move_path(&source, &target) {
+            Ok(()) => {
+                println!("📦 Moved {} to {}", source.display(), target.display());
+                moved += 1;
+            }
+            Err(e) => errors.push(format!("{}: {}", source.file_name().unwrap_or_default().to_string_lossy(), e)),
+        }
+    }
+
+    Ok(json_response(StatusCode::OK, serde_json::json!({
+        "status": if errors.is_empty() { "success" } else { "partial" },
+        "moved": moved,
+        "errors": errors
+    })))
+}
+
+// Copies run as a background job; progress is read from /api/jobs/<id>
+pub async fn copy_items_request(req: Request<Incoming>, username: &str, access: Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let paths = request["paths"].as_array().ok_or("Missing paths")?;
+    let destination_rel = request["destination"].as_str().ok_or("Missing destination")?;
+    let overwrite = request["overwrite"].as_bool().unwrap_or(false);
+
+    if paths.is_empty() {
+        return Ok(json_error(StatusCode::BAD_REQUEST, "Nothing selected to copy"));
+    }
+
+    let destination = match resolve_relative_path(destination_rel, &access) {
+        Some(destination) if destination.is_dir() => destination,
+        _ => return Ok(json_error(StatusCode::NOT_FOUND, "Destination folder not found")),
+    };
+    if VOLUMES.is_read_only(&destination) {
+        return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE));
+    }
+
+    let mut items = Vec::new();
+    let mut conflicts = Vec::new();
+    for path in paths {
+        let source = match path.as_str().and_then(|path| resolve_item(path, &access)) {
+            Some(source) => source,
+            None => return Ok(json_error(StatusCode::NOT_FOUND, "File or folder not found")),
+        };
+        if destination.starts_with(&source) {
+            return Ok(json_error(StatusCode::BAD_REQUEST, "Cannot copy a folder into itself"));
+        }
+
+        let name = source.file_name().ok_or("Invalid path")?.to_owned();
+        let mut target = destination.join(&name);
+        if target == source {
+            // Copying into the same folder makes a duplicate next to the original
+            target = duplicate_name(&source);
+        } else if fs::symlink_metadata(&target).is_ok() && !overwrite {
+            conflicts.push(name.to_string_lossy().into_owned());
+            continue;
+        }
+        items.push((source, target));
+    }
+
+    if !conflicts.is_empty() {
+        return Ok(json_response(StatusCode::CONFLICT, serde_json::json!({
+            "status": "error",
+            "error": "Some items already exist in the destination",
+            "conflicts": conflicts
+        })));
+    }
+
+    let job_id = JOB_MANAGER.start("copy", username, move |job| copy_items(&items, overwrite, &access, job));
+
+    Ok(json_response(StatusCode::ACCEPTED, serde_json::json!({
+        "status": "success",
+        "jobId": job_id
+    })))
+}
+
+// `report.pdf` -> `report (copy).pdf`, then `report (copy 2).pdf` and so on
+fn duplicate_name(source: &Path) -> PathBuf {
+    let name = source.file_name().unwrap_or_default().to_string_lossy();
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() && !source.is_dir() => (stem.to_string(), format!(".{}", extension)),
+        _ => (name.to_string(), String::new()),
+    };
+
+    (1..)
+        .map(|n| {
+            let suffix = if n == 1 { "copy".to_string() } else { format!("copy {}", n) };
+            source.with_file_name(format!("{} ({}){}", stem, suffix, extension))
+        })
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .unwrap()
+}
+
+// Saves are serialized so the ETag check and the rename cannot interleave
+// between two editors of the same file
+lazy_static::lazy_static! {
+    static ref SAVE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+// Replaces the contents of a text file from the editor. The client sends the
+// ETag it loaded in If-Match (or `*` to overwrite regardless); a mismatch
+// means someone else saved in between and is answered with 409.
+pub async fn save_file(req: Request<Incoming>, path: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/api/file").unwrap_or("/");
+    let if_match = req.headers().get("if-match")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string());
+
+    let file_path = match resolve_path(relative_path, access) {
+        Some(file_path) if file_path.is_file() => file_path,
+        _ => return Ok(json_error(StatusCode::NOT_FOUND, "File not found")),
+    };
+    if VOLUMES.is_read_only(&file_path) {
+        return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE));
+    }
+    let file_name = file_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    if !is_editable_file(&file_name) {
+        return Ok(json_error(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Only text files can be edited"));
+    }
+    let if_match = match if_match {
+        Some(if_match) => if_match,
+        None => return Ok(json_error(StatusCode::PRECONDITION_REQUIRED, "Missing If-Match header")),
+    };
+
+    // Refuse an oversized body up front when its length is declared, and
+    // otherwise stop reading once it passes the limit
+    let content_length = req.headers().get("content-length")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    let body = match content_length {
+        Some(length) if length > CONFIG.limits.max_edit_size => None,
+        _ => collect_body_bytes_limited(req.into_body(), CONFIG.limits.max_edit_size).await?,
+    };
+    let body = match body {
+        Some(body) => body,
+        None => return Ok(json_error(StatusCode::PAYLOAD_TOO_LARGE, "File is too large to edit in the browser")),
+    };
+    if std::str::from_utf8(&body).is_err() {
+        return Ok(json_error(StatusCode::BAD_REQUEST, "Contents must be UTF-8 text"));
+    }
+
+    let _guard = SAVE_LOCK.lock().await;
+
+    let current_etag = content_etag(&fs::read(&file_path)?);
+    if if_match != "*" && if_match != current_etag {
+        return Ok(json_response(StatusCode::CONFLICT, serde_json::json!({
+            "status": "error",
+            "error": "The file was changed since it was loaded",
+            "etag": current_etag
+        })));
+    }
+
+    let target = file_path.clone();
+    let contents = body.clone();
+    tokio::task::spawn_blocking(move || {
+        VERSIONS.save_version(&target)?;
+        write_atomically(&target, &contents)
+    }).await??;
+
+    let etag = content_etag(&body);
+    println!("💾 Saved {}", file_path.display());
+    reindex_in_background(file_path);
+    let mut response = json_response(StatusCode::OK, serde_json::json!({
+        "status": "success",
+        "etag": etag
+    }));
+    response.headers_mut().insert("ETag", etag.parse()?);
+    Ok(response)
+}
+
+// Writes a sibling temp file and renames it over `target`, so readers see
+// either the old or the new contents and never a partial write
+fn write_atomically(target: &Path, contents: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = target.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(target) {
+            fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        fs::rename(&temp_path, target)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+// Deleting never unlinks anything: items go to the user's trash, from where
+// they can be restored until the retention period runs out
+pub async fn delete_items(req: Request<Incoming>, username: String, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let paths = request["paths"].as_array().ok_or("Missing paths")?;
+
+    let mut items = Vec::new();
+    for path in paths {
+        let relative = path.as_str().ok_or("Invalid path")?;
+        match resolve_item(relative, access) {
+            Some(source) if VOLUMES.is_read_only(&source) => return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE)),
+            Some(source) => items.push((source, relative.to_string())),
+            None => return Ok(json_error(StatusCode::NOT_FOUND, "File or folder not found")),
+        }
+    }
+
+    let (trashed, errors) = tokio::task::spawn_blocking(move || {
+        let mut trashed = 0;
+        let mut errors = Vec::new();
+        for (source, relative) in items {
+            match TRASH.trash_item(&username, &source, &relative) {
+                Ok(_) => {
+                    println!("🗑️ Moved {} to the trash of {}", source.display(), username);
+                    trashed += 1;
+                }
+                Err(e) => errors.push(format!("{}: {}", relative, e)),
+            }
+        }
+        (trashed, errors)
+    }).await?;
+
+    Ok(json_response(StatusCode::OK, serde_json::json!({
+        "status": if errors.is_empty() { "success" } else { "partial" },
+        "trashed": trashed,
+        "errors": errors
+    })))
+}
+
+// Resolves an existing file or folder below a volume's root. The roots
+// themselves are never a valid source for rename or move.
+fn resolve_item(relative: &str, access: &Access) -> Option<PathBuf> {
+    if VOLUMES.is_root(relative) {
+        return None;
+    }
+    resolve_relative_path(relative, access).filter(|path| fs::symlink_metadata(path).is_ok())
+}
+
+// Returns the response to send if `target` cannot be replaced by `source`
+fn check_target(source: &Path, target: &Path, overwrite: bool) -> Option<Response<BoxBody>> {
+    if fs::symlink_metadata(target).is_err() {
+        return None;
+    }
+
+    // A case-only rename on a case-insensitive filesystem sees itself
+    let same_item = match (source.canonicalize(), target.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    };
+    if same_item {
+        return None;
+    }
+
+    if !overwrite {
+        return Some(json_response(StatusCode::CONFLICT, serde_json::json!({
+            "status": "error",
+            "error": "A file or folder with that name already exists",
+            "conflicts": [target.file_name().unwrap_or_default().to_string_lossy()]
+        })));
+    }
+    if source.is_dir() || target.is_dir() {
+        return Some(json_error(StatusCode::CONFLICT, "Folders cannot be overwritten"));
+    }
+    None
+}
+
+fn io_error_response(context: &str, e: io::Error) -> Response<BoxBody> {
+    match e.kind() {
+        io::ErrorKind::PermissionDenied => json_error(StatusCode::FORBIDDEN, "Permission denied"),
+        io::ErrorKind::NotFound => json_error(StatusCode::NOT_FOUND, "File or folder not found"),
+        _ => json_error(StatusCode::INTERNAL_SERVER_ERROR, &format!("{}: {}", context, e)),
+    }
+}
@@ -0,0 +1,56 @@
+use crate::bodies::ChannelBody;
+use crate::content_index::CONTENT_INDEX;
+use crate::search::{generate_search_html, search_names, SearchQuery};
+use crate::visibility::Access;
+use hyper::{Response, StatusCode, header};
+
+use super::file_handlers::{is_volume_list, scan_roots};
+use super::utils::{BoxBody, html_response, json_error, json_response, query_param, resolve_relative_path};
+
+pub async fn search_page(query: Option<&str>) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let folder = query_param(query, "path").unwrap_or_default();
+    Ok(html_response(generate_search_html(&folder)))
+}
+
+// Streams matches as newline-delimited JSON; see `search_names`
+pub async fn search_request(query: Option<&str>, access: Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let search = match SearchQuery::from_query(query) {
+        Ok(search) => search,
+        Err(e) => return Ok(json_error(StatusCode::BAD_REQUEST, &e)),
+    };
+    let folder = query_param(query, "path").unwrap_or_default();
+    let roots = match scan_roots(&folder, &access) {
+        Some(roots) => roots,
+        None => return Ok(json_error(StatusCode::FORBIDDEN, "Access denied")),
+    };
+    if roots.iter().any(|(root, _)| !root.is_dir()) {
+        return Ok(json_error(StatusCode::NOT_FOUND, "Folder not found"));
+    }
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Box::new(ChannelBody::new(search_names(roots, search, access))) as BoxBody)?)
+}
+
+// Ranked full-text matches from the content index
+pub async fn content_search_request(query: Option<&str>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let text = query_param(query, "q").unwrap_or_default();
+    if text.trim().is_empty() {
+        return Ok(json_error(StatusCode::BAD_REQUEST, "Enter something to search for"));
+    }
+    let folder = query_param(query, "path").unwrap_or_default();
+    if resolve_relative_path(&folder, access).is_none() && !is_volume_list(&folder) {
+        return Ok(json_error(StatusCode::FORBIDDEN, "Access denied"));
+    }
+
+    let access = access.clone();
+    let results = tokio::task::spawn_blocking(move || CONTENT_INDEX.search(&text, &folder, &access)).await?;
+    Ok(json_response(StatusCode::OK, serde_json::json!({
+        "status": "success",
+        "results": results,
+        "indexedFiles": CONTENT_INDEX.document_count(),
+        "indexing": CONTENT_INDEX.is_scanning()
+    })))
+}
@@ -0,0 +1,117 @@
+use crate::trash::{generate_trash_html, TRASH};
+use crate::utils::collect_body_bytes;
+use crate::visibility::Access;
+use crate::volumes::{READ_ONLY_MESSAGE, VOLUMES};
+use hyper::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+use std::io;
+
+use super::utils::{BoxBody, html_response, json_error, json_response, resolve_relative_path};
+
+pub async fn trash_page(username: String) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let entries = tokio::task::spawn_blocking(move || TRASH.list(&username)).await?;
+    Ok(html_response(generate_trash_html(&entries, TRASH.retention_days())))
+}
+
+pub async fn list_trash(username: String) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let entries = tokio::task::spawn_blocking(move || TRASH.list(&username)).await?;
+    Ok(json_response(StatusCode::OK, serde_json::json!({
+        "status": "success",
+        "retentionDays": TRASH.retention_days(),
+        "items": entries
+    })))
+}
+
+pub async fn restore_trash_items(req: Request<Incoming>, username: String, access: Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let ids = read_ids(req).await?;
+
+    let (restored, conflicts, errors) = tokio::task::spawn_blocking(move || {
+        let mut restored = 0;
+        let mut conflicts = Vec::new();
+        let mut errors = Vec::new();
+        for id in ids {
+            let entry = match TRASH.get(&username, &id) {
+                Some(entry) => entry,
+                None => {
+                    errors.push(format!("{}: not found in trash", id));
+                    continue;
+                }
+            };
+            let target = match resolve_relative_path(&entry.original_path, &access) {
+                Some(target) if VOLUMES.is_read_only(&target) => {
+                    errors.push(format!("{}: {}", entry.name, READ_ONLY_MESSAGE));
+                    continue;
+                }
+                Some(target) => target,
+                None => {
+                    errors.push(format!("{}: original location is not allowed", entry.name));
+                    continue;
+                }
+            };
+            match TRASH.restore(&username, &id, &target) {
+                Ok(()) => {
+                    println!("♻️ Restored {} from the trash of {}", target.display(), username);
+                    restored += 1;
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => conflicts.push(entry.original_path),
+                Err(e) => errors.push(format!("{}: {}", entry.name, e)),
+            }
+        }
+        (restored, conflicts, errors)
+    }).await?;
+
+    let status = if restored == 0 && !conflicts.is_empty() { StatusCode::CONFLICT } else { StatusCode::OK };
+    Ok(json_response(status, serde_json::json!({
+        "status": if conflicts.is_empty() && errors.is_empty() { "success" } else { "partial" },
+        "restored": restored,
+        "conflicts": conflicts,
+        "errors": errors
+    })))
+}
+
+pub async fn delete_trash_items(req: Request<Incoming>, username: String) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let ids = read_ids(req).await?;
+    let (deleted, errors) = tokio::task::spawn_blocking(move || delete_ids(&username, ids)).await?;
+    deleted_response(deleted, errors)
+}
+
+pub async fn empty_trash(username: String) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let (deleted, errors) = tokio::task::spawn_blocking(move || {
+        let ids = TRASH.list(&username).into_iter().map(|entry| entry.id).collect();
+        delete_ids(&username, ids)
+    }).await?;
+    deleted_response(deleted, errors)
+}
+
+async fn read_ids(req: Request<Incoming>) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let ids = request["ids"].as_array().ok_or("Missing ids")?;
+    Ok(ids.iter().filter_map(|id| id.as_str().map(str::to_string)).collect())
+}
+
+fn delete_ids(username: &str, ids: Vec<String>) -> (usize, Vec<String>) {
+    let mut deleted = 0;
+    let mut errors = Vec::new();
+    for id in ids {
+        match TRASH.delete_permanently(username, &id) {
+            Ok(()) => deleted += 1,
+            Err(e) => errors.push(format!("{}: {}", id, e)),
+        }
+    }
+    if deleted > 0 {
+        println!("🔥 Permanently deleted {} item(s) from the trash of {}", deleted, username);
+    }
+    (deleted, errors)
+}
+
+fn deleted_response(deleted: usize, errors: Vec<String>) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    if deleted == 0 && !errors.is_empty() {
+        return Ok(json_error(StatusCode::NOT_FOUND, &errors.join("; ")));
+    }
+    Ok(json_response(StatusCode::OK, serde_json::json!({
+        "status": if errors.is_empty() { "success" } else { "partial" },
+        "deleted": deleted,
+        "errors": errors
+    })))
+}
@@ -1,15 +1,38 @@
 use crate::bodies::StringBody;
-use crate::upload::handle_upload;
-use crate::utils::collect_body_bytes;
-use crate::resumable_upload::UPLOAD_MANAGER;
-use hyper::{Request, Response};
+use crate::config::CONFIG;
+use crate::multipart::{self, MultipartError, MultipartLimits};
+use crate::upload::{handle_upload, upload_path};
+use crate::utils::{collect_body_bytes, escape_html, format_file_size};
+use crate::resumable_upload::{UploadSession, UPLOAD_CHECKSUM_JS, UPLOAD_MANAGER};
+use crate::content_index::reindex_in_background;
+use crate::versions::VERSIONS;
+use crate::visibility::Access;
+use crate::volumes::{READ_ONLY_MESSAGE, VOLUMES};
+use hyper::{Request, Response, StatusCode};
 use hyper::body::Incoming;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use serde_json;
 
-use super::utils::{BoxBody, get_home_directory};
+use super::utils::{BoxBody, json_error, query_param, resolve_relative_path};
+
+// `dir` is the folder the page was opened from in /browse; without it files
+// go to the configured upload folder
+pub fn upload_page(query: Option<&str>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let dir = query_param(query, "dir");
+    let (target, back_url) = match &dir {
+        Some(dir) => {
+            let folder_url = dir.split('/').map(|part| urlencoding::encode(part).into_owned()).collect::<Vec<_>>().join("/");
+            let label = if dir.trim_matches('/').is_empty() { "/".to_string() } else { escape_html(dir) };
+            let problem = match upload_folder(Some(dir), access) {
+                Ok(_) => String::new(),
+                Err((_, message)) => format!(r#" <span class="target-error">{}</span>"#, message),
+            };
+            (format!(r#"<div class="upload-target" id="uploadTarget" data-dir="{}">📁 Uploading to <strong>{}</strong>{}</div>"#,
+                escape_html(dir), label, problem), format!("/browse/{}", folder_url))
+        }
+        None => (r#"<div class="upload-target" id="uploadTarget">📁 Uploading to the default upload folder</div>"#.to_string(), String::new()),
+    };
 
-pub fn upload_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
     let html = r#"
 <!DOCTYPE html>
 <html>
@@ -49,12 +72,15 @@ pub fn upload_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Se
         .nav-button.home { background: #f97316; }
         .nav-button.home:hover { background: #ea580c; box-shadow: 0 4px 15px rgba(249, 115, 22, 0.3); }
         .empty-state { text-align: center; color: #6c757d; font-style: italic; margin: 20px 0; }
+        .upload-target { text-align: center; color: #374151; margin: -10px 0 20px; word-break: break-all; }
+        .target-error { display: block; color: #dc2626; margin-top: 6px; }
         .hidden { display: none !important; }
     </style>
 </head>
 <body>
     <div class="container">
         <h1>📁 Upload Files</h1>
+        {target}
         
         <div class="select-files-link" id="selectFilesLink" onclick="showUploadArea()">+ Select Files</div>
         
@@ -82,8 +108,13 @@ pub fn upload_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Se
     </div>
 
     <script>
+        {checksum_js}
+        const targetDir = document.getElementById('uploadTarget').dataset.dir;
+        
         function goBack() {
-            if (window.history.length > 1) {
+            if ('{back_url}') {
+                window.location.href = '{back_url}';
+            } else if (window.history.length > 1) {
                 window.history.back();
             } else {
                 window.location.href = '/browse';
@@ -136,6 +167,7 @@ pub fn upload_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Se
                     completed: false,
                     failed: false,
                     startTime: Date.now(),
+                    fileHash: new Sha256(),
                     element: this.createUploadElement(file.name, uploadId)
                 };
                 
@@ -148,7 +180,8 @@ pub fn upload_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Se
                         headers: { 'Content-Type': 'application/json' },
                         body: JSON.stringify({
                             filename: file.name,
-                            totalSize: file.size
+                            totalSize: file.size,
+                            targetDir: targetDir
                         })
                     });
                     
@@ -187,13 +220,15 @@ pub fn upload_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Se
                     const chunk = upload.file.slice(start, end);
                     
                     try {
+                        const bytes = new Uint8Array(await chunk.arrayBuffer());
                         const response = await fetch(`/upload/chunk/${upload.sessionId}`, {
                             method: 'POST',
                             headers: {
                                 'Content-Range': `bytes ${start}-${end-1}/${upload.totalSize}`,
-                                'Content-Type': 'application/octet-stream'
+                                'Content-Type': 'application/octet-stream',
+                                'X-Chunk-SHA256': await chunkChecksum(bytes)
                             },
-                            body: chunk
+                            body: bytes
                         });
                         
                         if (!response.ok) {
@@ -202,6 +237,7 @@ pub fn upload_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Se
                         }
                         
                         const result = await response.json();
+                        upload.fileHash.update(bytes);
                         upload.uploadedSize = result.uploadedSize;
                         this.updateProgress(upload);
                         
@@ -230,10 +266,7 @@ pub fn upload_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Se
                     const response = await fetch(`/upload/complete/${upload.sessionId}`, {
                         method: 'POST',
                         headers: { 'Content-Type': 'application/json' },
-                        body: JSON.stringify({
-                            filename: upload.file.name,
-                            finalPath: `/uploads/${upload.file.name}`
-                        })
+                        body: JSON.stringify({ sha256: upload.fileHash.hex() })
                     });
                     
                     if (response.ok) {
@@ -457,36 +490,90 @@ pub fn upload_page() -> Result<Response<BoxBody>, Box<dyn std::error::Error + Se
     </script>
 </body>
 </html>
-    "#;
+    "#
+    .replace("{target}", &target)
+    .replace("{back_url}", &back_url)
+    .replace("{checksum_js}", UPLOAD_CHECKSUM_JS);
 
     Ok(Response::builder()
         .status(200)
         .header("Content-Type", "text/html")
-        .body(Box::new(StringBody::new(html.to_string())) as BoxBody)?)
+        .body(Box::new(StringBody::new(html)) as BoxBody)?)
 }
 
-pub async fn handle_upload_request(req: Request<Incoming>) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
-    use hyper::StatusCode;
+// The folder `dir` names, if the user can see it and it is writable, or the
+// configured upload folder when `dir` is None
+fn upload_folder(dir: Option<&str>, access: &Access) -> Result<PathBuf, (StatusCode, &'static str)> {
+    let dir = match dir {
+        Some(dir) => dir,
+        None => return Ok(CONFIG.upload_dir.clone()),
+    };
+    match resolve_relative_path(dir, access) {
+        Some(folder) if !folder.is_dir() => Err((StatusCode::NOT_FOUND, "Folder not found")),
+        Some(folder) if VOLUMES.is_read_only(&folder) => Err((StatusCode::FORBIDDEN, READ_ONLY_MESSAGE)),
+        Some(folder) => Ok(folder),
+        None => Err((StatusCode::NOT_FOUND, "Folder not found")),
+    }
+}
+
+// Where a resumable upload of `file_name` into `dir` is saved
+fn upload_target(dir: Option<&str>, file_name: &str, access: &Access) -> Result<PathBuf, (StatusCode, &'static str)> {
+    let folder = upload_folder(dir, access)?;
+    upload_path(&folder, file_name, access).map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+// The target folder is taken from the `dir` query parameter
+pub async fn handle_upload_request(req: Request<Incoming>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let folder = match upload_folder(query_param(req.uri().query(), "dir").as_deref(), access) {
+        Ok(folder) => folder,
+        Err((status, message)) => return Ok(text_response(status, message.to_string())),
+    };
     
-    match collect_body_bytes(req.into_body()).await {
-        Ok(body_bytes) => {
-            let response = handle_upload(body_bytes).await;
-            Ok(Response::builder()
-                .status(StatusCode::OK)
-                .header("Content-Type", "text/plain")
-                .body(Box::new(StringBody::new(response)) as BoxBody)
-                .unwrap())
+    let content_length = req.headers().get("content-length")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if content_length.is_some_and(|length| CONFIG.exceeds_upload_limit(length)) {
+        return Ok(text_response(StatusCode::PAYLOAD_TOO_LARGE, upload_limit_message()));
+    }
+    
+    let boundary = match req.headers().get("content-type").and_then(|value| value.to_str().ok()).and_then(multipart::boundary) {
+        Some(boundary) => boundary,
+        None => return Ok(text_response(StatusCode::BAD_REQUEST, "Expected a multipart/form-data body".to_string())),
+    };
+    
+    // Chunked bodies have no Content-Length, so each file is also held to
+    // the limit as it streams in
+    let limits = MultipartLimits { max_file_size: CONFIG.limits.max_upload_size, ..MultipartLimits::default() };
+    let report = handle_upload(req.into_body(), &boundary, &folder, access, limits).await;
+    
+    let mut lines: Vec<String> = report.saved.iter()
+        .map(|name| format!("File '{}' uploaded successfully to {}!", name, folder.display()))
+        .collect();
+    lines.extend(report.rejected.iter().map(|rejected| format!("Not saved: {}", rejected)));
+    let status = match &report.error {
+        Some(error) => {
+            eprintln!("❌ Upload into {} failed: {}", folder.display(), error);
+            lines.push(error.to_string());
+            if error.is_too_large() {
+                StatusCode::PAYLOAD_TOO_LARGE
+            } else if matches!(error, MultipartError::Io(_)) {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::BAD_REQUEST
+            }
         }
-        Err(_) => {
-            Ok(Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Box::new(StringBody::new("Error processing upload".to_string())) as BoxBody)
-                .unwrap())
+        None if report.saved.is_empty() => {
+            if report.rejected.is_empty() {
+                lines.push("No file data received".to_string());
+            }
+            StatusCode::BAD_REQUEST
         }
-    }
+        None => StatusCode::OK,
+    };
+    Ok(text_response(status, lines.join("\n")))
 }
 
-pub async fn start_resumable_upload(req: Request<Incoming>) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+pub async fn start_resumable_upload(req: Request<Incoming>, username: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
     let body = collect_body_bytes(req.into_body()).await?;
     let body_str = String::from_utf8(body.to_vec())?;
     
@@ -494,9 +581,22 @@ pub async fn start_resumable_upload(req: Request<Incoming>) -> Result<Response<B
     let upload_request: serde_json::Value = serde_json::from_str(&body_str)?;
     let filename = upload_request["filename"].as_str().unwrap();
     let total_size = upload_request["totalSize"].as_u64().unwrap();
+    let target_dir = upload_request["targetDir"].as_str().map(str::to_string);
+    let file_hash = upload_request["sha256"].as_str();
+    if file_hash.is_some_and(|hash| !is_sha256_hex(hash)) {
+        return Ok(json_error(StatusCode::BAD_REQUEST, SHA256_FORMAT_MESSAGE));
+    }
+    if CONFIG.exceeds_upload_limit(total_size) {
+        return Ok(json_error(StatusCode::PAYLOAD_TOO_LARGE, &upload_limit_message()));
+    }
+    
+    // Refuse a bad target now rather than after the data has been sent
+    if let Err((status, message)) = upload_target(target_dir.as_deref(), filename, access) {
+        return Ok(json_error(status, message));
+    }
     
     // Create upload session using global manager
-    let session_id = UPLOAD_MANAGER.create_session(filename, total_size);
+    let session_id = UPLOAD_MANAGER.create_session(username, filename, total_size, target_dir, file_hash);
     
     let response = serde_json::json!({
         "sessionId": session_id,
@@ -510,8 +610,11 @@ pub async fn start_resumable_upload(req: Request<Incoming>) -> Result<Response<B
         .body(Box::new(StringBody::new(response.to_string())) as BoxBody)?)
 }
 
-pub async fn upload_chunk(req: Request<Incoming>, path: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+pub async fn upload_chunk(req: Request<Incoming>, path: &str, username: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
     let session_id = path.strip_prefix("/upload/chunk/").unwrap();
+    if UPLOAD_MANAGER.get_session(session_id, username).is_none() {
+        return Ok(json_error(StatusCode::NOT_FOUND, "Session not found"));
+    }
     
     // Get chunk offset from headers
     let offset = req.headers()
@@ -519,21 +622,22 @@ pub async fn upload_chunk(req: Request<Incoming>, path: &str) -> Result<Response
         .and_then(|h| h.to_str().ok())
         .and_then(|s| {
             // Parse "bytes 0-1023/1024" format
-            if let Some(bytes_part) = s.strip_prefix("bytes ") {
-                if let Some(range_part) = bytes_part.split('/').next() {
-                    if let Some(start_str) = range_part.split('-').next() {
-                        return start_str.parse::<u64>().ok();
-                    }
-                }
-            }
-            None
+            let range_part = s.strip_prefix("bytes ")?.split('/').next()?;
+            range_part.split('-').next()?.parse::<u64>().ok()
         })
         .unwrap_or(0);
     
+    // SHA-256 of this chunk as the client computed it
+    let checksum = match req.headers().get("X-Chunk-SHA256").and_then(|h| h.to_str().ok()) {
+        Some(hash) if is_sha256_hex(hash) => hash.to_string(),
+        Some(_) => return Ok(json_error(StatusCode::BAD_REQUEST, SHA256_FORMAT_MESSAGE)),
+        None => return Ok(json_error(StatusCode::BAD_REQUEST, "Missing X-Chunk-SHA256 header")),
+    };
+    
     let body = collect_body_bytes(req.into_body()).await?;
     
     // Use global upload manager
-    match UPLOAD_MANAGER.upload_chunk(session_id, &body, offset) {
+    match UPLOAD_MANAGER.upload_chunk(session_id, username, &body, offset, &checksum) {
         Ok(uploaded_size) => {
             let response = serde_json::json!({
                 "uploadedSize": uploaded_size,
@@ -559,29 +663,55 @@ pub async fn upload_chunk(req: Request<Incoming>, path: &str) -> Result<Response
     }
 }
 
-pub async fn complete_upload(req: Request<Incoming>, path: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+// The target named when the upload started is checked again, since the
+// folder may have gone or changed while the data was being sent. The body
+// may carry the file's SHA-256 if it was not given at the start.
+pub async fn complete_upload(req: Request<Incoming>, path: &str, username: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
     let session_id = path.strip_prefix("/upload/complete/").unwrap();
     
     let body = collect_body_bytes(req.into_body()).await?;
-    let body_str = String::from_utf8(body.to_vec())?;
-    
-    // Parse JSON request
-    let complete_request: serde_json::Value = serde_json::from_str(&body_str)?;
-    let filename = complete_request["filename"].as_str().unwrap();
-    let _final_path = complete_request["finalPath"].as_str().unwrap();
+    let complete_request: serde_json::Value = if body.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_slice(&body)?
+    };
+    let file_hash = complete_request["sha256"].as_str().map(str::to_string);
+    if file_hash.as_deref().is_some_and(|hash| !is_sha256_hex(hash)) {
+        return Ok(json_error(StatusCode::BAD_REQUEST, SHA256_FORMAT_MESSAGE));
+    }
     
-    // Get home directory and construct Desktop\Uploads path
-    let home_dir = get_home_directory();
-    let final_path = format!("{}\\Desktop\\Uploads\\{}", home_dir, filename);
+    let session = match UPLOAD_MANAGER.get_session(session_id, username) {
+        Some(session) => session,
+        None => return Ok(json_error(StatusCode::NOT_FOUND, "Session not found")),
+    };
+    let final_path = match upload_target(session.target_dir.as_deref(), &session.file_name, access) {
+        Ok(final_path) => final_path.to_string_lossy().into_owned(),
+        Err((status, message)) => return Ok(json_error(status, message)),
+    };
     
-    // Ensure Desktop\Uploads directory exists
-    if let Some(parent) = Path::new(&final_path).parent() {
-        std::fs::create_dir_all(parent)?;
-    }
+    // Nothing is moved into place until the assembled file checks out
+    let (verify_id, verify_user) = (session_id.to_string(), username.to_string());
+    let session = match tokio::task::spawn_blocking(move || UPLOAD_MANAGER.finish_upload(&verify_id, &verify_user, file_hash.as_deref())).await? {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("❌ Upload {} of {} failed verification: {}", session_id, session.file_name, e);
+            return Ok(json_error(StatusCode::BAD_REQUEST, &e));
+        }
+    };
     
-    // Use global upload manager
-    match UPLOAD_MANAGER.complete_upload(session_id, &final_path) {
+    // The session is gone now, so a failure from here on discards the upload.
+    // Keeping a version and moving the file may copy it, so off the runtime.
+    let save_path = final_path.clone();
+    let saved = tokio::task::spawn_blocking(move || {
+        let saved = save_upload(&session, &save_path);
+        if saved.is_err() {
+            UPLOAD_MANAGER.discard(&session);
+        }
+        saved
+    }).await?;
+    match saved {
         Ok(()) => {
+            reindex_in_background(PathBuf::from(&final_path));
             let response = serde_json::json!({
                 "status": "success",
                 "message": "Upload completed successfully",
@@ -600,18 +730,31 @@ pub async fn complete_upload(req: Request<Incoming>, path: &str) -> Result<Respo
             });
             
             Ok(Response::builder()
-                .status(400)
+                .status(500)
                 .header("Content-Type", "application/json")
                 .body(Box::new(StringBody::new(response.to_string())) as BoxBody)?)
         }
     }
 }
 
-pub async fn get_upload_status(_req: Request<Incoming>, path: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+fn save_upload(session: &UploadSession, final_path: &str) -> Result<(), String> {
+    // Ensure the upload directory exists
+    if let Some(parent) = Path::new(final_path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create the folder: {}", e))?;
+    }
+    
+    // Keep the file being replaced, if any, as an earlier version
+    VERSIONS.save_version(Path::new(final_path))
+        .map_err(|e| format!("Failed to keep the previous version: {}", e))?;
+    
+    UPLOAD_MANAGER.complete_upload(session, final_path)
+}
+
+pub async fn get_upload_status(_req: Request<Incoming>, path: &str, username: &str) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
     let session_id = path.strip_prefix("/upload/status/").unwrap();
     
     // Use global upload manager
-    match UPLOAD_MANAGER.get_session(session_id) {
+    match UPLOAD_MANAGER.get_session(session_id, username) {
         Some(session) => {
             let response = serde_json::json!({
                 "status": "success",
@@ -638,4 +781,22 @@ pub async fn get_upload_status(_req: Request<Incoming>, path: &str) -> Result<Re
                 .body(Box::new(StringBody::new(response.to_string())) as BoxBody)?)
         }
     }
-}
\ No newline at end of file
+}
+
+const SHA256_FORMAT_MESSAGE: &str = "SHA-256 checksums must be 64 hexadecimal characters";
+
+fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn text_response(status: StatusCode, text: String) -> Response<BoxBody> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(Box::new(StringBody::new(text)) as BoxBody)
+        .unwrap()
+}
+
+fn upload_limit_message() -> String {
+    format!("Uploads are limited to {}", format_file_size(CONFIG.limits.max_upload_size))
+}
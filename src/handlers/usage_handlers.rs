@@ -0,0 +1,73 @@
+use crate::disk_usage::{filesystem_space, generate_usage_html, FOLDER_SIZES};
+use crate::visibility::Access;
+use crate::volumes::VOLUMES;
+use hyper::{Response, StatusCode};
+
+use super::file_handlers::is_volume_list;
+use super::utils::{BoxBody, html_response, json_error, json_response, not_found, query_param, resolve_path};
+
+pub async fn usage_page(path: &str, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/usage").unwrap_or("/");
+    let decoded = urlencoding::decode(relative_path)?.into_owned();
+    match resolve_path(relative_path, access) {
+        Some(dir_path) if dir_path.is_dir() => Ok(html_response(generate_usage_html(&decoded))),
+        _ if is_volume_list(relative_path) => Ok(html_response(generate_usage_html(&decoded))),
+        _ => not_found(),
+    }
+}
+
+// Recursive sizes of the folder's children, largest first, counting only what
+// the viewer may see. Sizes are cached for a few minutes; `?refresh=1`
+// recomputes them. On the volume list the children are the volumes, which
+// may sit on different filesystems.
+pub async fn usage_json(path: &str, query: Option<&str>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/api/usage").unwrap_or("/");
+    let decoded = urlencoding::decode(relative_path)?.trim_matches('/').to_string();
+    let refresh = query_param(query, "refresh").is_some();
+    let access = access.clone();
+
+    if is_volume_list(relative_path) {
+        let (total, entries) = tokio::task::spawn_blocking(move || {
+            if refresh {
+                for volume in VOLUMES.list() {
+                    FOLDER_SIZES.invalidate(&volume.path);
+                }
+            }
+            FOLDER_SIZES.volume_usage(&access)
+        }).await?;
+        return Ok(json_response(StatusCode::OK, serde_json::json!({
+            "status": "success",
+            "path": decoded,
+            "size": total.size,
+            "files": total.files,
+            "folders": total.folders,
+            "filesystem": null,
+            "entries": entries
+        })));
+    }
+
+    let dir_path = match resolve_path(relative_path, &access) {
+        Some(dir_path) if dir_path.is_dir() => dir_path,
+        Some(dir_path) if dir_path.exists() => return Ok(json_error(StatusCode::BAD_REQUEST, "Not a directory")),
+        Some(_) => return Ok(json_error(StatusCode::NOT_FOUND, "Directory not found")),
+        None => return Ok(json_error(StatusCode::FORBIDDEN, "Access denied")),
+    };
+
+    let (total, entries, filesystem) = tokio::task::spawn_blocking(move || {
+        if refresh {
+            FOLDER_SIZES.invalidate(&dir_path);
+        }
+        let (total, entries) = FOLDER_SIZES.usage(&dir_path, &access);
+        (total, entries, filesystem_space(&dir_path))
+    }).await?;
+
+    Ok(json_response(StatusCode::OK, serde_json::json!({
+        "status": "success",
+        "path": decoded,
+        "size": total.size,
+        "files": total.files,
+        "folders": total.folders,
+        "filesystem": filesystem,
+        "entries": entries
+    })))
+}
@@ -1,5 +1,8 @@
 use hyper::{Response, StatusCode, header};
 use std::env;
+use crate::visibility::Access;
+use crate::volumes::VOLUMES;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use hyper::body::{Body, Bytes, Frame};
@@ -36,83 +39,32 @@ impl Body for StringBody {
     }
 }
 
-// System files and folders to exclude
-pub const SYSTEM_EXCLUSIONS: &[&str] = &[
-    // Windows system directories
-    "System Volume Information",
-    "$Recycle.Bin",
-    "$RECYCLE.BIN",
-    "Config.Msi",
-    "PerfLogs",
-    "Recovery",
-    "Windows",
-    "Program Files",
-    "Program Files (x86)",
-    "ProgramData",
-    "AppData",
-    "Users/All Users",
-    "Users/Default",
-    "Users/Default User",
-    "Users/Public",
-    
-    // Hidden/system files
-    "pagefile.sys",
-    "hiberfil.sys",
-    "swapfile.sys",
-    "bootmgr",
-    "BOOTNXT",
-    "BOOTSECT.BAK",
-    "ntldr",
-    "NTDETECT.COM",
-    "boot.ini",
-    "Desktop.ini",
-    
-    // Mac system files
-    ".DS_Store",
-    ".Trash",
-    ".fseventsd",
-    ".Spotlight-V100",
-    ".TemporaryItems",
-    
-    // Linux system files
-    ".bash_history",
-    ".bashrc",
-    ".profile",
-    
-    // Common system/hidden patterns
-    "desktop.ini",
-    "thumbs.db",
-    "Thumbs.db",
-    ".tmp",
-    ".temp",
-];
-
-pub fn is_system_file_or_folder(name: &str, is_hidden: bool) -> bool {
-    // Check if it's a hidden file/folder (starts with .)
-    if is_hidden || name.starts_with('.') {
-        return true;
-    }
-    
-    // Check against system exclusions list
-    for exclusion in SYSTEM_EXCLUSIONS {
-        if name.eq_ignore_ascii_case(exclusion) {
-            return true;
-        }
-    }
-    
-    // Check for temporary files
-    if name.ends_with(".tmp") || name.ends_with(".temp") || name.ends_with("~") {
-        return true;
+// Check if file has hidden attribute on Windows
+#[cfg(windows)]
+pub fn is_hidden_file(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    if let Ok(metadata) = path.metadata() {
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x02;
+        const FILE_ATTRIBUTE_SYSTEM: u32 = 0x04;
+        let attrs = metadata.file_attributes();
+        (attrs & FILE_ATTRIBUTE_HIDDEN) != 0 || (attrs & FILE_ATTRIBUTE_SYSTEM) != 0
+    } else {
+        false
     }
-    
-    // Check for .ink files (Windows shortcuts)
-    if name.ends_with(".lnk") {
-        return true;
-    }
-    
+}
+
+#[cfg(not(windows))]
+pub fn is_hidden_file(_path: &Path) -> bool {
     false
 }
 
+// Whether an entry found while listing or walking a folder is kept out of
+// sight of `access`: system and hidden files, and whatever the rules for
+// its volume and the viewer's role exclude
+pub fn is_excluded_entry(path: &Path, is_dir: bool, access: &Access) -> bool {
+    !VOLUMES.is_visible(path, is_dir, access)
+}
+
 pub fn get_home_directory() -> String {
     env::var("USERPROFILE")
         .or_else(|_| env::var("HOME"))
@@ -143,6 +95,53 @@ pub fn url_decode(s: &str) -> String {
     result
 }
 
+// Maps a URL path relative to the served roots onto the filesystem, refusing
+// anything that would climb out of its volume or reach something hidden
+pub fn resolve_path(url_path: &str, access: &Access) -> Option<PathBuf> {
+    let decoded = urlencoding::decode(url_path)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| url_decode(url_path));
+
+    resolve_relative_path(&decoded, access)
+}
+
+// Same as `resolve_path` for paths that are not URL-encoded, such as the
+// ones clients send in JSON bodies
+pub fn resolve_relative_path(relative_path: &str, access: &Access) -> Option<PathBuf> {
+    VOLUMES.resolve(relative_path, access)
+}
+
+// Checks a single file or folder name typed by a user
+pub fn validate_file_name(name: &str) -> Result<(), &'static str> {
+    if name.trim().is_empty() {
+        return Err("Name cannot be empty");
+    }
+    if name.len() > 255 {
+        return Err("Name is too long");
+    }
+    if name == "." || name == ".." {
+        return Err("Name is reserved");
+    }
+    if name.chars().any(|c| c.is_control() || "/\\<>:\"|?*".contains(c)) {
+        return Err("Name contains characters that are not allowed");
+    }
+    if name.ends_with(' ') || name.ends_with('.') {
+        return Err("Name cannot end with a space or a dot");
+    }
+    Ok(())
+}
+
+pub fn query_param(query: Option<&str>, key: &str) -> Option<String> {
+    query?.split('&').find_map(|pair| {
+        let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+        if k == key {
+            Some(urlencoding::decode(&v.replace('+', " ")).map(|s| s.into_owned()).unwrap_or_default())
+        } else {
+            None
+        }
+    })
+}
+
 pub fn redirect_to_login() -> Response<BoxBody> {
     Response::builder()
         .status(StatusCode::FOUND)
@@ -151,6 +150,21 @@ pub fn redirect_to_login() -> Response<BoxBody> {
         .unwrap()
 }
 
+pub fn json_response(status: StatusCode, value: serde_json::Value) -> Response<BoxBody> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Box::new(StringBody::new(value.to_string())) as BoxBody)
+        .unwrap()
+}
+
+pub fn json_error(status: StatusCode, message: &str) -> Response<BoxBody> {
+    json_response(status, serde_json::json!({
+        "status": "error",
+        "error": message
+    }))
+}
+
 pub fn html_response(html: String) -> Response<BoxBody> {
     Response::builder()
         .status(StatusCode::OK)
@@ -199,15 +213,33 @@ use hyper::Request;
 use std::sync::Arc;
 
 pub fn is_authenticated(req: &Request<hyper::body::Incoming>, auth_manager: &Arc<AuthManager>) -> bool {
-    if let Some(cookie_header) = req.headers().get("cookie") {
-        if let Ok(cookie_str) = cookie_header.to_str() {
-            for cookie in cookie_str.split(';') {
-                let cookie = cookie.trim();
-                if let Some(token) = cookie.strip_prefix("session_id=") {
-                    return auth_manager.is_valid_token(token);
-                }
-            }
-        }
-    }
-    false
+    session_token(req).is_some_and(|token| auth_manager.is_valid_token(token))
+}
+
+pub fn session_token(req: &Request<hyper::body::Incoming>) -> Option<&str> {
+    let cookie_str = req.headers().get("cookie")?.to_str().ok()?;
+    cookie_str.split(';')
+        .map(|cookie| cookie.trim())
+        .find_map(|cookie| cookie.strip_prefix("session_id="))
+}
+
+// The role of the user making the request, and whether they asked to see
+// hidden files (which only admins may)
+pub fn current_access(req: &Request<hyper::body::Incoming>, auth_manager: &Arc<AuthManager>) -> Access {
+    let role = session_token(req)
+        .and_then(|token| auth_manager.get_session_info(token))
+        .map(|session| session.role)
+        .unwrap_or_default();
+    let wants_hidden = req.headers().get("cookie")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|cookies| cookies.split(';').any(|cookie| cookie.trim() == "show_hidden=1"));
+    let mut access = Access { role, show_hidden: false };
+    access.show_hidden = wants_hidden && access.is_admin();
+    access
+}
+
+// Username of the logged-in user making the request
+pub fn current_username(req: &Request<hyper::body::Incoming>, auth_manager: &Arc<AuthManager>) -> Option<String> {
+    let token = session_token(req)?;
+    auth_manager.get_session_info(token).map(|session| session.username)
 }
\ No newline at end of file
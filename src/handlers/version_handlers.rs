@@ -0,0 +1,120 @@
+use crate::bodies::BytesBody;
+use crate::utils::collect_body_bytes;
+use crate::content_index::reindex_in_background;
+use crate::versions::{generate_versions_html, render_diff_rows, VERSIONS};
+use crate::viewer::{is_source_file, MAX_HIGHLIGHT_BYTES};
+use crate::visibility::Access;
+use crate::volumes::{READ_ONLY_MESSAGE, VOLUMES};
+use hyper::{Request, Response, StatusCode};
+use hyper::body::Incoming;
+use std::fs;
+use std::path::Path;
+
+use super::utils::{BoxBody, html_response, json_error, json_response, not_found, query_param, resolve_path, resolve_relative_path};
+
+// Versions page for a file, optionally with a diff of one version against
+// the current contents (`?diff=<id>`)
+pub async fn versions_page(path: &str, query: Option<&str>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/versions").unwrap_or("/");
+    let file_path = match resolve_path(relative_path, access) {
+        Some(file_path) if file_path.is_file() => file_path,
+        _ => return not_found(),
+    };
+    let file_name = file_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let diff_id = query_param(query, "diff");
+    let url_path = relative_path.to_string();
+
+    let html = tokio::task::spawn_blocking(move || {
+        let versions = VERSIONS.list_versions(&file_path);
+        let can_diff = is_source_file(&file_name);
+        let diff = diff_id.filter(|_| can_diff).and_then(|id| {
+            let version_path = VERSIONS.version_path(&file_path, &id)?;
+            let old = read_text(&version_path)?;
+            let new = read_text(&file_path)?;
+            Some((id, render_diff_rows(&old, &new)))
+        });
+        generate_versions_html(
+            &file_name,
+            &url_path,
+            &versions,
+            can_diff,
+            diff.as_ref().map(|(id, rows)| (id.as_str(), rows.as_str())),
+        )
+    }).await?;
+
+    Ok(html_response(html))
+}
+
+// Lists the versions of a file as JSON, or downloads one with `?id=<id>`
+pub async fn get_versions(path: &str, query: Option<&str>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/api/versions").unwrap_or("/");
+    let file_path = match resolve_path(relative_path, access) {
+        Some(file_path) if file_path.is_file() => file_path,
+        _ => return Ok(json_error(StatusCode::NOT_FOUND, "File not found")),
+    };
+
+    let id = match query_param(query, "id") {
+        Some(id) => id,
+        None => {
+            let versions = VERSIONS.list_versions(&file_path);
+            return Ok(json_response(StatusCode::OK, serde_json::json!({
+                "status": "success",
+                "versions": versions
+            })));
+        }
+    };
+
+    let version_path = match VERSIONS.version_path(&file_path, &id) {
+        Some(version_path) => version_path,
+        None => return Ok(json_error(StatusCode::NOT_FOUND, "Version not found")),
+    };
+    let data = fs::read(&version_path)?;
+
+    let file_name = file_path.file_name().unwrap_or_default().to_string_lossy();
+    let download_name = match file_name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{} ({}).{}", stem, id, extension),
+        None => format!("{} ({})", file_name, id),
+    };
+    let mime_type = mime_guess::from_path(&file_path).first_or_octet_stream();
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", mime_type.as_ref())
+        .header("Content-Length", data.len())
+        .header("Content-Disposition", format!("attachment; filename=\"{}\"", download_name.replace('"', "")))
+        .body(Box::new(BytesBody::new(data)) as BoxBody)
+        .unwrap())
+}
+
+pub async fn restore_version(req: Request<Incoming>, access: &Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let body = collect_body_bytes(req.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body)?;
+    let file_rel = request["path"].as_str().ok_or("Missing path")?;
+    let id = request["id"].as_str().ok_or("Missing id")?.to_string();
+
+    let file_path = match resolve_relative_path(file_rel, access) {
+        Some(file_path) if file_path.is_file() => file_path,
+        _ => return Ok(json_error(StatusCode::NOT_FOUND, "File not found")),
+    };
+    if VOLUMES.is_read_only(&file_path) {
+        return Ok(json_error(StatusCode::FORBIDDEN, READ_ONLY_MESSAGE));
+    }
+
+    let target = file_path.clone();
+    match tokio::task::spawn_blocking(move || VERSIONS.restore_version(&target, &id)).await? {
+        Ok(()) => {
+            println!("🕘 Restored an earlier version of {}", file_path.display());
+            reindex_in_background(file_path);
+            Ok(json_response(StatusCode::OK, serde_json::json!({ "status": "success" })))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(json_error(StatusCode::NOT_FOUND, "Version not found")),
+        Err(e) => Ok(json_error(StatusCode::INTERNAL_SERVER_ERROR, &format!("Failed to restore version: {}", e))),
+    }
+}
+
+fn read_text(path: &Path) -> Option<String> {
+    if fs::metadata(path).ok()?.len() > MAX_HIGHLIGHT_BYTES {
+        return None;
+    }
+    String::from_utf8(fs::read(path).ok()?).ok()
+}
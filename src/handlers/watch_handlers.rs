@@ -0,0 +1,137 @@
+use crate::bodies::ChannelBody;
+use crate::file_browser::{render_entry_tiles, FileEntry};
+use crate::visibility::Access;
+use crate::volumes::VOLUMES;
+use crate::watcher::{DirEvent, DIRECTORY_WATCHER};
+use hyper::body::Bytes;
+use hyper::{Response, StatusCode, header};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{sleep, sleep_until, Instant};
+
+use super::file_handlers::is_volume_list;
+use super::utils::{BoxBody, json_error, resolve_path};
+
+// Comment lines keep proxies from closing an idle stream
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(25);
+// How long a removal waits for the other half of a rename
+const RENAME_WINDOW: Duration = Duration::from_millis(150);
+
+// Streams changes to a folder as Server-Sent Events while the client stays
+// connected. Events are `add`, `change` and `rename` (carrying the new tile's
+// HTML), `remove`, and `resync` when events were dropped and the listing
+// should be reloaded. Entries hidden from the viewer are never mentioned.
+pub async fn watch_directory(path: &str, access: Access) -> Result<Response<BoxBody>, Box<dyn std::error::Error + Send + Sync>> {
+    let relative_path = path.strip_prefix("/api/watch").unwrap_or("/");
+    if is_volume_list(relative_path) {
+        return Ok(json_error(StatusCode::NOT_FOUND, "The volume list does not change; watch a volume instead"));
+    }
+    let dir_path = match resolve_path(relative_path, &access) {
+        Some(dir_path) if dir_path.is_dir() => dir_path,
+        Some(_) => return Ok(json_error(StatusCode::NOT_FOUND, "Directory not found")),
+        None => return Ok(json_error(StatusCode::FORBIDDEN, "Access denied")),
+    };
+    let url_path = format!("/browse{}", relative_path);
+
+    let (sender, receiver) = mpsc::channel::<std::io::Result<Bytes>>(16);
+    tokio::spawn(async move {
+        let mut subscription = DIRECTORY_WATCHER.subscribe(&dir_path);
+        let mut pending_removal: Option<(String, Option<usize>, Instant)> = None;
+
+        if sender.send(Ok(Bytes::from(": watching\n\n"))).await.is_err() {
+            return;
+        }
+        loop {
+            let removal_deadline = pending_removal.as_ref().map(|(_, _, deadline)| *deadline);
+            let message = tokio::select! {
+                event = subscription.recv() => match event {
+                    Ok(event) if !is_visible_event(&event, &dir_path, &access) => None,
+                    Ok(DirEvent::Removed { name, tracker: Some(tracker) }) => {
+                        let flushed = pending_removal.take().map(|(name, _, _)| removed_event(&name));
+                        pending_removal = Some((name, Some(tracker), Instant::now() + RENAME_WINDOW));
+                        flushed
+                    }
+                    Ok(DirEvent::Added { name, tracker: Some(tracker) })
+                        if pending_removal.as_ref().is_some_and(|(_, pending, _)| *pending == Some(tracker)) =>
+                    {
+                        let (from, _, _) = pending_removal.take().unwrap();
+                        Some(entry_event("rename", Some(&from), &name, &dir_path, &url_path, &access))
+                    }
+                    Ok(event) => {
+                        let mut message = pending_removal.take().map(|(name, _, _)| removed_event(&name)).unwrap_or_default();
+                        message.push_str(&match event {
+                            DirEvent::Added { name, .. } => entry_event("add", None, &name, &dir_path, &url_path, &access),
+                            DirEvent::Changed { name } => entry_event("change", None, &name, &dir_path, &url_path, &access),
+                            DirEvent::Removed { name, .. } => removed_event(&name),
+                        });
+                        Some(message)
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        pending_removal = None;
+                        Some("event: resync\ndata: {}\n\n".to_string())
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = sleep_until(removal_deadline.unwrap_or_else(Instant::now)), if removal_deadline.is_some() => {
+                    pending_removal.take().map(|(name, _, _)| removed_event(&name))
+                }
+                _ = sleep(KEEPALIVE_INTERVAL) => Some(": keepalive\n\n".to_string()),
+            };
+
+            // A failed send means the viewer navigated away
+            if let Some(message) = message
+                && sender.send(Ok(Bytes::from(message))).await.is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Box::new(ChannelBody::new(receiver)) as BoxBody)?)
+}
+
+fn is_visible_event(event: &DirEvent, dir_path: &Path, access: &Access) -> bool {
+    match event {
+        DirEvent::Added { name, .. } | DirEvent::Changed { name } => {
+            let path = dir_path.join(name);
+            VOLUMES.is_visible(&path, path.is_dir(), access)
+        }
+        // The entry is gone, so it may have been either a file or a folder
+        DirEvent::Removed { name, .. } => {
+            let path = dir_path.join(name);
+            VOLUMES.is_visible(&path, false, access) || VOLUMES.is_visible(&path, true, access)
+        }
+    }
+}
+
+fn removed_event(name: &str) -> String {
+    sse_message("remove", &serde_json::json!({ "name": name }))
+}
+
+// An event carrying the entry's freshly rendered tile. The entry may already
+// be gone again by the time it is read, in which case it is reported removed.
+fn entry_event(kind: &str, from: Option<&str>, name: &str, dir_path: &Path, url_path: &str, access: &Access) -> String {
+    let entry = match FileEntry::from_path(&dir_path.join(name)) {
+        Ok(entry) => entry,
+        Err(_) => {
+            let mut message = from.map(removed_event).unwrap_or_default();
+            message.push_str(&removed_event(name));
+            return message;
+        }
+    };
+    sse_message(kind, &serde_json::json!({
+        "name": name,
+        "from": from,
+        "folder": entry.is_dir,
+        "html": render_entry_tiles(&[entry], url_path, access)
+    }))
+}
+
+fn sse_message(event: &str, data: &serde_json::Value) -> String {
+    format!("event: {}\ndata: {}\n\n", event, data)
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use uuid::Uuid;
+
+// Finished jobs are kept around this long so clients can read the outcome
+const FINISHED_JOB_TTL_SECS: u64 = 3600;
+const MAX_REPORTED_ERRORS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatus {
+    pub job_id: String,
+    pub kind: String,
+    pub state: JobState,
+    pub bytes_total: u64,
+    pub bytes_done: u64,
+    pub files_total: u64,
+    pub files_done: u64,
+    pub current_item: String,
+    pub errors: Vec<String>,
+    pub message: Option<String>,
+    pub created_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+struct Job {
+    status: Mutex<JobStatus>,
+    cancelled: AtomicBool,
+    // The user who started the job; only they can follow or cancel it
+    owner: String,
+}
+
+// Handed to the job body so it can report progress and notice cancellation
+#[derive(Clone)]
+pub struct JobHandle {
+    job: Arc<Job>,
+}
+
+impl JobHandle {
+    fn update(&self, f: impl FnOnce(&mut JobStatus)) {
+        if let Ok(mut status) = self.job.status.lock() {
+            f(&mut status);
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.job.status.lock().map(|s| s.job_id.clone()).unwrap_or_default()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.job.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_totals(&self, files_total: u64, bytes_total: u64) {
+        self.update(|s| {
+            s.files_total = files_total;
+            s.bytes_total = bytes_total;
+        });
+    }
+
+    pub fn set_current(&self, item: &str) {
+        self.update(|s| s.current_item = item.to_string());
+    }
+
+    pub fn add_bytes(&self, bytes: u64) {
+        self.update(|s| s.bytes_done += bytes);
+    }
+
+    pub fn file_done(&self) {
+        self.update(|s| s.files_done += 1);
+    }
+
+    pub fn add_error(&self, error: String) {
+        self.update(|s| {
+            if s.errors.len() < MAX_REPORTED_ERRORS {
+                s.errors.push(error);
+            }
+        });
+    }
+}
+
+// Counts bytes read towards the job's progress and stops reading once the job
+// is cancelled. The error is deliberately not `Interrupted`, which `io::copy`
+// would retry forever.
+pub struct ProgressReader<'a, R> {
+    inner: R,
+    job: &'a JobHandle,
+}
+
+impl<'a, R> ProgressReader<'a, R> {
+    pub fn new(inner: R, job: &'a JobHandle) -> Self {
+        ProgressReader { inner, job }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.job.is_cancelled() {
+            return Err(io::Error::other("Cancelled"));
+        }
+        let read = self.inner.read(buf)?;
+        self.job.add_bytes(read as u64);
+        Ok(read)
+    }
+}
+
+pub struct JobManager {
+    jobs: Mutex<HashMap<String, Arc<Job>>>,
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        JobManager {
+            jobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Runs `work` on the blocking pool. Its Ok value becomes the job's final
+    // message; an Err marks the job failed.
+    pub fn start<F>(&self, kind: &str, owner: &str, work: F) -> String
+    where
+        F: FnOnce(&JobHandle) -> Result<String, String> + Send + 'static,
+    {
+        self.cleanup_finished_jobs();
+
+        let job_id = Uuid::new_v4().to_string();
+        let job = Arc::new(Job {
+            status: Mutex::new(JobStatus {
+                job_id: job_id.clone(),
+                kind: kind.to_string(),
+                state: JobState::Running,
+                bytes_total: 0,
+                bytes_done: 0,
+                files_total: 0,
+                files_done: 0,
+                current_item: String::new(),
+                errors: Vec::new(),
+                message: None,
+                created_at: now_secs(),
+                finished_at: None,
+            }),
+            cancelled: AtomicBool::new(false),
+            owner: owner.to_string(),
+        });
+
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.insert(job_id.clone(), job.clone());
+        }
+
+        let handle = JobHandle { job };
+        let log_label = format!("{} {}", kind, job_id);
+        tokio::task::spawn_blocking(move || {
+            let result = work(&handle);
+            let cancelled = handle.is_cancelled();
+            handle.update(|s| {
+                s.finished_at = Some(now_secs());
+                s.current_item.clear();
+                match result {
+                    _ if cancelled => {
+                        s.state = JobState::Cancelled;
+                        s.message = Some("Cancelled".to_string());
+                    }
+                    Ok(message) => {
+                        s.state = JobState::Completed;
+                        s.message = Some(message);
+                    }
+                    Err(e) => {
+                        s.state = JobState::Failed;
+                        s.message = Some(e);
+                    }
+                }
+            });
+            println!("🧰 Job {} finished", log_label);
+        });
+
+        job_id
+    }
+
+    // Jobs started by another user are not found
+    pub fn get_status(&self, job_id: &str, owner: &str) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().ok()?;
+        let job = jobs.get(job_id).filter(|job| job.owner == owner)?;
+        job.status.lock().ok().map(|s| s.clone())
+    }
+
+    pub fn cancel(&self, job_id: &str, owner: &str) -> bool {
+        if let Ok(jobs) = self.jobs.lock()
+            && let Some(job) = jobs.get(job_id).filter(|job| job.owner == owner) {
+            job.cancelled.store(true, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+
+    fn cleanup_finished_jobs(&self) {
+        let now = now_secs();
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.retain(|_, job| {
+                job.status.lock()
+                    .map(|s| s.finished_at.is_none_or(|finished| now - finished < FINISHED_JOB_TTL_SECS))
+                    .unwrap_or(false)
+            });
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+// Global job manager instance
+lazy_static::lazy_static! {
+    pub static ref JOB_MANAGER: JobManager = JobManager::new();
+}
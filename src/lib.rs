@@ -6,3 +6,20 @@ pub mod upload;
 pub mod utils;
 pub mod performance;
 pub mod resumable_upload;
+pub mod thumbnails;
+pub mod viewer;
+pub mod editor;
+pub mod archive;
+pub mod jobs;
+pub mod file_ops;
+pub mod trash;
+pub mod versions;
+pub mod search;
+pub mod content_index;
+pub mod disk_usage;
+pub mod duplicates;
+pub mod watcher;
+pub mod volumes;
+pub mod visibility;
+pub mod config;
+pub mod multipart;
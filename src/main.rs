@@ -2,6 +2,12 @@ use hyper::server::conn::http1;
 use hyper::service::service_fn;
 use rust_web_server::handlers::handle_request;
 use rust_web_server::auth::AuthManager;
+use clap::Parser;
+use rust_web_server::config::{Args, Config, CONFIG};
+use rust_web_server::trash;
+use rust_web_server::thumbnails;
+use rust_web_server::content_index;
+use rust_web_server::volumes::VOLUMES;
 use std::net::{SocketAddr, UdpSocket, IpAddr, Ipv4Addr};
 use std::sync::Arc;
 use tokio::net::TcpListener;
@@ -24,17 +30,60 @@ fn get_local_ip() -> IpAddr {
     }
 }
 
+fn print_volumes() {
+    println!("💾 Volumes:");
+    for volume in VOLUMES.list() {
+        let access = if volume.read_only { " (read-only)" } else { "" };
+        if VOLUMES.is_named() {
+            println!("   📁 {}: {}{}", volume.name, volume.path.display(), access);
+        } else {
+            println!("   📁 {}{}", volume.path.display(), access);
+        }
+    }
+}
+
+// What --check-config reports once the configuration has loaded
+fn print_config_summary() {
+    match &CONFIG.file {
+        Some(file) => println!("✅ Configuration is valid: {}", file.display()),
+        None => println!("✅ Configuration is valid (no config file, using defaults)"),
+    }
+    println!("   📡 Listen:   {}:{}", CONFIG.bind, CONFIG.port);
+    println!("   📤 Uploads:  {}", CONFIG.upload_dir.display());
+    println!("   👤 Admin:    {}", CONFIG.auth.username);
+    println!("   ⏱️  Sessions: {} minutes", CONFIG.auth.session_timeout_minutes);
+    println!("🗄️ Server data:");
+    for (name, dir) in CONFIG.data_dirs() {
+        println!("   {}: {}", name, dir.display());
+    }
+    print_volumes();
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Load the configuration and volumes first so a bad file stops us before
+    // the banner. A configuration that cannot be used stops the server.
+    match Config::from_args(Args::parse()) {
+        Ok(config) => config.init(),
+        Err(e) => {
+            eprintln!("❌ Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    }
+    lazy_static::initialize(&VOLUMES);
+    if CONFIG.check_only {
+        print_config_summary();
+        return Ok(());
+    }
     let auth_manager = Arc::new(AuthManager::new());
-    let port = 8000;
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let port = CONFIG.port;
+    let addr = SocketAddr::new(CONFIG.bind, port);
     let local_ip = get_local_ip();
     
     // Beautiful startup banner with emojis
     println!("\n🦀 Rust Web-based File Manager");
     println!("{}", "═".repeat(55));
-    println!("🚀 Server Status: {} STARTING UP", "✅");
+    println!("🚀 Server Status: ✅ STARTING UP");
     println!();
     println!("📡 Network Information:");
     println!("   🏠 Local:      http://127.0.0.1:{}", port);
@@ -42,17 +91,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("   📱 LAN Access: http://{}:{}", local_ip, port);
     println!();
     println!("🔐 Authentication:");
-    println!("   👤 Username: admin");
-    println!("   🔑 Password: admin123");
+    println!("   👤 Username: {}", CONFIG.auth.username);
+    if CONFIG.has_default_credentials() {
+        println!("   🔑 Password: {} (default, set auth.password to change it)", CONFIG.auth.password);
+    } else {
+        println!("   🔑 Password: (from configuration)");
+    }
     println!();
     println!("🎯 Quick Access:");
     println!("   📂 Browse Files:  /browse");
     println!("   📤 Upload Files:  /upload");
     println!("   🏠 Home Page:     /");
     println!();
+    print_volumes();
+    println!();
     println!("📋 Features:");
     println!("   ✅ Secure file browsing");
     println!("   ✅ Multi-file uploads");
+    println!("   ✅ Recycle bin with restore");
+    println!("   ✅ Full-text search");
     println!("   ✅ System file protection");
     println!("   ✅ Cross-platform support");
     println!("   ✅ Mobile-friendly interface");
@@ -61,11 +118,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("   ⏹️  Stop Server: Ctrl+C");
     println!("   📊 View Logs:   Check terminal below");
     println!();
-    println!("{}🎉 READY! Server is now accepting connections 🎉{}", "🎊 ", " 🎊");
+    println!("🎊 🎉 READY! Server is now accepting connections 🎉 🎊");
     println!("{}", "═".repeat(55));
     println!();
 
     let listener = TcpListener::bind(addr).await?;
+    trash::spawn_purge_task();
+    thumbnails::spawn_prune_task();
+    content_index::spawn_index_task();
 
     loop {
         let (stream, client_addr) = listener.accept().await?;
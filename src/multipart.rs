@@ -0,0 +1,602 @@
+// Streaming parser for multipart/form-data request bodies (RFC 7578).
+// Chunks are fed in as they arrive and handed to a `PartHandler` part by
+// part, so a file never has to fit in memory: only the tail that could be
+// the start of the next boundary is held back between chunks.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MultipartLimits {
+    // Largest file part in bytes; 0 for no limit
+    pub max_file_size: u64,
+    // Largest value of a form field that is not a file
+    pub max_field_size: u64,
+    // Largest header block of a single part
+    pub max_header_size: usize,
+    pub max_parts: usize,
+}
+
+impl Default for MultipartLimits {
+    fn default() -> Self {
+        MultipartLimits {
+            max_file_size: 0,
+            max_field_size: 64 * 1024,
+            max_header_size: 8 * 1024,
+            max_parts: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartInfo {
+    pub name: String,
+    // Set for file parts, exactly as the client sent it. Browsers send an
+    // empty name for a file input left blank.
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+}
+
+impl PartInfo {
+    pub fn is_file(&self) -> bool {
+        self.filename.is_some()
+    }
+}
+
+// Receives the parts of a body in order: `begin_part`, any number of
+// `part_data` calls, then `end_part`
+pub trait PartHandler {
+    fn begin_part(&mut self, part: &PartInfo) -> io::Result<()>;
+    fn part_data(&mut self, data: &[u8]) -> io::Result<()>;
+    fn end_part(&mut self) -> io::Result<()>;
+}
+
+#[derive(Debug)]
+pub enum MultipartError {
+    Malformed(&'static str),
+    FileTooLarge { filename: String, limit: u64 },
+    FieldTooLarge { name: String, limit: u64 },
+    TooManyParts(usize),
+    Io(io::Error),
+}
+
+impl MultipartError {
+    // Whether the client should be told 413 Payload Too Large
+    pub fn is_too_large(&self) -> bool {
+        matches!(self, MultipartError::FileTooLarge { .. } | MultipartError::FieldTooLarge { .. } | MultipartError::TooManyParts(_))
+    }
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultipartError::Malformed(reason) => write!(f, "Malformed multipart body: {}", reason),
+            MultipartError::FileTooLarge { filename, limit } => write!(f, "'{}' is larger than the {} byte limit", filename, limit),
+            MultipartError::FieldTooLarge { name, limit } => write!(f, "Field '{}' is larger than the {} byte limit", name, limit),
+            MultipartError::TooManyParts(limit) => write!(f, "More than {} parts in one request", limit),
+            MultipartError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for MultipartError {}
+
+impl From<io::Error> for MultipartError {
+    fn from(e: io::Error) -> Self {
+        MultipartError::Io(e)
+    }
+}
+
+// The boundary named in a Content-Type header, if it is multipart/form-data
+pub fn boundary(content_type: &str) -> Option<String> {
+    let (mime, params) = parse_header_value(content_type);
+    if !mime.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    let boundary = params.into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("boundary"))?
+        .1;
+    // RFC 2046 allows 1 to 70 characters
+    (1..=70).contains(&boundary.len()).then_some(boundary)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Preamble,
+    AfterBoundary,
+    Headers,
+    Body,
+    Done,
+}
+
+pub struct MultipartParser {
+    // CRLF, "--" and the boundary: what separates one part from the next
+    delimiter: Vec<u8>,
+    limits: MultipartLimits,
+    state: State,
+    buffer: Vec<u8>,
+    part: Option<PartInfo>,
+    part_size: u64,
+    parts: usize,
+}
+
+impl MultipartParser {
+    pub fn new(boundary: &str, limits: MultipartLimits) -> Self {
+        let mut delimiter = b"\r\n--".to_vec();
+        delimiter.extend_from_slice(boundary.as_bytes());
+        MultipartParser {
+            delimiter,
+            limits,
+            state: State::Preamble,
+            // The first boundary may start the body with no line break before
+            // it; a leading CRLF lets it be matched like every other one
+            buffer: b"\r\n".to_vec(),
+            part: None,
+            part_size: 0,
+            parts: 0,
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8], handler: &mut impl PartHandler) -> Result<(), MultipartError> {
+        if self.state == State::Done {
+            // The epilogue after the closing boundary is ignored
+            return Ok(());
+        }
+        self.buffer.extend_from_slice(chunk);
+        self.process(handler)
+    }
+
+    // Call once the body has ended. Fails if it stopped before the closing
+    // boundary, in which case the last part is incomplete.
+    pub fn finish(&self) -> Result<(), MultipartError> {
+        match self.state {
+            State::Done => Ok(()),
+            State::Preamble => Err(MultipartError::Malformed("no boundary found")),
+            _ => Err(MultipartError::Malformed("body ended before the closing boundary")),
+        }
+    }
+
+    fn process(&mut self, handler: &mut impl PartHandler) -> Result<(), MultipartError> {
+        loop {
+            match self.state {
+                State::Preamble => match find(&self.buffer, &self.delimiter) {
+                    Some(i) => {
+                        self.buffer.drain(..i + self.delimiter.len());
+                        self.state = State::AfterBoundary;
+                    }
+                    None => {
+                        let keep = self.delimiter.len() - 1;
+                        if self.buffer.len() > keep {
+                            self.buffer.drain(..self.buffer.len() - keep);
+                        }
+                        return Ok(());
+                    }
+                },
+                State::AfterBoundary => {
+                    if self.buffer.len() < 2 {
+                        return Ok(());
+                    }
+                    if self.buffer.starts_with(b"--") {
+                        self.buffer.clear();
+                        self.state = State::Done;
+                        return Ok(());
+                    }
+                    // Spaces and tabs may follow a boundary before its line ends
+                    let padding = self.buffer.iter().take_while(|&&b| b == b' ' || b == b'\t').count();
+                    match self.buffer.get(padding..padding + 2) {
+                        Some(b"\r\n") => {
+                            self.buffer.drain(..padding + 2);
+                            if self.parts == self.limits.max_parts {
+                                return Err(MultipartError::TooManyParts(self.limits.max_parts));
+                            }
+                            self.parts += 1;
+                            self.state = State::Headers;
+                        }
+                        Some(_) => return Err(MultipartError::Malformed("unexpected data after a boundary")),
+                        None if self.buffer.len() > 1024 => return Err(MultipartError::Malformed("unexpected data after a boundary")),
+                        None => {
+                            if self.buffer.get(padding).is_some_and(|&b| b != b'\r') {
+                                return Err(MultipartError::Malformed("unexpected data after a boundary"));
+                            }
+                            return Ok(());
+                        }
+                    }
+                }
+                State::Headers => {
+                    let end = if self.buffer.starts_with(b"\r\n") {
+                        Some((0, 2))
+                    } else {
+                        find(&self.buffer, b"\r\n\r\n").map(|i| (i, i + 4))
+                    };
+                    match end {
+                        Some((length, _)) if length > self.limits.max_header_size => {
+                            return Err(MultipartError::Malformed("part headers are too long"));
+                        }
+                        Some((length, consumed)) => {
+                            let part = parse_part_headers(&self.buffer[..length])?;
+                            self.buffer.drain(..consumed);
+                            handler.begin_part(&part)?;
+                            self.part = Some(part);
+                            self.part_size = 0;
+                            self.state = State::Body;
+                        }
+                        None if self.buffer.len() > self.limits.max_header_size + 4 => {
+                            return Err(MultipartError::Malformed("part headers are too long"));
+                        }
+                        None => return Ok(()),
+                    }
+                }
+                State::Body => match find(&self.buffer, &self.delimiter) {
+                    Some(i) => {
+                        self.count_part_bytes(i)?;
+                        handler.part_data(&self.buffer[..i])?;
+                        handler.end_part()?;
+                        self.buffer.drain(..i + self.delimiter.len());
+                        self.part = None;
+                        self.state = State::AfterBoundary;
+                    }
+                    None => {
+                        // No delimiter starts before `safe`, but one may start in the
+                        // tail and be completed by the next chunk
+                        let safe = self.buffer.len().saturating_sub(self.delimiter.len() - 1);
+                        if safe > 0 {
+                            self.count_part_bytes(safe)?;
+                            handler.part_data(&self.buffer[..safe])?;
+                            self.buffer.drain(..safe);
+                        }
+                        return Ok(());
+                    }
+                },
+                State::Done => return Ok(()),
+            }
+        }
+    }
+
+    fn count_part_bytes(&mut self, length: usize) -> Result<(), MultipartError> {
+        self.part_size += length as u64;
+        let part = match &self.part {
+            Some(part) => part,
+            None => return Ok(()),
+        };
+        match &part.filename {
+            Some(filename) if self.limits.max_file_size > 0 && self.part_size > self.limits.max_file_size => {
+                Err(MultipartError::FileTooLarge { filename: filename.clone(), limit: self.limits.max_file_size })
+            }
+            None if self.part_size > self.limits.max_field_size => {
+                Err(MultipartError::FieldTooLarge { name: part.name.clone(), limit: self.limits.max_field_size })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn parse_part_headers(block: &[u8]) -> Result<PartInfo, MultipartError> {
+    let block = String::from_utf8_lossy(block);
+    let mut disposition = None;
+    let mut content_type = None;
+
+    for line in block.split("\r\n") {
+        let (name, value) = line.split_once(':')
+            .ok_or(MultipartError::Malformed("part header without a colon"))?;
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("content-disposition") {
+            disposition = Some(value.trim().to_string());
+        } else if name.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.trim().to_string());
+        }
+    }
+
+    let disposition = disposition.ok_or(MultipartError::Malformed("part has no Content-Disposition header"))?;
+    let (kind, params) = parse_header_value(&disposition);
+    if !kind.eq_ignore_ascii_case("form-data") {
+        return Err(MultipartError::Malformed("part is not form-data"));
+    }
+
+    let param = |key: &str| params.iter().find(|(name, _)| name.eq_ignore_ascii_case(key)).map(|(_, value)| value.clone());
+    let name = param("name").ok_or(MultipartError::Malformed("part has no name"))?;
+    // RFC 5987 `filename*=UTF-8''...` wins over the plain filename
+    let filename = param("filename*")
+        .and_then(|value| decode_extended_value(&value))
+        .or_else(|| param("filename"));
+
+    Ok(PartInfo {
+        name: unescape_form_name(&name),
+        filename: filename.map(|filename| unescape_form_name(&filename)),
+        content_type,
+    })
+}
+
+// Splits `type; key=value; key="quoted value"` into the leading value and
+// its parameters. Inside quotes a backslash escapes a quote or a backslash,
+// as curl sends them; any other backslash is kept, since browsers send
+// Windows paths unescaped.
+fn parse_header_value(value: &str) -> (String, Vec<(String, String)>) {
+    let mut chars = value.chars().peekable();
+    let mut first = String::new();
+    while let Some(&c) = chars.peek() {
+        if c == ';' {
+            break;
+        }
+        first.push(c);
+        chars.next();
+    }
+
+    let mut params = Vec::new();
+    while chars.next() == Some(';') {
+        let mut key = String::new();
+        while let Some(&c) = chars.peek() {
+            if c == '=' || c == ';' {
+                break;
+            }
+            key.push(c);
+            chars.next();
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'=') {
+            chars.next();
+            while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' if matches!(chars.peek(), Some('"') | Some('\\')) => value.push(chars.next().unwrap()),
+                        _ => value.push(c),
+                    }
+                }
+                // Skip anything between the closing quote and the next parameter
+                while chars.peek().is_some_and(|&c| c != ';') {
+                    chars.next();
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if c == ';' {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                value = value.trim().to_string();
+            }
+        }
+        let key = key.trim();
+        if !key.is_empty() {
+            params.push((key.to_string(), value));
+        }
+    }
+
+    (first.trim().to_string(), params)
+}
+
+// `UTF-8''na%C3%AFve.txt` as sent in `filename*`
+fn decode_extended_value(value: &str) -> Option<String> {
+    let (charset, rest) = value.split_once('\'')?;
+    let (_language, encoded) = rest.split_once('\'')?;
+    if !charset.eq_ignore_ascii_case("utf-8") {
+        return None;
+    }
+    urlencoding::decode(encoded).ok().map(|decoded| decoded.into_owned())
+}
+
+// Browsers percent-encode quotes and line breaks in names and filenames
+// (and nothing else), so only those are decoded
+fn unescape_form_name(name: &str) -> String {
+    name.replace("%22", "\"").replace("%0D", "\r").replace("%0A", "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDARY: &str = "----WebKitFormBoundaryX3bY";
+
+    #[derive(Default)]
+    struct Collector {
+        parts: Vec<(PartInfo, Vec<u8>)>,
+        open: bool,
+    }
+
+    impl PartHandler for Collector {
+        fn begin_part(&mut self, part: &PartInfo) -> io::Result<()> {
+            assert!(!self.open, "part started before the previous one ended");
+            self.open = true;
+            self.parts.push((part.clone(), Vec::new()));
+            Ok(())
+        }
+
+        fn part_data(&mut self, data: &[u8]) -> io::Result<()> {
+            assert!(self.open, "data outside a part");
+            self.parts.last_mut().unwrap().1.extend_from_slice(data);
+            Ok(())
+        }
+
+        fn end_part(&mut self) -> io::Result<()> {
+            assert!(self.open, "part ended twice");
+            self.open = false;
+            Ok(())
+        }
+    }
+
+    fn parse_in_chunks(body: &[u8], limits: MultipartLimits, chunk_size: usize) -> Result<Vec<(PartInfo, Vec<u8>)>, MultipartError> {
+        let mut parser = MultipartParser::new(BOUNDARY, limits);
+        let mut collector = Collector::default();
+        for chunk in body.chunks(chunk_size) {
+            parser.feed(chunk, &mut collector)?;
+        }
+        parser.finish()?;
+        assert!(!collector.open);
+        Ok(collector.parts)
+    }
+
+    // Parses `body` fed whole and split at every chunk size that is likely
+    // to cut a boundary, and checks every split gives the same parts
+    fn parse(body: &[u8], limits: MultipartLimits) -> Result<Vec<(PartInfo, Vec<u8>)>, MultipartError> {
+        let whole = parse_in_chunks(body, limits, body.len().max(1));
+        for chunk_size in [1, 2, 3, 5, 7, 16, 41, 64, 1000] {
+            let split = parse_in_chunks(body, limits, chunk_size);
+            match (&whole, &split) {
+                (Ok(expected), Ok(actual)) => assert_eq!(expected, actual, "chunk size {}", chunk_size),
+                (Err(expected), Err(actual)) => assert_eq!(expected.to_string(), actual.to_string(), "chunk size {}", chunk_size),
+                _ => panic!("chunk size {} gave {:?}, whole body gave {:?}", chunk_size, split.as_ref().err(), whole.as_ref().err()),
+            }
+        }
+        whole
+    }
+
+    fn file_part(name: &str, filename: &str, content: &[u8]) -> Vec<u8> {
+        let mut part = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+            BOUNDARY, name, filename
+        ).into_bytes();
+        part.extend_from_slice(content);
+        part.extend_from_slice(b"\r\n");
+        part
+    }
+
+    fn field_part(name: &str, value: &str) -> Vec<u8> {
+        format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", BOUNDARY, name, value).into_bytes()
+    }
+
+    fn body(parts: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = parts.concat();
+        body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+        body
+    }
+
+    #[test]
+    fn parses_files_and_fields() {
+        let body = body(&[
+            field_part("dir", "Projects/site"),
+            file_part("file", "a.txt", b"first file"),
+            file_part("file", "b.bin", &[0, 1, 2, 255]),
+        ]);
+        let parts = parse(&body, MultipartLimits::default()).unwrap();
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].0, PartInfo { name: "dir".into(), filename: None, content_type: None });
+        assert_eq!(parts[0].1, b"Projects/site");
+        assert_eq!(parts[1].0.filename.as_deref(), Some("a.txt"));
+        assert_eq!(parts[1].0.content_type.as_deref(), Some("application/octet-stream"));
+        assert_eq!(parts[1].1, b"first file");
+        assert_eq!(parts[2].0.filename.as_deref(), Some("b.bin"));
+        assert_eq!(parts[2].1, [0, 1, 2, 255]);
+    }
+
+    #[test]
+    fn keeps_content_that_looks_like_a_boundary() {
+        let tricky = [
+            b"line\r\n--not the boundary\r\n".to_vec(),
+            format!("\r\n--{}", &BOUNDARY[..BOUNDARY.len() - 1]).into_bytes(),
+            format!("--{}", BOUNDARY).into_bytes(),
+            format!("x\n--{}\r\n", BOUNDARY).into_bytes(),
+            b"\r\n\r\n--\r\n-".to_vec(),
+            b"ends with a carriage return\r".to_vec(),
+        ];
+        for content in tricky {
+            let body = body(&[file_part("file", "tricky.bin", &content), field_part("after", "still here")]);
+            let parts = parse(&body, MultipartLimits::default()).unwrap();
+            assert_eq!(parts.len(), 2, "content {:?}", String::from_utf8_lossy(&content));
+            assert_eq!(parts[0].1, content);
+            assert_eq!(parts[1].1, b"still here");
+        }
+    }
+
+    #[test]
+    fn keeps_binary_content_intact() {
+        let content: Vec<u8> = (0..20_000u32).map(|i| (i * 7 % 256) as u8).collect();
+        let body = body(&[file_part("file", "noise.bin", &content)]);
+        let parts = parse(&body, MultipartLimits::default()).unwrap();
+        assert_eq!(parts[0].1, content);
+    }
+
+    #[test]
+    fn handles_empty_parts() {
+        let body = body(&[file_part("file", "empty.txt", b""), file_part("file", "", b""), field_part("note", "")]);
+        let parts = parse(&body, MultipartLimits::default()).unwrap();
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|(_, data)| data.is_empty()));
+        assert_eq!(parts[1].0.filename.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn ignores_preamble_epilogue_and_padding() {
+        let mut body = b"This is the preamble.\r\n".to_vec();
+        body.extend_from_slice(format!("--{} \t\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nvalue\r\n", BOUNDARY).as_bytes());
+        body.extend_from_slice(format!("--{}--\r\nThis is the epilogue.\r\n--{}\r\n", BOUNDARY, BOUNDARY).as_bytes());
+        let parts = parse(&body, MultipartLimits::default()).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].1, b"value");
+    }
+
+    #[test]
+    fn parses_awkward_disposition_parameters() {
+        let headers = [
+            (r#"form-data; name="file"; filename="semi;colon \"quoted\".txt""#, "semi;colon \"quoted\".txt"),
+            (r#"form-data; name="file"; filename="%22browser%22.txt""#, "\"browser\".txt"),
+            (r#"form-data; name="file"; filename="C:\Users\me\report.pdf""#, r"C:\Users\me\report.pdf"),
+            (r#"FORM-DATA; NAME=file; FILENAME=plain.txt"#, "plain.txt"),
+            (r#"form-data; name="file"; filename="fallback.txt"; filename*=UTF-8''na%C3%AFve%20file.txt"#, "naïve file.txt"),
+        ];
+        for (disposition, expected) in headers {
+            let mut body = format!("--{}\r\ncontent-disposition: {}\r\n\r\ndata\r\n", BOUNDARY, disposition).into_bytes();
+            body.extend_from_slice(format!("--{}--", BOUNDARY).as_bytes());
+            let parts = parse(&body, MultipartLimits::default()).unwrap();
+            assert_eq!(parts[0].0.name, "file");
+            assert_eq!(parts[0].0.filename.as_deref(), Some(expected), "{}", disposition);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_bodies() {
+        let truncated = file_part("file", "cut.txt", b"no closing boundary");
+        assert!(matches!(parse(&truncated, MultipartLimits::default()), Err(MultipartError::Malformed(_))));
+
+        let no_boundary = b"just some text".to_vec();
+        assert!(matches!(parse(&no_boundary, MultipartLimits::default()), Err(MultipartError::Malformed(_))));
+
+        let no_disposition = body(&[format!("--{}\r\nContent-Type: text/plain\r\n\r\nvalue\r\n", BOUNDARY).into_bytes()]);
+        assert!(matches!(parse(&no_disposition, MultipartLimits::default()), Err(MultipartError::Malformed(_))));
+
+        let junk_after_boundary = body(&[format!("--{}junk\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nv\r\n", BOUNDARY).into_bytes()]);
+        assert!(matches!(parse(&junk_after_boundary, MultipartLimits::default()), Err(MultipartError::Malformed(_))));
+    }
+
+    #[test]
+    fn enforces_limits() {
+        let limits = MultipartLimits { max_file_size: 10, max_field_size: 5, max_header_size: 200, max_parts: 2 };
+
+        let at_limit = body(&[file_part("file", "ten.txt", b"0123456789"), field_part("f", "12345")]);
+        assert!(parse(&at_limit, limits).is_ok());
+
+        let big_file = body(&[file_part("file", "big.txt", b"0123456789A")]);
+        let err = parse(&big_file, limits).unwrap_err();
+        assert!(matches!(err, MultipartError::FileTooLarge { ref filename, limit: 10 } if filename == "big.txt"));
+        assert!(err.is_too_large());
+
+        let big_field = body(&[field_part("f", "123456")]);
+        assert!(matches!(parse(&big_field, limits), Err(MultipartError::FieldTooLarge { limit: 5, .. })));
+
+        let too_many = body(&[field_part("a", "1"), field_part("b", "2"), field_part("c", "3")]);
+        assert!(matches!(parse(&too_many, limits), Err(MultipartError::TooManyParts(2))));
+
+        let long_headers = body(&[file_part("file", &"x".repeat(300), b"data")]);
+        assert!(matches!(parse(&long_headers, limits), Err(MultipartError::Malformed(_))));
+    }
+
+    #[test]
+    fn reads_boundary_from_content_type() {
+        assert_eq!(boundary("multipart/form-data; boundary=abc123").as_deref(), Some("abc123"));
+        assert_eq!(boundary("Multipart/Form-Data; charset=utf-8; BOUNDARY=\"a b;c\"").as_deref(), Some("a b;c"));
+        assert_eq!(boundary("multipart/form-data"), None);
+        assert_eq!(boundary("multipart/form-data; boundary="), None);
+        assert_eq!(boundary("application/json; boundary=abc"), None);
+        assert_eq!(boundary(&format!("multipart/form-data; boundary={}", "x".repeat(71))), None);
+    }
+}
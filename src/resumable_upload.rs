@@ -1,18 +1,31 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::fs::{File, OpenOptions};
-use std::io::{Write, Seek, SeekFrom};
+use std::io::{self, Write, Seek, SeekFrom};
+use std::path::Path;
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::config::CONFIG;
+use crate::file_ops::replace_file;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UploadSession {
     pub session_id: String,
     pub file_path: String,
+    pub file_name: String,
+    // Folder the file is saved in, relative to the served root; the
+    // configured upload folder when the client named none
+    pub target_dir: Option<String>,
     pub total_size: u64,
     pub uploaded_size: u64,
     pub chunk_size: u64,
-    pub file_hash: String,
+    // SHA-256 of the whole file, in lowercase hex, if the client gave one
+    // when starting; it may also be given when completing
+    pub file_hash: Option<String>,
+    // The user who started the upload; no one else can add to, finish or
+    // see it
+    pub username: String,
     pub created_at: u64,
 }
 
@@ -30,17 +43,20 @@ impl ResumableUploadManager {
         }
     }
     
-    pub fn create_session(&self, _filename: &str, total_size: u64) -> String {
+    pub fn create_session(&self, username: &str, filename: &str, total_size: u64, target_dir: Option<String>, file_hash: Option<&str>) -> String {
         let session_id = Uuid::new_v4().to_string();
         let file_path = format!("{}/{}.partial", self.temp_dir, session_id);
         
         let session = UploadSession {
             session_id: session_id.clone(),
             file_path: file_path.clone(),
+            file_name: filename.to_string(),
+            target_dir,
             total_size,
             uploaded_size: 0,
             chunk_size: 1024 * 1024, // 1MB chunks
-            file_hash: String::new(),
+            file_hash: file_hash.map(str::to_ascii_lowercase),
+            username: username.to_string(),
             created_at: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
@@ -60,15 +76,36 @@ impl ResumableUploadManager {
         session_id
     }
     
-    pub fn upload_chunk(&self, session_id: &str, chunk_data: &[u8], offset: u64) -> Result<u64, String> {
+    // `checksum` is the SHA-256 the client computed for the chunk. A chunk
+    // that does not match it is not written. Chunks must follow on from what
+    // has already arrived, so `uploaded_size` is always a prefix with no holes.
+    pub fn upload_chunk(&self, session_id: &str, username: &str, chunk_data: &[u8], offset: u64, checksum: &str) -> Result<u64, String> {
         let mut sessions = self.sessions.lock().unwrap();
         
         println!("Uploading chunk for session: {}, offset: {}, size: {}", session_id, offset, chunk_data.len());
         
-        if let Some(session) = sessions.get_mut(session_id) {
+        if let Some(session) = sessions.get_mut(session_id).filter(|session| session.username == username) {
+            if offset + chunk_data.len() as u64 > session.total_size {
+                return Err("Chunk extends past the end of the file".to_string());
+            }
+            if offset > session.uploaded_size {
+                return Err(format!(
+                    "Chunk at offset {} would leave a gap: only the first {} bytes have been received",
+                    offset, session.uploaded_size
+                ));
+            }
+            let actual = format!("{:x}", Sha256::digest(chunk_data));
+            if !actual.eq_ignore_ascii_case(checksum) {
+                return Err(format!(
+                    "Chunk at offset {} was corrupted in transit: its SHA-256 is {}, expected {}. Send it again.",
+                    offset, actual, checksum
+                ));
+            }
+            
             let mut file = OpenOptions::new()
                 .write(true)
                 .create(true)
+                .truncate(false)
                 .open(&session.file_path)
                 .map_err(|e| format!("Failed to open file: {}", e))?;
             
@@ -91,26 +128,163 @@ impl ResumableUploadManager {
         }
     }
     
-    pub fn get_session(&self, session_id: &str) -> Option<UploadSession> {
+    // Sessions started by another user are not found
+    pub fn get_session(&self, session_id: &str, username: &str) -> Option<UploadSession> {
         let sessions = self.sessions.lock().unwrap();
-        sessions.get(session_id).cloned()
+        sessions.get(session_id).filter(|session| session.username == username).cloned()
     }
     
-    pub fn complete_upload(&self, session_id: &str, final_path: &str) -> Result<(), String> {
-        let mut sessions = self.sessions.lock().unwrap();
+    // Once every byte has arrived, takes the session out of the map so no
+    // more chunks are accepted, then checks the assembled file against the
+    // SHA-256 the client gave when starting or completing. A file that does
+    // not match is discarded. The hash is computed without holding the lock,
+    // so call this off the async runtime.
+    pub fn finish_upload(&self, session_id: &str, username: &str, file_hash: Option<&str>) -> Result<UploadSession, String> {
+        let (session, expected) = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let session = sessions.get(session_id)
+                .filter(|session| session.username == username)
+                .ok_or("Session not found")?;
+            if session.uploaded_size < session.total_size {
+                return Err(format!("Upload is incomplete: {} of {} bytes received", session.uploaded_size, session.total_size));
+            }
+            let expected = match (file_hash.map(str::to_ascii_lowercase), &session.file_hash) {
+                (None, None) => return Err("The SHA-256 of the whole file is required to complete an upload".to_string()),
+                (Some(given), None) => given,
+                (Some(given), Some(started)) if given != *started => {
+                    return Err("The SHA-256 given on completion differs from the one given when the upload started".to_string());
+                }
+                (_, Some(started)) => started.clone(),
+            };
+            (sessions.remove(session_id).unwrap(), expected)
+        };
         
-        if let Some(session) = sessions.remove(session_id) {
-            std::fs::rename(&session.file_path, final_path)
-                .map_err(|e| format!("Failed to move file: {}", e))?;
-            println!("Upload completed: {} -> {}", session.file_path, final_path);
-            Ok(())
-        } else {
-            Err("Session not found".to_string())
-        }
+        let checked = match hash_file(&session.file_path) {
+            Ok(actual) if actual == expected => return Ok(session),
+            Ok(actual) => format!(
+                "The uploaded file is corrupt: its SHA-256 is {}, expected {}. The upload was discarded; please upload the file again.",
+                actual, expected
+            ),
+            Err(e) => format!("Failed to read the uploaded file: {}. The upload was discarded.", e),
+        };
+        self.discard(&session);
+        Err(checked)
+    }
+    
+    pub fn discard(&self, session: &UploadSession) {
+        let _ = std::fs::remove_file(&session.file_path);
+        println!("Discarded upload session: {}", session.session_id);
+    }
+    
+    pub fn complete_upload(&self, session: &UploadSession, final_path: &str) -> Result<(), String> {
+        replace_file(Path::new(&session.file_path), Path::new(final_path))
+            .map_err(|e| format!("Failed to move file: {}", e))?;
+        println!("Upload completed: {} -> {}", session.file_path, final_path);
+        Ok(())
     }
 }
 
+// Checksum helpers shared by the upload page and drag-and-drop uploads in
+// the file browser. Each chunk is sent with an X-Chunk-SHA256 header, and
+// the SHA-256 of the whole file is built up as the chunks go out.
+pub const UPLOAD_CHECKSUM_JS: &str = r#"
+        // Incremental SHA-256 for upload checksums. crypto.subtle cannot hash
+        // a file piece by piece and is missing on pages served over plain HTTP.
+        const SHA256_K = new Int32Array([
+            0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+            0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+            0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+            0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+            0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+            0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+            0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+            0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2
+        ]);
+        
+        class Sha256 {
+            constructor() {
+                this.state = new Int32Array([0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19]);
+                this.block = new Uint8Array(64);
+                this.blockLength = 0;
+                this.length = 0;
+                this.words = new Int32Array(64);
+            }
+            
+            update(bytes) {
+                this.length += bytes.length;
+                let i = 0;
+                if (this.blockLength > 0) {
+                    const take = Math.min(64 - this.blockLength, bytes.length);
+                    this.block.set(bytes.subarray(0, take), this.blockLength);
+                    this.blockLength += take;
+                    i = take;
+                    if (this.blockLength < 64) return this;
+                    this.compress(this.block, 0);
+                    this.blockLength = 0;
+                }
+                // Whole blocks are hashed straight from the input
+                for (; i + 64 <= bytes.length; i += 64) {
+                    this.compress(bytes, i);
+                }
+                this.block.set(bytes.subarray(i), 0);
+                this.blockLength = bytes.length - i;
+                return this;
+            }
+            
+            compress(b, offset) {
+                const w = this.words, s = this.state;
+                for (let t = 0; t < 16; t++) {
+                    const j = offset + t * 4;
+                    w[t] = (b[j] << 24) | (b[j + 1] << 16) | (b[j + 2] << 8) | b[j + 3];
+                }
+                for (let t = 16; t < 64; t++) {
+                    const x = w[t - 15], y = w[t - 2];
+                    const s0 = ((x >>> 7) | (x << 25)) ^ ((x >>> 18) | (x << 14)) ^ (x >>> 3);
+                    const s1 = ((y >>> 17) | (y << 15)) ^ ((y >>> 19) | (y << 13)) ^ (y >>> 10);
+                    w[t] = w[t - 16] + s0 + w[t - 7] + s1;
+                }
+                let a = s[0] | 0, b1 = s[1] | 0, c = s[2] | 0, d = s[3] | 0;
+                let e = s[4] | 0, f = s[5] | 0, g = s[6] | 0, h = s[7] | 0;
+                for (let t = 0; t < 64; t++) {
+                    const S1 = ((e >>> 6) | (e << 26)) ^ ((e >>> 11) | (e << 21)) ^ ((e >>> 25) | (e << 7));
+                    const t1 = (h + S1 + ((e & f) ^ (~e & g)) + SHA256_K[t] + w[t]) | 0;
+                    const S0 = ((a >>> 2) | (a << 30)) ^ ((a >>> 13) | (a << 19)) ^ ((a >>> 22) | (a << 10));
+                    const t2 = (S0 + ((a & b1) ^ (a & c) ^ (b1 & c))) | 0;
+                    h = g; g = f; f = e; e = (d + t1) | 0;
+                    d = c; c = b1; b1 = a; a = (t1 + t2) | 0;
+                }
+                s[0] += a; s[1] += b1; s[2] += c; s[3] += d; s[4] += e; s[5] += f; s[6] += g; s[7] += h;
+            }
+            
+            // Finishes the hash; the object cannot be updated afterwards
+            hex() {
+                const bits = this.length * 8;
+                const padding = new Uint8Array((this.blockLength < 56 ? 64 : 128) - this.blockLength);
+                padding[0] = 0x80;
+                const view = new DataView(padding.buffer);
+                view.setUint32(padding.length - 8, Math.floor(bits / 0x100000000));
+                view.setUint32(padding.length - 4, bits >>> 0);
+                this.update(padding);
+                return Array.from(this.state, word => (word >>> 0).toString(16).padStart(8, '0')).join('');
+            }
+        }
+        
+        // SHA-256 of one chunk, natively where the browser allows it
+        async function chunkChecksum(bytes) {
+            if (!window.crypto || !crypto.subtle) return new Sha256().update(bytes).hex();
+            const digest = new Uint8Array(await crypto.subtle.digest('SHA-256', bytes));
+            return Array.from(digest, byte => byte.toString(16).padStart(2, '0')).join('');
+        }
+"#;
+
+fn hash_file(path: &str) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 // Global upload manager instance
 lazy_static::lazy_static! {
-    pub static ref UPLOAD_MANAGER: ResumableUploadManager = ResumableUploadManager::new("./temp_uploads");
+    pub static ref UPLOAD_MANAGER: ResumableUploadManager = ResumableUploadManager::new(&CONFIG.temp_dir.to_string_lossy());
 }
\ No newline at end of file
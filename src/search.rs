@@ -0,0 +1,441 @@
+use crate::config::CONFIG;
+use crate::archive::archive_kind;
+use crate::file_browser::{FileEntry, FileInfo, get_file_type_class};
+use crate::handlers::utils::{is_excluded_entry, query_param};
+use crate::visibility::Access;
+use crate::utils::{escape_html, parse_size};
+use chrono::{Local, NaiveDate, TimeZone};
+use hyper::body::Bytes;
+use regex::{Regex, RegexBuilder};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tokio::sync::mpsc;
+
+// Stop after this many matches; a broader query is more useful than a longer list
+pub const MAX_SEARCH_RESULTS: usize = 1000;
+// A progress line goes out after this many entries even when nothing matches
+const PROGRESS_INTERVAL: usize = 500;
+
+pub enum NameMatcher {
+    // Lowercased needle
+    Substring(String),
+    // Globs are translated to an anchored regex
+    Pattern(Regex),
+}
+
+impl NameMatcher {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NameMatcher::Substring(needle) => name.to_lowercase().contains(needle.as_str()),
+            NameMatcher::Pattern(regex) => regex.is_match(name),
+        }
+    }
+}
+
+pub struct SearchQuery {
+    pub matcher: NameMatcher,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub modified_after: Option<SystemTime>,
+    pub modified_before: Option<SystemTime>,
+    // "file", "folder" or a file category such as "image"
+    pub kind: Option<String>,
+}
+
+impl SearchQuery {
+    // Reads `q`, `mode` (substring, glob, regex), `minSize`, `maxSize`
+    // (bytes, or with a K/M/G suffix), `after`, `before` (YYYY-MM-DD, both
+    // inclusive) and `type`
+    pub fn from_query(query: Option<&str>) -> Result<SearchQuery, String> {
+        let text = query_param(query, "q").unwrap_or_default();
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("Enter something to search for".to_string());
+        }
+
+        let matcher = match query_param(query, "mode").as_deref() {
+            None | Some("") | Some("substring") => NameMatcher::Substring(text.to_lowercase()),
+            Some("glob") => NameMatcher::Pattern(build_regex(&glob_to_regex(text))?),
+            Some("regex") => NameMatcher::Pattern(build_regex(text)?),
+            Some(other) => return Err(format!("Unknown search mode: {}", other)),
+        };
+
+        let kind = query_param(query, "type").filter(|kind| !kind.is_empty());
+        if let Some(kind) = &kind
+            && !["file", "folder", "image", "video", "text", "pdf", "archive"].contains(&kind.as_str()) {
+            return Err(format!("Unknown type: {}", kind));
+        }
+
+        Ok(SearchQuery {
+            matcher,
+            min_size: parse_param(query, "minSize", parse_size)?,
+            max_size: parse_param(query, "maxSize", parse_size)?,
+            modified_after: parse_param(query, "after", |value| parse_date(value, 0))?,
+            modified_before: parse_param(query, "before", |value| parse_date(value, 1))?,
+            kind,
+        })
+    }
+
+    fn matches(&self, entry: &FileEntry) -> bool {
+        let kind_matches = match self.kind.as_deref() {
+            None => true,
+            Some("folder") => entry.is_dir,
+            Some("file") => !entry.is_dir,
+            Some("archive") => !entry.is_dir && archive_kind(&entry.name).is_some(),
+            Some(category) => !entry.is_dir && get_file_type_class(&entry.name).strip_suffix("-file") == Some(category),
+        };
+        // Size limits only make sense for files
+        let size_matches = match entry.size {
+            Some(size) => self.min_size.is_none_or(|min| size >= min) && self.max_size.is_none_or(|max| size <= max),
+            None => self.min_size.is_none() && self.max_size.is_none(),
+        };
+        let date_matches = match entry.modified {
+            Some(modified) => self.modified_after.is_none_or(|after| modified >= after)
+                && self.modified_before.is_none_or(|before| modified < before),
+            None => self.modified_after.is_none() && self.modified_before.is_none(),
+        };
+        kind_matches && size_matches && date_matches
+    }
+}
+
+fn build_regex(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(1 << 20)
+        .build()
+        .map_err(|e| format!("Invalid pattern: {}", e))
+}
+
+// `*` and `?` wildcards plus `[...]` classes, matched against the whole name
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                let mut class = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        closed = true;
+                        break;
+                    }
+                    class.push(c);
+                }
+                if closed && !class.is_empty() {
+                    let class = class.strip_prefix('!').map(|rest| format!("^{}", rest)).unwrap_or(class);
+                    regex.push('[');
+                    regex.push_str(&class.replace('\\', "\\\\"));
+                    regex.push(']');
+                } else {
+                    regex.push_str(&regex::escape(&format!("[{}", class)));
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+fn parse_param<T>(query: Option<&str>, name: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Option<T>, String> {
+    match query_param(query, name) {
+        Some(value) if !value.trim().is_empty() => parse(value.trim())
+            .map(Some)
+            .ok_or_else(|| format!("Invalid {}: {}", name, value)),
+        _ => Ok(None),
+    }
+}
+
+// Local midnight at the start of the given day, `days_after` days later
+fn parse_date(value: &str, days_after: u64) -> Option<SystemTime> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?
+        .checked_add_days(chrono::Days::new(days_after))?;
+    let midnight = Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?;
+    Some(midnight.into())
+}
+
+// Walks `roots` depth-first, each a folder with its path as clients see it,
+// and streams newline-delimited JSON: one `{"match": ...}` line per hit,
+// occasional `{"scanned": n}` lines, and a final `{"done": true, ...}`
+// summary. Dropping the receiver (the client went away or cancelled) stops
+// the walk at the next entry. Symlinked folders are listed but never followed.
+pub fn search_names(roots: Vec<(PathBuf, String)>, query: SearchQuery, access: Access) -> mpsc::Receiver<io::Result<Bytes>> {
+    let (sender, receiver) = mpsc::channel(32);
+
+    tokio::task::spawn_blocking(move || {
+        let send = |line: serde_json::Value| sender.blocking_send(Ok(Bytes::from(format!("{}\n", line)))).is_ok();
+        let mut stack = roots;
+        let mut scanned = 0;
+        let mut matches = 0;
+
+        while let Some((dir, relative_dir)) = stack.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let path = entry.path();
+                let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+                if is_excluded_entry(&path, is_dir, &access) {
+                    continue;
+                }
+                let relative_path = if relative_dir.is_empty() { name.clone() } else { format!("{}/{}", relative_dir, name) };
+                if is_dir {
+                    stack.push((path.clone(), relative_path.clone()));
+                }
+
+                scanned += 1;
+                if scanned % PROGRESS_INTERVAL == 0 && !send(serde_json::json!({ "scanned": scanned })) {
+                    return;
+                }
+                if !query.matcher.matches(&name) {
+                    continue;
+                }
+                let file_entry = match FileEntry::from_path(&path) {
+                    Ok(file_entry) if query.matches(&file_entry) => file_entry,
+                    _ => continue,
+                };
+                if !send(serde_json::json!({ "match": FileInfo::from_entry(&file_entry, &relative_path) })) {
+                    return;
+                }
+                matches += 1;
+                if matches >= MAX_SEARCH_RESULTS {
+                    send(serde_json::json!({ "done": true, "scanned": scanned, "matches": matches, "truncated": true }));
+                    return;
+                }
+            }
+        }
+
+        send(serde_json::json!({ "done": true, "scanned": scanned, "matches": matches, "truncated": false }));
+    });
+
+    receiver
+}
+
+// `folder` is the decoded folder the search starts in, relative to the root
+pub fn generate_search_html(folder: &str) -> String {
+    let folder = folder.trim_matches('/');
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Search - {app_title}</title>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <style>
+        body {{ font-family: Arial, sans-serif; margin: 0; padding: 20px; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; }}
+        .container {{ max-width: 1100px; margin: 0 auto; background: rgba(255, 255, 255, 0.95); border-radius: 16px; box-shadow: 0 8px 32px rgba(0,0,0,0.15); overflow: hidden; }}
+        .search-header {{ display: flex; align-items: center; justify-content: space-between; gap: 16px; padding: 16px 24px; border-bottom: 1px solid #e5e7eb; }}
+        .search-title {{ font-size: 18px; font-weight: 700; color: #1f2937; }}
+        .search-meta {{ font-size: 12px; color: #6b7280; margin-top: 4px; }}
+        .search-meta.error {{ color: #dc2626; }}
+        .search-form {{ display: flex; flex-wrap: wrap; gap: 8px; padding: 16px 24px; border-bottom: 1px solid #e5e7eb; align-items: center; }}
+        .search-form input, .search-form select {{ padding: 7px 10px; border: 1px solid #d1d5db; border-radius: 6px; font-size: 13px; }}
+        .search-form input[name=q] {{ flex: 1; min-width: 220px; }}
+        .search-form input[type=text].small {{ width: 80px; }}
+        .search-form label {{ font-size: 12px; color: #6b7280; display: flex; align-items: center; gap: 4px; }}
+        .action-btn {{ font-size: 13px; padding: 6px 12px; border: 1px solid #2563eb; border-radius: 6px; text-decoration: none; color: #2563eb; background: white; font-weight: 500; cursor: pointer; transition: all 0.2s ease; }}
+        .action-btn:hover {{ background: #2563eb; color: white; }}
+        .action-btn.primary {{ background: #2563eb; color: white; }}
+        .action-btn.danger {{ border-color: #dc2626; color: #dc2626; }}
+        .action-btn.danger:hover {{ background: #dc2626; color: white; }}
+        table {{ width: 100%; border-collapse: collapse; font-size: 14px; }}
+        th, td {{ padding: 10px 16px; text-align: left; border-bottom: 1px solid #f3f4f6; }}
+        th {{ color: #6b7280; font-weight: 600; font-size: 12px; text-transform: uppercase; }}
+        td a {{ color: #1f2937; text-decoration: none; font-weight: 500; word-break: break-all; }}
+        td a:hover {{ color: #2563eb; }}
+        .location {{ color: #6b7280; font-size: 13px; word-break: break-all; }}
+        .empty {{ padding: 60px; text-align: center; color: #6b7280; font-size: 16px; }}
+        .snippet {{ color: #4b5563; font-size: 13px; margin-top: 4px; font-weight: normal; word-break: break-word; }}
+        .snippet mark {{ background: #fde68a; color: #1f2937; border-radius: 2px; padding: 0 1px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="search-header">
+            <div>
+                <div class="search-title">🔍 Search in /{folder}</div>
+                <div class="search-meta" id="status">Searches this folder and everything below it, by name or by what is inside text files.</div>
+            </div>
+            <a href="/browse/{folder_url}" class="action-btn">← Back to files</a>
+        </div>
+        <form class="search-form" id="searchForm">
+            <input type="search" name="q" placeholder="Name to search for..." autofocus>
+            <select name="mode">
+                <option value="substring">Contains</option>
+                <option value="glob">Glob (*.rs)</option>
+                <option value="regex">Regex</option>
+                <option value="content">File contents</option>
+            </select>
+            <select name="type">
+                <option value="">Any type</option>
+                <option value="file">Files</option>
+                <option value="folder">Folders</option>
+                <option value="image">Images</option>
+                <option value="video">Videos</option>
+                <option value="text">Text</option>
+                <option value="pdf">PDFs</option>
+                <option value="archive">Archives</option>
+            </select>
+            <label>Size <input type="text" class="small" name="minSize" placeholder="min"> – <input type="text" class="small" name="maxSize" placeholder="max"></label>
+            <label>Modified <input type="date" name="after"> – <input type="date" name="before"></label>
+            <button type="submit" class="action-btn primary" id="searchBtn">Search</button>
+            <button type="button" class="action-btn danger" id="cancelBtn" onclick="cancelSearch()" hidden>Cancel</button>
+        </form>
+        <table id="results" hidden>
+            <thead>
+                <tr><th>Name</th><th>Location</th><th>Size</th><th>Modified</th></tr>
+            </thead>
+            <tbody id="resultRows"></tbody>
+        </table>
+        <div class="empty" id="empty" hidden>No matches</div>
+    </div>
+    <script>
+        const folder = {folder_json};
+        const form = document.getElementById('searchForm');
+        const status = document.getElementById('status');
+        const rows = document.getElementById('resultRows');
+        let controller = null;
+
+        function setStatus(text, isError) {{
+            status.textContent = text;
+            status.className = 'search-meta' + (isError ? ' error' : '');
+        }}
+
+        function escapeHtml(text) {{
+            const div = document.createElement('div');
+            div.textContent = text;
+            return div.innerHTML;
+        }}
+
+        function encodePath(path) {{
+            return path.split('/').map(encodeURIComponent).join('/');
+        }}
+
+        function formatSize(bytes) {{
+            if (bytes === null || bytes === undefined) return '';
+            const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+            let size = bytes, unit = 0;
+            while (size >= 1024 && unit < units.length - 1) {{ size /= 1024; unit++; }}
+            return (unit === 0 ? size : size.toFixed(1)) + ' ' + units[unit];
+        }}
+
+        function addRow(item) {{
+            const slash = item.path.lastIndexOf('/');
+            const parent = slash < 0 ? '' : item.path.slice(0, slash);
+            const href = item.type === 'directory' ? '/browse/' + encodePath(item.path) : '/file/' + encodePath(item.path);
+            const modified = item.modified ? new Date(item.modified).toLocaleString() : '';
+            const row = document.createElement('tr');
+            row.innerHTML = `<td><a href="${{href}}">${{item.type === 'directory' ? '📁' : '📄'}} ${{escapeHtml(item.name)}}</a></td>`
+                + `<td class="location"><a href="/browse/${{encodePath(parent)}}">/${{escapeHtml(parent)}}</a></td>`
+                + `<td>${{formatSize(item.size)}}</td><td>${{modified}}</td>`;
+            rows.appendChild(row);
+        }}
+
+        // Content matches come back ranked in one response, each with a snippet
+        // that the server has already escaped and highlighted
+        async function contentSearch(params, signal) {{
+            const response = await fetch('/api/content-search?' + params, {{ signal }});
+            const result = await response.json().catch(() => ({{}}));
+            if (!response.ok) throw new Error(result.error || response.status);
+            for (const item of result.results) {{
+                const slash = item.path.lastIndexOf('/');
+                const parent = slash < 0 ? '' : item.path.slice(0, slash);
+                const modified = item.modified ? new Date(item.modified * 1000).toLocaleString() : '';
+                const row = document.createElement('tr');
+                row.innerHTML = `<td><a href="/file/${{encodePath(item.path)}}">📄 ${{escapeHtml(item.name)}}</a><div class="snippet">${{item.snippet}}</div></td>`
+                    + `<td class="location"><a href="/browse/${{encodePath(parent)}}">/${{escapeHtml(parent)}}</a></td>`
+                    + `<td>${{formatSize(item.size)}}</td><td>${{modified}}</td>`;
+                rows.appendChild(row);
+            }}
+            document.getElementById('results').hidden = !result.results.length;
+            document.getElementById('empty').hidden = !!result.results.length;
+            setStatus(`${{result.results.length}} document(s) · ${{result.indexedFiles}} files indexed`
+                + (result.indexing ? ' · the index is still being built, results may be incomplete' : ''));
+        }}
+
+        function finish() {{
+            controller = null;
+            document.getElementById('cancelBtn').hidden = true;
+            document.getElementById('searchBtn').disabled = false;
+        }}
+
+        function cancelSearch() {{
+            if (controller) controller.abort();
+        }}
+
+        form.addEventListener('submit', async e => {{
+            e.preventDefault();
+            cancelSearch();
+            rows.innerHTML = '';
+            document.getElementById('results').hidden = true;
+            document.getElementById('empty').hidden = true;
+
+            const params = new URLSearchParams(new FormData(form));
+            params.set('path', folder);
+            controller = new AbortController();
+            const signal = controller.signal;
+            document.getElementById('cancelBtn').hidden = false;
+            document.getElementById('searchBtn').disabled = true;
+            setStatus('Searching...');
+
+            let matches = 0;
+            try {{
+                if (params.get('mode') === 'content') {{
+                    await contentSearch(params, signal);
+                    return;
+                }}
+                const response = await fetch('/api/search?' + params, {{ signal }});
+                if (!response.ok) {{
+                    const result = await response.json().catch(() => ({{}}));
+                    throw new Error(result.error || response.status);
+                }}
+                // Each line is a complete JSON object; a chunk may end mid-line
+                const reader = response.body.getReader();
+                const decoder = new TextDecoder();
+                let buffered = '';
+                while (true) {{
+                    const {{ done, value }} = await reader.read();
+                    if (done) break;
+                    buffered += decoder.decode(value, {{ stream: true }});
+                    const lines = buffered.split('\n');
+                    buffered = lines.pop();
+                    for (const line of lines) {{
+                        if (!line) continue;
+                        const message = JSON.parse(line);
+                        if (message.match) {{
+                            matches++;
+                            document.getElementById('results').hidden = false;
+                            addRow(message.match);
+                            setStatus(`Searching... ${{matches}} match(es)`);
+                        }} else if (message.scanned && !message.done) {{
+                            setStatus(`Searching... ${{matches}} match(es) in ${{message.scanned}} items`);
+                        }} else if (message.done) {{
+                            setStatus(`${{message.matches}} match(es) in ${{message.scanned}} items`
+                                + (message.truncated ? ' · showing the first ' + message.matches + ', narrow the search to see more' : ''));
+                        }}
+                    }}
+                }}
+                if (!matches) document.getElementById('empty').hidden = false;
+            }} catch (err) {{
+                if (err.name === 'AbortError') setStatus(`Cancelled · ${{matches}} match(es) so far`);
+                else setStatus('Search failed: ' + err.message, true);
+            }} finally {{
+                if (controller && controller.signal === signal) finish();
+            }}
+        }});
+    </script>
+</body>
+</html>
+"#,
+        folder = escape_html(folder),
+        folder_url = folder.split('/').map(|part| urlencoding::encode(part).into_owned()).collect::<Vec<_>>().join("/"),
+        folder_json = serde_json::to_string(folder).unwrap_or_default().replace("</", "<\\/"),
+        app_title = escape_html(&CONFIG.branding.title),
+    )
+}
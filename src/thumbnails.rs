@@ -0,0 +1,223 @@
+use crate::config::CONFIG;
+use image::{DynamicImage, ImageFormat, ImageReader, Limits, Rgb, RgbImage};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
+
+pub const DEFAULT_THUMBNAIL_DIR: &str = "./thumbnail_cache";
+pub const DEFAULT_THUMBNAIL_SIZE: u32 = 160;
+pub const MIN_THUMBNAIL_SIZE: u32 = 32;
+pub const MAX_THUMBNAIL_SIZE: u32 = 512;
+
+// Images larger than this are not worth decoding just for a preview
+const MAX_SOURCE_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_SOURCE_DIMENSION: u32 = 16384;
+
+// Thumbnails of images that changed or went away are never asked for again,
+// so unused ones are dropped after a while and the cache is kept to a size
+const MAX_UNUSED_SECS: u64 = 30 * 24 * 3600;
+const MAX_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+const PRUNE_INTERVAL_SECS: u64 = 3600;
+
+pub struct Thumbnail {
+    pub data: Vec<u8>,
+    pub etag: String,
+}
+
+pub struct ThumbnailCache {
+    cache_dir: PathBuf,
+    permits: Arc<Semaphore>,
+}
+
+impl ThumbnailCache {
+    pub fn new(cache_dir: &Path, max_workers: usize) -> Self {
+        std::fs::create_dir_all(cache_dir).unwrap_or_default();
+        ThumbnailCache {
+            cache_dir: cache_dir.to_path_buf(),
+            permits: Arc::new(Semaphore::new(max_workers.max(1))),
+        }
+    }
+
+    // Returns a JPEG thumbnail for the image at `source`, generating it on the
+    // blocking pool if there is no cached copy for the current mtime and size
+    pub async fn get_or_create(&self, source: &Path, size: u32) -> Result<Thumbnail, String> {
+        let size = size.clamp(MIN_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE);
+        let metadata = fs::metadata(source).map_err(|e| format!("Failed to read metadata: {}", e))?;
+
+        if !metadata.is_file() {
+            return Err("Not a file".to_string());
+        }
+        if metadata.len() > MAX_SOURCE_BYTES {
+            return Err("Image is too large to thumbnail".to_string());
+        }
+
+        let mtime = metadata.modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+
+        let key = cache_key(source, mtime, size);
+        let cache_path = self.cache_dir.join(format!("{}.jpg", key));
+
+        if let Ok(data) = fs::read(&cache_path) {
+            mark_used(&cache_path);
+            return Ok(Thumbnail { data, etag: key });
+        }
+
+        // Bound the number of concurrent decodes so a folder full of photos
+        // cannot starve the blocking pool
+        let _permit = self.permits.acquire().await
+            .map_err(|e| format!("Thumbnail pool closed: {}", e))?;
+
+        // Another request may have produced it while we were waiting
+        if let Ok(data) = fs::read(&cache_path) {
+            return Ok(Thumbnail { data, etag: key });
+        }
+
+        let source = source.to_path_buf();
+        let data = tokio::task::spawn_blocking(move || render_thumbnail(&source, size))
+            .await
+            .map_err(|e| format!("Thumbnail task failed: {}", e))??;
+
+        // Write to a temp name first so readers never see a half-written file
+        let temp_path = self.cache_dir.join(format!("{}.{}.tmp", key, uuid::Uuid::new_v4()));
+        if fs::write(&temp_path, &data).is_ok() && fs::rename(&temp_path, &cache_path).is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+
+        Ok(Thumbnail { data, etag: key })
+    }
+
+    // Deletes thumbnails unused for `MAX_UNUSED_SECS`, then the least
+    // recently used ones until the cache fits in `MAX_CACHE_BYTES`. Returns
+    // how many files were deleted.
+    pub fn prune(&self) -> usize {
+        let entries = match fs::read_dir(&self.cache_dir) {
+            Ok(entries) => entries,
+            Err(_) => return 0,
+        };
+        let now = SystemTime::now();
+        let mut removed = 0;
+        let mut kept = Vec::new();
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let metadata = match entry.metadata() {
+                Ok(metadata) if metadata.is_file() => metadata,
+                _ => continue,
+            };
+            let used = metadata.modified().unwrap_or(UNIX_EPOCH);
+            let unused_for = now.duration_since(used).unwrap_or_default();
+            if unused_for > Duration::from_secs(MAX_UNUSED_SECS) {
+                if fs::remove_file(entry.path()).is_ok() {
+                    removed += 1;
+                }
+            } else {
+                kept.push((used, metadata.len(), entry.path()));
+            }
+        }
+
+        let mut total: u64 = kept.iter().map(|(_, len, _)| len).sum();
+        kept.sort_by_key(|(used, _, _)| *used);
+        for (_, len, path) in kept {
+            if total <= MAX_CACHE_BYTES {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= len;
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+// Runs `prune` once an hour for the lifetime of the server
+pub fn spawn_prune_task() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(Duration::from_secs(PRUNE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match tokio::task::spawn_blocking(|| THUMBNAIL_CACHE.prune()).await {
+                Ok(0) => {}
+                Ok(removed) => println!("🖼️ Removed {} thumbnail(s) from the cache", removed),
+                Err(e) => eprintln!("❌ Thumbnail cache prune task failed: {}", e),
+            }
+        }
+    });
+}
+
+// A cached thumbnail's mtime records when it was last served
+fn mark_used(path: &Path) {
+    let _ = File::options().write(true).open(path).and_then(|file| file.set_modified(SystemTime::now()));
+}
+
+pub fn is_thumbnailable(filename: &str) -> bool {
+    let lower = filename.to_lowercase();
+    lower.ends_with(".jpg") || lower.ends_with(".jpeg") ||
+    lower.ends_with(".png") || lower.ends_with(".gif") ||
+    lower.ends_with(".webp")
+}
+
+fn cache_key(source: &Path, mtime: u128, size: u32) -> String {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    mtime.hash(&mut hasher);
+    size.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn render_thumbnail(source: &Path, size: u32) -> Result<Vec<u8>, String> {
+    let mut reader = ImageReader::open(source)
+        .map_err(|e| format!("Failed to open image: {}", e))?
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to detect image format: {}", e))?;
+
+    match reader.format() {
+        Some(ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::Gif | ImageFormat::WebP) => {}
+        _ => return Err("Unsupported image format".to_string()),
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    reader.limits(limits);
+
+    let image = reader.decode().map_err(|e| format!("Failed to decode image: {}", e))?;
+    let thumbnail = image.thumbnail(size, size);
+    let flattened = flatten_onto_white(&thumbnail);
+
+    let mut output = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(flattened)
+        .write_to(&mut output, ImageFormat::Jpeg)
+        .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+
+    Ok(output.into_inner())
+}
+
+// JPEG has no alpha channel, so blend transparent pixels onto a white background
+fn flatten_onto_white(image: &DynamicImage) -> RgbImage {
+    let rgba = image.to_rgba8();
+    let mut rgb = RgbImage::new(rgba.width(), rgba.height());
+
+    for (x, y, pixel) in rgba.enumerate_pixels() {
+        let [r, g, b, a] = pixel.0;
+        let alpha = a as u32;
+        let blend = |c: u8| ((c as u32 * alpha + 255 * (255 - alpha)) / 255) as u8;
+        rgb.put_pixel(x, y, Rgb([blend(r), blend(g), blend(b)]));
+    }
+
+    rgb
+}
+
+// Global thumbnail cache instance
+lazy_static::lazy_static! {
+    pub static ref THUMBNAIL_CACHE: ThumbnailCache = ThumbnailCache::new(
+        &CONFIG.thumbnails.dir,
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(2).min(4),
+    );
+}
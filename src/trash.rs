@@ -0,0 +1,343 @@
+use crate::config::CONFIG;
+use crate::file_ops::{move_path, remove_path, total_size};
+use crate::utils::{escape_html, format_file_size};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+pub const DEFAULT_TRASH_DIR: &str = "./trash";
+pub const DEFAULT_RETENTION_DAYS: u64 = 30;
+const PURGE_INTERVAL_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    pub id: String,
+    pub name: String,
+    // Relative to the served root, as used in /browse URLs
+    pub original_path: String,
+    pub deleted_at: u64,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+// Each user gets `<trash_dir>/<user>/files/<id>` holding the deleted item and
+// `<trash_dir>/<user>/info/<id>.json` describing where it came from
+pub struct TrashManager {
+    trash_dir: PathBuf,
+    retention_days: u64,
+}
+
+impl TrashManager {
+    pub fn new(trash_dir: &Path, retention_days: u64) -> Self {
+        std::fs::create_dir_all(trash_dir).unwrap_or_default();
+        TrashManager {
+            trash_dir: trash_dir.to_path_buf(),
+            retention_days,
+        }
+    }
+
+    pub fn retention_days(&self) -> u64 {
+        self.retention_days
+    }
+
+    fn user_dir(&self, username: &str) -> PathBuf {
+        let safe_name: String = username.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.trash_dir.join(safe_name)
+    }
+
+    fn item_paths(&self, username: &str, id: &str) -> Option<(PathBuf, PathBuf)> {
+        // Ids come from clients, so only accept what we generate
+        Uuid::parse_str(id).ok()?;
+        let user_dir = self.user_dir(username);
+        Some((user_dir.join("files").join(id), user_dir.join("info").join(format!("{}.json", id))))
+    }
+
+    // Moves `source` into the user's trash and records where it came from
+    pub fn trash_item(&self, username: &str, source: &Path, original_path: &str) -> io::Result<TrashEntry> {
+        let metadata = fs::symlink_metadata(source)?;
+        let id = Uuid::new_v4().to_string();
+        let (file_path, info_path) = self.item_paths(username, &id)
+            .ok_or_else(|| io::Error::other("Invalid trash id"))?;
+        fs::create_dir_all(file_path.parent().unwrap())?;
+        fs::create_dir_all(info_path.parent().unwrap())?;
+
+        let entry = TrashEntry {
+            id,
+            name: source.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            original_path: original_path.trim_matches('/').to_string(),
+            deleted_at: now_secs(),
+            is_dir: metadata.is_dir(),
+            size: total_size(source),
+        };
+
+        move_path(source, &file_path)?;
+
+        let info = serde_json::to_vec_pretty(&entry).map_err(io::Error::other)?;
+        if let Err(e) = fs::write(&info_path, info) {
+            // Put the item back rather than leave it in the trash untracked
+            let _ = move_path(&file_path, source);
+            return Err(e);
+        }
+
+        Ok(entry)
+    }
+
+    // Newest first
+    pub fn list(&self, username: &str) -> Vec<TrashEntry> {
+        let user_dir = self.user_dir(username);
+        let mut entries: Vec<TrashEntry> = fs::read_dir(user_dir.join("info"))
+            .map(|dir| {
+                dir.flatten()
+                    .filter_map(|entry| fs::read(entry.path()).ok())
+                    .filter_map(|data| serde_json::from_slice::<TrashEntry>(&data).ok())
+                    .filter(|entry| fs::symlink_metadata(user_dir.join("files").join(&entry.id)).is_ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+        entries
+    }
+
+    pub fn get(&self, username: &str, id: &str) -> Option<TrashEntry> {
+        let (_, info_path) = self.item_paths(username, id)?;
+        let data = fs::read(info_path).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    // Moves the item back to `target`, recreating missing parent folders.
+    // Fails with AlreadyExists instead of replacing whatever is there now.
+    pub fn restore(&self, username: &str, id: &str, target: &Path) -> io::Result<()> {
+        let (file_path, info_path) = self.item_paths(username, id)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Unknown trash item"))?;
+
+        if fs::symlink_metadata(target).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "An item already exists at the original location"));
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        move_path(&file_path, target)?;
+        fs::remove_file(info_path)
+    }
+
+    pub fn delete_permanently(&self, username: &str, id: &str) -> io::Result<()> {
+        let (file_path, info_path) = self.item_paths(username, id)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Unknown trash item"))?;
+
+        match remove_path(&file_path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        fs::remove_file(info_path)
+    }
+
+    // Permanently deletes everything older than the retention period for all
+    // users. A retention of zero days keeps items until they are removed by hand.
+    pub fn purge_expired(&self) -> usize {
+        if self.retention_days == 0 {
+            return 0;
+        }
+        let cutoff = now_secs().saturating_sub(self.retention_days * 24 * 3600);
+
+        let mut purged = 0;
+        for user_dir in fs::read_dir(&self.trash_dir).into_iter().flatten().flatten() {
+            let username = user_dir.file_name().to_string_lossy().into_owned();
+            for entry in self.list(&username) {
+                if entry.deleted_at < cutoff {
+                    match self.delete_permanently(&username, &entry.id) {
+                        Ok(()) => purged += 1,
+                        Err(e) => eprintln!("❌ Failed to purge {} from trash: {}", entry.name, e),
+                    }
+                }
+            }
+        }
+        purged
+    }
+}
+
+// Runs `purge_expired` once an hour for the lifetime of the server
+pub fn spawn_purge_task() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(Duration::from_secs(PURGE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match tokio::task::spawn_blocking(|| TRASH.purge_expired()).await {
+                Ok(0) => {}
+                Ok(purged) => println!("🗑️ Purged {} expired item(s) from the trash", purged),
+                Err(e) => eprintln!("❌ Trash purge task failed: {}", e),
+            }
+        }
+    });
+}
+
+pub fn generate_trash_html(entries: &[TrashEntry], retention_days: u64) -> String {
+    let mut rows = String::new();
+    for entry in entries {
+        let deleted_at = chrono::DateTime::from_timestamp(entry.deleted_at as i64, 0)
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let location = match entry.original_path.rsplit_once('/') {
+            Some((parent, _)) => format!("/{}", parent),
+            None => "/".to_string(),
+        };
+
+        rows.push_str(&format!(r#"
+            <tr data-id="{id}">
+                <td><input type="checkbox" class="select-item" value="{id}"></td>
+                <td class="item-name">{icon} {name}</td>
+                <td>{location}</td>
+                <td>{deleted_at}</td>
+                <td>{size}</td>
+                <td class="row-actions">
+                    <button class="action-btn" onclick="restoreItems(['{id}'])">Restore</button>
+                    <button class="action-btn danger" onclick="deleteItems(['{id}'])">Delete forever</button>
+                </td>
+            </tr>"#,
+            id = entry.id,
+            icon = if entry.is_dir { "📁" } else { "📄" },
+            name = escape_html(&entry.name),
+            location = escape_html(&location),
+            deleted_at = deleted_at,
+            size = format_file_size(entry.size),
+        ));
+    }
+
+    let retention_note = if retention_days == 0 {
+        "Items stay here until you delete them.".to_string()
+    } else {
+        format!("Items are deleted permanently {} days after they are moved here.", retention_days)
+    };
+
+    let body = if entries.is_empty() {
+        r#"<div class="empty">🗑️ The trash is empty</div>"#.to_string()
+    } else {
+        format!(r#"
+        <table>
+            <thead>
+                <tr>
+                    <th><input type="checkbox" id="selectAll" onclick="toggleAll(this.checked)"></th>
+                    <th>Name</th>
+                    <th>Original location</th>
+                    <th>Deleted</th>
+                    <th>Size</th>
+                    <th></th>
+                </tr>
+            </thead>
+            <tbody>{}
+            </tbody>
+        </table>"#, rows)
+    };
+
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Trash - {app_title}</title>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <style>
+        body {{ font-family: Arial, sans-serif; margin: 0; padding: 20px; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; }}
+        .container {{ max-width: 1100px; margin: 0 auto; background: rgba(255, 255, 255, 0.95); border-radius: 16px; box-shadow: 0 8px 32px rgba(0,0,0,0.15); overflow: hidden; }}
+        .trash-header {{ display: flex; align-items: center; justify-content: space-between; gap: 16px; padding: 16px 24px; border-bottom: 1px solid #e5e7eb; }}
+        .trash-title {{ font-size: 18px; font-weight: 700; color: #1f2937; }}
+        .trash-meta {{ font-size: 12px; color: #6b7280; margin-top: 4px; }}
+        .trash-actions {{ display: flex; gap: 8px; flex-shrink: 0; }}
+        .action-btn {{ font-size: 13px; padding: 6px 12px; border: 1px solid #2563eb; border-radius: 6px; text-decoration: none; color: #2563eb; background: white; font-weight: 500; cursor: pointer; transition: all 0.2s ease; }}
+        .action-btn:hover {{ background: #2563eb; color: white; }}
+        .action-btn.danger {{ border-color: #dc2626; color: #dc2626; }}
+        .action-btn.danger:hover {{ background: #dc2626; color: white; }}
+        table {{ width: 100%; border-collapse: collapse; font-size: 14px; }}
+        th, td {{ padding: 10px 16px; text-align: left; border-bottom: 1px solid #f3f4f6; }}
+        th {{ color: #6b7280; font-weight: 600; font-size: 12px; text-transform: uppercase; }}
+        .item-name {{ font-weight: 500; color: #1f2937; word-break: break-all; }}
+        .row-actions {{ white-space: nowrap; text-align: right; }}
+        .empty {{ padding: 60px; text-align: center; color: #6b7280; font-size: 16px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="trash-header">
+            <div>
+                <div class="trash-title">🗑️ Trash</div>
+                <div class="trash-meta">{count} item(s) · {retention}</div>
+            </div>
+            <div class="trash-actions">
+                <a href="/browse" class="action-btn">← Back to files</a>
+                <button class="action-btn" onclick="restoreItems(selectedIds())">Restore selected</button>
+                <button class="action-btn danger" onclick="deleteItems(selectedIds())">Delete selected</button>
+                <button class="action-btn danger" onclick="emptyTrash()">Empty trash</button>
+            </div>
+        </div>
+        {body}
+    </div>
+    <script>
+        function selectedIds() {{
+            return Array.from(document.querySelectorAll('.select-item:checked')).map(box => box.value);
+        }}
+
+        function toggleAll(checked) {{
+            document.querySelectorAll('.select-item').forEach(box => box.checked = checked);
+        }}
+
+        async function postJson(url, body) {{
+            const response = await fetch(url, {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify(body)
+            }});
+            const result = await response.json().catch(() => ({{}}));
+            return {{ response, result }};
+        }}
+
+        function report(action, response, result) {{
+            const problems = (result.conflicts || []).map(c => c + ' (already exists)').concat(result.errors || []);
+            if (!response.ok || problems.length) {{
+                alert(action + ' failed' + (result.error ? ': ' + result.error : '') + (problems.length ? ':\n' + problems.join('\n') : ''));
+            }}
+            location.reload();
+        }}
+
+        async function restoreItems(ids) {{
+            if (!ids.length) return;
+            const {{ response, result }} = await postJson('/api/trash/restore', {{ ids }});
+            report('Restore', response, result);
+        }}
+
+        async function deleteItems(ids) {{
+            if (!ids.length || !confirm(`Permanently delete ${{ids.length}} item(s)? This cannot be undone.`)) return;
+            const {{ response, result }} = await postJson('/api/trash/delete', {{ ids }});
+            report('Delete', response, result);
+        }}
+
+        async function emptyTrash() {{
+            if (!confirm('Permanently delete everything in the trash? This cannot be undone.')) return;
+            const {{ response, result }} = await postJson('/api/trash/empty', {{}});
+            report('Empty trash', response, result);
+        }}
+    </script>
+</body>
+</html>
+"#,
+        count = entries.len(),
+        retention = retention_note,
+        body = body,
+        app_title = escape_html(&CONFIG.branding.title),
+    )
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+// Global trash instance
+lazy_static::lazy_static! {
+    pub static ref TRASH: TrashManager = TrashManager::new(&CONFIG.trash.dir, CONFIG.trash.retention_days);
+}
@@ -1,97 +1,148 @@
-use std::fs;
-use std::io::Write;
-use std::env;
-use std::path::Path;
+use crate::content_index::reindex_in_background;
+use crate::file_ops::replace_file;
+use crate::handlers::utils::validate_file_name;
+use crate::multipart::{MultipartError, MultipartLimits, MultipartParser, PartHandler, PartInfo};
+use crate::utils::next_body_chunk;
+use crate::versions::VERSIONS;
+use crate::visibility::Access;
+use crate::volumes::{HIDDEN_NAME_MESSAGE, VOLUMES};
+use hyper::body::Incoming;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
 
-pub async fn handle_upload(body_bytes: Vec<u8>) -> String {
-    // Simple file upload handler - in a real application, you'd parse multipart data
-    // For now, this is a basic implementation
-    
-    if body_bytes.is_empty() {
-        return "No file data received".to_string();
-    }
-    
-    // Extract filename from multipart data (simplified)
-    let body_str = String::from_utf8_lossy(&body_bytes);
-    let filename = extract_filename(&body_str).unwrap_or("uploaded_file.txt".to_string());
-    
-    // Create upload directory in $HOME\Desktop
-    let upload_dir = get_upload_directory();
-    if !Path::new(&upload_dir).exists() {
-        if let Err(e) = fs::create_dir_all(&upload_dir) {
-            return format!("Failed to create upload directory: {}", e);
-        }
-    }
-    
-    let file_path = format!("{}/{}", upload_dir, filename);
-    
-    // Extract file content from multipart data (simplified)
-    let file_content = extract_file_content(&body_bytes);
-    
-    match fs::File::create(&file_path) {
-        Ok(mut file) => {
-            if let Err(e) = file.write_all(&file_content) {
-                format!("Failed to write file: {}", e)
-            } else {
-                format!("File '{}' uploaded successfully to {}!", filename, upload_dir)
-            }
-        }
-        Err(e) => format!("Failed to create file: {}", e),
+// Where a file named `file_name` uploaded into `folder` is saved. Inside a
+// volume the name must be one the user can see afterwards; the configured
+// upload folder may sit outside every volume.
+pub fn upload_path(folder: &Path, file_name: &str, access: &Access) -> Result<PathBuf, &'static str> {
+    validate_file_name(file_name)?;
+    let path = folder.join(file_name);
+    if VOLUMES.volume_of(folder).is_some() && !VOLUMES.is_visible(&path, false, access) {
+        return Err(HIDDEN_NAME_MESSAGE);
     }
+    Ok(path)
 }
 
-fn get_upload_directory() -> String {
-    // Get the home directory and append Desktop/uploads
-    let home_dir = env::var("USERPROFILE")
-        .or_else(|_| env::var("HOME"))
-        .unwrap_or_else(|_| ".".to_string());
-    
-    format!("{}/Desktop/uploads", home_dir)
+// What became of a multipart upload. Files saved before an error are kept.
+#[derive(Debug, Default)]
+pub struct UploadReport {
+    pub saved: Vec<String>,
+    // "name: reason" for files that were skipped
+    pub rejected: Vec<String>,
+    // Set when the request could not be read to the end
+    pub error: Option<MultipartError>,
 }
 
-fn extract_filename(body_str: &str) -> Option<String> {
-    // Simple filename extraction from multipart data
-    if let Some(start) = body_str.find("filename=\"") {
-        let start = start + 10; // Length of "filename=\""
-        if let Some(end) = body_str[start..].find('"') {
-            return Some(body_str[start..start + end].to_string());
+// Streams a multipart/form-data body into `folder`. Each file is written to
+// a hidden temporary file in `folder` as it arrives, so putting it in place
+// once its part is complete is a rename rather than a copy. Form fields
+// other than files are ignored.
+pub async fn handle_upload(
+    mut body: Incoming,
+    boundary: &str,
+    folder: &Path,
+    access: &Access,
+    limits: MultipartLimits,
+) -> UploadReport {
+    let mut parser = MultipartParser::new(boundary, limits);
+    let mut writer = UploadWriter {
+        folder,
+        access,
+        current: None,
+        report: UploadReport::default(),
+    };
+
+    let result: Result<(), MultipartError> = async {
+        // The configured upload folder is created on first use
+        fs::create_dir_all(folder)?;
+        while let Some(chunk) = next_body_chunk(&mut body).await.map_err(io::Error::other)? {
+            parser.feed(&chunk, &mut writer)?;
         }
+        parser.finish()
+    }.await;
+
+    let mut report = std::mem::take(&mut writer.report);
+    report.error = result.err();
+    report
+}
+
+enum CurrentPart {
+    File { name: String, target: PathBuf, temp: PathBuf, file: BufWriter<File> },
+    Skipped,
+}
+
+struct UploadWriter<'a> {
+    folder: &'a Path,
+    access: &'a Access,
+    current: Option<CurrentPart>,
+    report: UploadReport,
+}
+
+impl UploadWriter<'_> {
+    fn save_file(&mut self, target: &Path, temp: &Path) -> io::Result<()> {
+        // Keep the file being replaced as an earlier version
+        VERSIONS.save_version(target)?;
+        replace_file(temp, target)?;
+        reindex_in_background(target.to_path_buf());
+        Ok(())
     }
-    None
 }
 
-fn extract_file_content(body_bytes: &[u8]) -> Vec<u8> {
-    // Simple file content extraction from multipart data
-    // Look for the double CRLF that separates headers from content
-    let separator = b"\r\n\r\n";
-    
-    for i in 0..body_bytes.len().saturating_sub(separator.len()) {
-        if &body_bytes[i..i + separator.len()] == separator {
-            let content_start = i + separator.len();
-            // Find the end boundary (simplified)
-            let content = &body_bytes[content_start..];
-            
-            // Look for the closing boundary
-            if let Some(boundary_pos) = find_boundary_end(content) {
-                return content[..boundary_pos].to_vec();
+impl PartHandler for UploadWriter<'_> {
+    fn begin_part(&mut self, part: &PartInfo) -> io::Result<()> {
+        let current = match &part.filename {
+            // Browsers send the full path of the file on some systems
+            Some(filename) => match filename.rsplit(['/', '\\']).next().unwrap_or("") {
+                // A file input left empty
+                "" => CurrentPart::Skipped,
+                name => match upload_path(self.folder, name, self.access) {
+                    Ok(target) => {
+                        let temp = self.folder.join(format!(".{}.partial", Uuid::new_v4()));
+                        let file = BufWriter::new(File::create(&temp)?);
+                        CurrentPart::File { name: name.to_string(), target, temp, file }
+                    }
+                    Err(reason) => {
+                        self.report.rejected.push(format!("{}: {}", name, reason));
+                        CurrentPart::Skipped
+                    }
+                },
+            },
+            None => CurrentPart::Skipped,
+        };
+        self.current = Some(current);
+        Ok(())
+    }
+
+    fn part_data(&mut self, data: &[u8]) -> io::Result<()> {
+        match &mut self.current {
+            Some(CurrentPart::File { file, .. }) => file.write_all(data),
+            _ => Ok(()),
+        }
+    }
+
+    fn end_part(&mut self) -> io::Result<()> {
+        if let Some(CurrentPart::File { name, target, temp, file }) = self.current.take() {
+            let saved = file.into_inner()
+                .map_err(|e| e.into_error())
+                .and_then(|_| self.save_file(&target, &temp));
+            if let Err(e) = saved {
+                let _ = fs::remove_file(&temp);
+                return Err(e);
             }
-            
-            return content.to_vec();
+            println!("📤 Uploaded {}", target.display());
+            self.report.saved.push(name);
         }
+        Ok(())
     }
-    
-    // If no proper multipart structure found, return the whole body
-    body_bytes.to_vec()
 }
 
-fn find_boundary_end(content: &[u8]) -> Option<usize> {
-    // Look for CRLF followed by boundary
-    let boundary_marker = b"\r\n--";
-    
-    for i in 0..content.len().saturating_sub(boundary_marker.len()) {
-        if &content[i..i + boundary_marker.len()] == boundary_marker {
-            return Some(i);
+// A file cut off by an error or a dropped connection is not kept
+impl Drop for UploadWriter<'_> {
+    fn drop(&mut self) {
+        if let Some(CurrentPart::File { temp, file, .. }) = self.current.take() {
+            drop(file);
+            let _ = fs::remove_file(temp);
         }
     }
-    None
-}
\ No newline at end of file
+}
@@ -1,4 +1,4 @@
-use hyper::body::Incoming;
+use hyper::body::{Bytes, Incoming};
 use hyper::body::Body;
 use std::future::poll_fn;
 use std::pin::Pin;
@@ -32,6 +32,37 @@ pub async fn collect_body_bytes(mut body: Incoming) -> Result<Vec<u8>, Box<dyn s
     Ok(bytes)
 }
 
+// Like `collect_body_bytes`, but stops reading and returns None as soon as
+// the body grows past `limit` bytes
+pub async fn collect_body_bytes_limited(mut body: Incoming, limit: u64) -> Result<Option<Vec<u8>>, hyper::Error> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = next_body_chunk(&mut body).await? {
+        if (bytes.len() + chunk.len()) as u64 > limit {
+            return Ok(None);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(Some(bytes))
+}
+
+// Next chunk of a request body as it arrives, or None once it has ended
+pub async fn next_body_chunk(body: &mut Incoming) -> Result<Option<Bytes>, hyper::Error> {
+    poll_fn(|cx| {
+        loop {
+            match Pin::new(&mut *body).poll_frame(cx) {
+                Poll::Ready(Some(Ok(frame))) => {
+                    if let Ok(chunk) = frame.into_data() {
+                        return Poll::Ready(Ok(Some(chunk)));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
+                Poll::Ready(None) => return Poll::Ready(Ok(None)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }).await
+}
+
 pub fn format_file_size(size: u64) -> String {
     if size < 1024 {
         format!("{} B", size)
@@ -42,4 +73,33 @@ pub fn format_file_size(size: u64) -> String {
     } else {
         format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
     }
-}
\ No newline at end of file
+}
+
+// "512", "1.5M" or "4GB" (powers of 1024) as a number of bytes
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.to_ascii_uppercase();
+    let value = value.trim_end_matches('B');
+    let (number, multiplier) = match value.char_indices().last()? {
+        (i, 'K') => (&value[..i], 1024),
+        (i, 'M') => (&value[..i], 1024 * 1024),
+        (i, 'G') => (&value[..i], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    (number >= 0.0).then_some((number * multiplier as f64) as u64)
+}
+
+pub fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
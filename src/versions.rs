@@ -0,0 +1,330 @@
+use crate::config::CONFIG;
+use crate::utils::{escape_html, format_file_size};
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_VERSIONS_DIR: &str = "./.versions";
+pub const DEFAULT_KEEP_VERSIONS: usize = 10;
+
+// Lines of unchanged text shown around each change in a diff
+const DIFF_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVersion {
+    pub id: String,
+    pub saved_at: u64,
+    pub size: u64,
+}
+
+// Old contents of overwritten files are kept under
+// `<store_dir>/<hash of the file's path>/<millis>-<n>`, newest last.
+// A `path` file in each folder records which file the versions belong to.
+pub struct VersionStore {
+    store_dir: PathBuf,
+    keep: usize,
+}
+
+impl VersionStore {
+    pub fn new(store_dir: &Path, keep: usize) -> Self {
+        std::fs::create_dir_all(store_dir).unwrap_or_default();
+        let store = VersionStore {
+            store_dir: store_dir.to_path_buf(),
+            keep,
+        };
+        store.rename_folders();
+        store
+    }
+
+    pub fn keep(&self) -> usize {
+        self.keep
+    }
+
+    fn versions_dir(&self, file: &Path) -> PathBuf {
+        let absolute = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        self.store_dir.join(folder_name(&absolute))
+    }
+
+    // Folders used to be named with a hash that could change between builds.
+    // Moves any folder whose recorded path now hashes to a different name.
+    fn rename_folders(&self) {
+        let Ok(entries) = fs::read_dir(&self.store_dir) else { return };
+        for entry in entries.flatten() {
+            let Ok(recorded) = fs::read_to_string(entry.path().join("path")) else { continue };
+            let target = self.store_dir.join(folder_name(Path::new(&recorded)));
+            if target != entry.path() && !target.exists()
+                && let Err(e) = fs::rename(entry.path(), &target) {
+                eprintln!("❌ Failed to rename the versions of {}: {}", recorded, e);
+            }
+        }
+    }
+
+    // Call before `file` is replaced. Keeps its current contents as a new
+    // version and drops the oldest ones beyond the configured limit.
+    // Does nothing when versioning is off or the file does not exist yet.
+    pub fn save_version(&self, file: &Path) -> io::Result<Option<String>> {
+        if self.keep == 0 || !file.is_file() {
+            return Ok(None);
+        }
+
+        let dir = self.versions_dir(file);
+        fs::create_dir_all(&dir)?;
+        let absolute = fs::canonicalize(file)?;
+        fs::write(dir.join("path"), absolute.to_string_lossy().as_bytes())?;
+
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis();
+        let id = (0..)
+            .map(|n| format!("{}-{}", millis, n))
+            .find(|id| !dir.join(id).exists())
+            .unwrap();
+        let version_path = dir.join(&id);
+
+        // The writer replaces the file by renaming over it, so a hard link
+        // keeps the old contents without copying them
+        if fs::hard_link(file, &version_path).is_err() {
+            fs::copy(file, &version_path)?;
+        }
+
+        let versions = self.list_versions(file);
+        for old in versions.iter().skip(self.keep) {
+            let _ = fs::remove_file(dir.join(&old.id));
+        }
+
+        Ok(Some(id))
+    }
+
+    // Newest first
+    pub fn list_versions(&self, file: &Path) -> Vec<FileVersion> {
+        let dir = self.versions_dir(file);
+        let mut versions: Vec<FileVersion> = fs::read_dir(&dir)
+            .map(|entries| {
+                entries.flatten()
+                    .filter_map(|entry| {
+                        let id = entry.file_name().to_string_lossy().into_owned();
+                        let millis = parse_version_id(&id)?;
+                        let size = entry.metadata().ok()?.len();
+                        Some(FileVersion { id, saved_at: (millis / 1000) as u64, size })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        versions.sort_by_key(|version| std::cmp::Reverse(version_sort_key(&version.id)));
+        versions
+    }
+
+    pub fn version_path(&self, file: &Path, id: &str) -> Option<PathBuf> {
+        parse_version_id(id)?;
+        let path = self.versions_dir(file).join(id);
+        path.is_file().then_some(path)
+    }
+
+    // Puts an earlier version back. The contents being replaced become a new
+    // version themselves, so a restore can be undone.
+    pub fn restore_version(&self, file: &Path, id: &str) -> io::Result<()> {
+        let version_path = self.version_path(file, id)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Version not found"))?;
+
+        let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = file.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+        fs::copy(&version_path, &temp_path)?;
+
+        self.save_version(file)?;
+        if let Err(e) = fs::rename(&temp_path, file) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+// Ids are `<millis>-<n>`; anything else is rejected before touching the disk
+fn folder_name(absolute: &Path) -> String {
+    format!("{:x}", Sha256::digest(absolute.as_os_str().as_encoded_bytes()))
+}
+
+fn parse_version_id(id: &str) -> Option<u128> {
+    let (millis, n) = id.split_once('-')?;
+    n.parse::<u32>().ok()?;
+    millis.parse().ok()
+}
+
+fn version_sort_key(id: &str) -> (u128, u32) {
+    let (millis, n) = id.split_once('-').unwrap_or((id, "0"));
+    (millis.parse().unwrap_or(0), n.parse().unwrap_or(0))
+}
+
+// Unified line diff from `old` to `new` as table rows
+pub fn render_diff_rows(old: &str, new: &str) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let mut rows = String::new();
+
+    for (index, group) in diff.grouped_ops(DIFF_CONTEXT_LINES).iter().enumerate() {
+        if index > 0 {
+            rows.push_str(r#"<tr class="diff-gap"><td></td><td></td><td>⋯</td></tr>"#);
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let (class, sign) = match change.tag() {
+                    ChangeTag::Delete => ("diff-del", "-"),
+                    ChangeTag::Insert => ("diff-add", "+"),
+                    ChangeTag::Equal => ("", " "),
+                };
+                let old_number = change.old_index().map(|n| (n + 1).to_string()).unwrap_or_default();
+                let new_number = change.new_index().map(|n| (n + 1).to_string()).unwrap_or_default();
+                rows.push_str(&format!(
+                    r#"<tr class="{}"><td class="line-number">{}</td><td class="line-number">{}</td><td class="line-code">{}{}</td></tr>"#,
+                    class, old_number, new_number, sign,
+                    escape_html(change.value().trim_end_matches(['\r', '\n']))
+                ));
+                rows.push('\n');
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        rows.push_str(r#"<tr><td></td><td></td><td class="line-code">No differences</td></tr>"#);
+    }
+    rows
+}
+
+// `url_path` is the still-encoded path of the file relative to the served root.
+// `diff` is the version id and rendered rows to show, if one was requested.
+pub fn generate_versions_html(file_name: &str, url_path: &str, versions: &[FileVersion], can_diff: bool, diff: Option<(&str, &str)>) -> String {
+    let mut rows = String::new();
+    for version in versions {
+        let saved_at = chrono::DateTime::from_timestamp(version.saved_at as i64, 0)
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let diff_link = if can_diff {
+            format!(r#"<a href="/versions{}?diff={}" class="action-btn">Diff</a>"#, url_path, version.id)
+        } else {
+            String::new()
+        };
+
+        rows.push_str(&format!(r#"
+            <tr>
+                <td>{saved_at}</td>
+                <td>{size}</td>
+                <td class="row-actions">
+                    {diff_link}
+                    <a href="/api/versions{path}?id={id}" class="action-btn">Download</a>
+                    <button class="action-btn" onclick="restoreVersion('{id}')">Restore</button>
+                </td>
+            </tr>"#,
+            saved_at = saved_at,
+            size = format_file_size(version.size),
+            diff_link = diff_link,
+            path = url_path,
+            id = version.id,
+        ));
+    }
+
+    let body = if versions.is_empty() {
+        r#"<div class="empty">🕘 No earlier versions of this file</div>"#.to_string()
+    } else {
+        format!(r#"
+        <table class="versions">
+            <thead>
+                <tr><th>Saved</th><th>Size</th><th></th></tr>
+            </thead>
+            <tbody>{}
+            </tbody>
+        </table>"#, rows)
+    };
+
+    let diff_section = match diff {
+        Some((id, diff_rows)) => format!(r#"
+        <div class="diff-header">Changes from version {} to the current file</div>
+        <div class="diff">
+            <table>
+{}            </table>
+        </div>"#, escape_html(id), diff_rows),
+        None => String::new(),
+    };
+
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Versions of {name} - {app_title}</title>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <style>
+        body {{ font-family: Arial, sans-serif; margin: 0; padding: 20px; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; }}
+        .container {{ max-width: 1100px; margin: 0 auto; background: rgba(255, 255, 255, 0.95); border-radius: 16px; box-shadow: 0 8px 32px rgba(0,0,0,0.15); overflow: hidden; }}
+        .versions-header {{ display: flex; align-items: center; justify-content: space-between; gap: 16px; padding: 16px 24px; border-bottom: 1px solid #e5e7eb; }}
+        .versions-title {{ font-size: 18px; font-weight: 700; color: #1f2937; word-break: break-all; }}
+        .versions-meta {{ font-size: 12px; color: #6b7280; margin-top: 4px; }}
+        .header-actions {{ display: flex; gap: 8px; flex-shrink: 0; }}
+        .action-btn {{ font-size: 13px; padding: 6px 12px; border: 1px solid #2563eb; border-radius: 6px; text-decoration: none; color: #2563eb; background: white; font-weight: 500; cursor: pointer; transition: all 0.2s ease; }}
+        .action-btn:hover {{ background: #2563eb; color: white; }}
+        table.versions {{ width: 100%; border-collapse: collapse; font-size: 14px; }}
+        .versions th, .versions td {{ padding: 10px 16px; text-align: left; border-bottom: 1px solid #f3f4f6; }}
+        .versions th {{ color: #6b7280; font-weight: 600; font-size: 12px; text-transform: uppercase; }}
+        .row-actions {{ white-space: nowrap; text-align: right; }}
+        .empty {{ padding: 60px; text-align: center; color: #6b7280; font-size: 16px; }}
+        .diff-header {{ padding: 12px 24px; font-weight: 600; color: #374151; border-top: 1px solid #e5e7eb; border-bottom: 1px solid #e5e7eb; }}
+        .diff {{ overflow-x: auto; }}
+        .diff table {{ border-collapse: collapse; width: 100%; font-family: Consolas, Menlo, monospace; font-size: 13px; line-height: 1.5; }}
+        .diff td {{ padding: 0 12px; vertical-align: top; }}
+        .diff .line-number {{ width: 1%; text-align: right; color: #9ca3af; user-select: none; background: #f9fafb; border-right: 1px solid #e5e7eb; }}
+        .diff .line-code {{ white-space: pre; }}
+        .diff-add {{ background: #dcfce7; }}
+        .diff-del {{ background: #fee2e2; }}
+        .diff-gap td {{ color: #9ca3af; background: #f3f4f6; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="versions-header">
+            <div>
+                <div class="versions-title">🕘 {name}</div>
+                <div class="versions-meta">{count} earlier version(s) · the last {keep} are kept</div>
+            </div>
+            <div class="header-actions">
+                <a href="/browse{parent}" class="action-btn">← Back</a>
+                <a href="/download{path}" class="action-btn">Download current</a>
+            </div>
+        </div>
+        {body}
+        {diff_section}
+    </div>
+    <script>
+        async function restoreVersion(id) {{
+            if (!confirm('Replace the current file with this version? The current contents are kept as a version.')) return;
+            const response = await fetch('/api/versions/restore', {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify({{ path: decodeURIComponent({path_json}), id }})
+            }});
+            const result = await response.json().catch(() => ({{}}));
+            if (!response.ok) {{
+                alert('Restore failed: ' + (result.error || response.status));
+            }}
+            location.href = location.pathname;
+        }}
+    </script>
+</body>
+</html>
+"#,
+        name = escape_html(file_name),
+        count = versions.len(),
+        keep = VERSIONS.keep(),
+        parent = url_path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or(""),
+        path = url_path,
+        path_json = serde_json::to_string(url_path.trim_start_matches('/')).unwrap_or_default(),
+        body = body,
+        diff_section = diff_section,
+        app_title = escape_html(&CONFIG.branding.title),
+    )
+}
+
+// Global version store instance
+lazy_static::lazy_static! {
+    pub static ref VERSIONS: VersionStore = VersionStore::new(&CONFIG.versions.dir, CONFIG.versions.keep);
+}
@@ -0,0 +1,228 @@
+use crate::utils::{escape_html, format_file_size};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{styled_line_to_highlighted_html, IncludeBackground};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+// Files above this size are sent to the raw view instead of being highlighted
+pub const MAX_HIGHLIGHT_BYTES: u64 = 1024 * 1024;
+
+// Folder READMEs larger than this are not rendered under the grid
+pub const MAX_README_BYTES: u64 = 256 * 1024;
+
+pub const MARKDOWN_CSS: &str = r#"
+        .markdown-body { color: #1f2937; line-height: 1.6; font-size: 15px; word-wrap: break-word; }
+        .markdown-body h1, .markdown-body h2 { border-bottom: 1px solid #e5e7eb; padding-bottom: 6px; }
+        .markdown-body a { color: #2563eb; }
+        .markdown-body code { font-family: Consolas, Menlo, monospace; font-size: 13px; background: #f3f4f6; padding: 2px 4px; border-radius: 4px; }
+        .markdown-body pre { background: #f3f4f6; padding: 12px 16px; border-radius: 8px; overflow-x: auto; }
+        .markdown-body pre code { background: none; padding: 0; }
+        .markdown-body blockquote { margin: 0; padding: 0 16px; color: #6b7280; border-left: 4px solid #d1d5db; }
+        .markdown-body table { border-collapse: collapse; }
+        .markdown-body th, .markdown-body td { border: 1px solid #d1d5db; padding: 6px 12px; }
+        .markdown-body img { max-width: 100%; }
+"#;
+
+lazy_static::lazy_static! {
+    static ref SYNTAX_SET: SyntaxSet = SyntaxSet::load_defaults_newlines();
+    static ref THEME_SET: ThemeSet = ThemeSet::load_defaults();
+}
+
+// Text and source files that get the highlighted viewer instead of the raw file
+pub fn is_source_file(filename: &str) -> bool {
+    let lower = filename.to_lowercase();
+    let extension = match lower.rsplit_once('.') {
+        Some((_, ext)) => ext,
+        None => return false,
+    };
+
+    matches!(extension,
+        "txt" | "log" | "csv" | "md" |
+        "html" | "htm" | "css" | "js" | "mjs" | "ts" | "json" | "xml" |
+        "rs" | "py" | "rb" | "go" | "java" | "c" | "h" | "cpp" | "hpp" | "cc" | "cs" |
+        "php" | "sh" | "bash" | "sql" | "yaml" | "yml" | "toml" | "ini" | "lua" | "pl"
+    )
+}
+
+// `url_path` is the still-encoded path of the file relative to the served root
+pub fn generate_source_view_html(file_name: &str, contents: &str, url_path: &str) -> String {
+    let extension = file_name.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+    let syntax = SYNTAX_SET.find_syntax_by_extension(extension)
+        .or_else(|| SYNTAX_SET.find_syntax_by_first_line(contents))
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    let theme = &THEME_SET.themes["InspiredGitHub"];
+    let mut highlighter = HighlightLines::new(syntax, theme);
+
+    let mut rows = String::new();
+    let mut line_count = 0;
+    for (index, line) in LinesWithEndings::from(contents).enumerate() {
+        let line_number = index + 1;
+        line_count = line_number;
+
+        // Fall back to the escaped plain line if the highlighter chokes
+        let highlighted = highlighter.highlight_line(line, &SYNTAX_SET)
+            .ok()
+            .and_then(|ranges| styled_line_to_highlighted_html(&ranges, IncludeBackground::No).ok())
+            .unwrap_or_else(|| escape_html(line));
+
+        rows.push_str(&format!(
+            r##"<tr id="L{0}"><td class="line-number"><a href="#L{0}">{0}</a></td><td class="line-code">{1}</td></tr>"##,
+            line_number, highlighted.replace(['\r', '\n'], "")
+        ));
+        rows.push('\n');
+    }
+
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{name} - File Viewer</title>
+    <meta charset="UTF-8">
+    <style>
+        body {{ font-family: Arial, sans-serif; margin: 0; padding: 20px; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; }}
+        .container {{ max-width: 1200px; margin: 0 auto; background: rgba(255, 255, 255, 0.95); border-radius: 16px; box-shadow: 0 8px 32px rgba(0,0,0,0.15); overflow: hidden; }}
+        .viewer-header {{ display: flex; align-items: center; justify-content: space-between; gap: 16px; padding: 16px 24px; border-bottom: 1px solid #e5e7eb; }}
+        .viewer-title {{ font-size: 18px; font-weight: 700; color: #1f2937; word-break: break-all; }}
+        .viewer-meta {{ font-size: 12px; color: #6b7280; margin-top: 4px; }}
+        .viewer-actions {{ display: flex; gap: 8px; flex-shrink: 0; }}
+        .action-btn {{ font-size: 13px; padding: 6px 12px; border: 1px solid #2563eb; border-radius: 6px; text-decoration: none; color: #2563eb; background: white; font-weight: 500; transition: all 0.2s ease; }}
+        .action-btn:hover {{ background: #2563eb; color: white; }}
+        .source {{ overflow-x: auto; }}
+        table {{ border-collapse: collapse; width: 100%; font-family: Consolas, Menlo, monospace; font-size: 13px; line-height: 1.5; }}
+        td {{ padding: 0 12px; vertical-align: top; }}
+        .line-number {{ width: 1%; text-align: right; user-select: none; background: #f9fafb; border-right: 1px solid #e5e7eb; }}
+        .line-number a {{ color: #9ca3af; text-decoration: none; }}
+        .line-number a:hover {{ color: #2563eb; }}
+        .line-code {{ white-space: pre; }}
+        tr:target {{ background: #fef3c7; }}
+        tr:target .line-number {{ background: #fde68a; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="viewer-header">
+            <div>
+                <div class="viewer-title">{name}</div>
+                <div class="viewer-meta">{lines} lines · {size} · {syntax}</div>
+            </div>
+            <div class="viewer-actions">
+                <a href="/browse{parent}" class="action-btn">← Back</a>
+                <a href="/edit{path}" class="action-btn">Edit</a>
+                <a href="/file{path}" class="action-btn">Raw</a>
+                <a href="/download{path}" class="action-btn">Download</a>
+            </div>
+        </div>
+        <div class="source">
+            <table>
+{rows}            </table>
+        </div>
+    </div>
+</body>
+</html>
+"#,
+        name = escape_html(file_name),
+        lines = line_count,
+        size = format_file_size(contents.len() as u64),
+        syntax = escape_html(&syntax.name),
+        path = url_path,
+        parent = url_path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or(""),
+        rows = rows,
+    )
+}
+
+// Renders Markdown to sanitized HTML. Relative links and images are resolved
+// against `base_url_path`, the encoded folder the document lives in.
+pub fn render_markdown(source: &str, base_url_path: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let base = base_url_path.trim_end_matches('/');
+
+    let parser = Parser::new_ext(source, options).map(|event| match event {
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+            let dest_url = rewrite_relative_url(dest_url, "/file", base);
+            Event::Start(Tag::Image { link_type, dest_url, title, id })
+        }
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+            let prefix = if dest_url.ends_with('/') {
+                "/browse"
+            } else if is_source_file(&dest_url) {
+                "/view"
+            } else {
+                "/file"
+            };
+            let dest_url = rewrite_relative_url(dest_url, prefix, base);
+            Event::Start(Tag::Link { link_type, dest_url, title, id })
+        }
+        other => other,
+    });
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+    ammonia::clean(&unsafe_html)
+}
+
+fn rewrite_relative_url<'a>(url: CowStr<'a>, prefix: &str, base: &str) -> CowStr<'a> {
+    let is_relative = !url.is_empty()
+        && !url.starts_with('/')
+        && !url.starts_with('#')
+        && !url.contains(':');
+
+    if is_relative {
+        let relative = url.trim_start_matches("./");
+        CowStr::from(format!("{}{}/{}", prefix, base, relative))
+    } else {
+        url
+    }
+}
+
+pub fn generate_markdown_view_html(file_name: &str, contents: &str, url_path: &str) -> String {
+    let parent = url_path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("");
+    let rendered = render_markdown(contents, parent);
+
+    format!(r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{name} - File Viewer</title>
+    <meta charset="UTF-8">
+    <style>
+        body {{ font-family: Arial, sans-serif; margin: 0; padding: 20px; background: linear-gradient(135deg, #f97316 0%, #2563eb 100%); min-height: 100vh; }}
+        .container {{ max-width: 960px; margin: 0 auto; background: rgba(255, 255, 255, 0.95); border-radius: 16px; box-shadow: 0 8px 32px rgba(0,0,0,0.15); overflow: hidden; }}
+        .viewer-header {{ display: flex; align-items: center; justify-content: space-between; gap: 16px; padding: 16px 24px; border-bottom: 1px solid #e5e7eb; }}
+        .viewer-title {{ font-size: 18px; font-weight: 700; color: #1f2937; word-break: break-all; }}
+        .viewer-meta {{ font-size: 12px; color: #6b7280; margin-top: 4px; }}
+        .viewer-actions {{ display: flex; gap: 8px; flex-shrink: 0; }}
+        .action-btn {{ font-size: 13px; padding: 6px 12px; border: 1px solid #2563eb; border-radius: 6px; text-decoration: none; color: #2563eb; background: white; font-weight: 500; transition: all 0.2s ease; }}
+        .action-btn:hover {{ background: #2563eb; color: white; }}
+        .markdown-body {{ padding: 24px 40px; }}
+        {css}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="viewer-header">
+            <div>
+                <div class="viewer-title">{name}</div>
+                <div class="viewer-meta">{size} · Markdown</div>
+            </div>
+            <div class="viewer-actions">
+                <a href="/browse{parent}" class="action-btn">← Back</a>
+                <a href="/edit{path}" class="action-btn">Edit</a>
+                <a href="/file{path}" class="action-btn">Raw</a>
+                <a href="/download{path}" class="action-btn">Download</a>
+            </div>
+        </div>
+        <div class="markdown-body">
+{rendered}
+        </div>
+    </div>
+</body>
+</html>
+"#,
+        name = escape_html(file_name),
+        size = format_file_size(contents.len() as u64),
+        css = MARKDOWN_CSS,
+        parent = parent,
+        path = url_path,
+        rendered = rendered,
+    )
+}
@@ -0,0 +1,170 @@
+use regex::Regex;
+
+// Always hidden unless a rule re-includes them with `!`. These used to be a
+// fixed list of names; as patterns they can be overridden per volume or role.
+pub const DEFAULT_EXCLUDE: &[&str] = &[
+    // Windows system directories
+    "System Volume Information",
+    "$Recycle.Bin",
+    "Config.Msi",
+    "PerfLogs",
+    "Recovery",
+    "Windows",
+    "Program Files",
+    "Program Files (x86)",
+    "ProgramData",
+    "AppData",
+    "/Users/All Users",
+    "/Users/Default",
+    "/Users/Default User",
+    "/Users/Public",
+
+    // Windows system files
+    "pagefile.sys",
+    "hiberfil.sys",
+    "swapfile.sys",
+    "bootmgr",
+    "BOOTNXT",
+    "BOOTSECT.BAK",
+    "ntldr",
+    "NTDETECT.COM",
+    "boot.ini",
+    "desktop.ini",
+    "thumbs.db",
+
+    // Temporary files and shortcuts
+    "*.tmp",
+    "*.temp",
+    "*~",
+    "*.lnk",
+];
+
+// Dotfiles, plus anything with the hidden attribute on Windows. Admins can
+// choose to see these.
+pub const HIDDEN_PATTERN: &str = ".*";
+
+// Who is looking: rules can differ per role, and admins may ask to see
+// hidden files. The default is the base rules with no role.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Access {
+    pub role: String,
+    pub show_hidden: bool,
+}
+
+impl Access {
+    pub fn is_admin(&self) -> bool {
+        self.role == "admin"
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+// A list of gitignore-style patterns. A pattern without a slash matches a
+// name at any depth; one with a slash is anchored to the root it applies
+// to. `*`, `?`, `[abc]` and `**` work as in .gitignore, a trailing `/`
+// matches only folders, and a leading `!` re-includes what an earlier
+// pattern excluded. The last matching pattern wins. Matching ignores case,
+// as the old fixed list did.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    patterns: Vec<Pattern>,
+}
+
+impl RuleSet {
+    pub fn parse<S: AsRef<str>>(lines: &[S]) -> Result<Self, String> {
+        let mut patterns = Vec::new();
+        for line in lines {
+            if let Some(pattern) = compile_pattern(line.as_ref())? {
+                patterns.push(pattern);
+            }
+        }
+        Ok(RuleSet { patterns })
+    }
+
+    // Some(true) if the last matching pattern excludes `relative_path`
+    // ('/'-separated, below the root), Some(false) if it re-includes it,
+    // None if nothing matches
+    pub fn matched(&self, relative_path: &str, is_dir: bool) -> Option<bool> {
+        self.patterns.iter().rev()
+            .find(|pattern| match pattern.regex.captures(relative_path) {
+                // Below a matched folder, or the entry itself if the type fits
+                Some(captures) => captures.name("inside").is_some() || is_dir || !pattern.dir_only,
+                None => false,
+            })
+            .map(|pattern| !pattern.negated)
+    }
+}
+
+fn compile_pattern(line: &str) -> Result<Option<Pattern>, String> {
+    let mut pattern = line.trim_end();
+    if pattern.is_empty() || pattern.starts_with('#') {
+        return Ok(None);
+    }
+
+    // `\!` and `\#` escape a literal leading `!` or `#`
+    let negated = pattern.starts_with('!');
+    if negated || pattern.starts_with("\\!") || pattern.starts_with("\\#") {
+        pattern = &pattern[1..];
+    }
+    let dir_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    if pattern.is_empty() {
+        return Err(format!("Pattern \"{}\" matches nothing", line));
+    }
+
+    let mut regex = String::from("(?i)^");
+    if !anchored {
+        regex.push_str("(?:.*/)?");
+    }
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let at_start = i == 0 || chars[i - 1] == '/';
+                if at_start && chars.get(i + 2) == Some(&'/') {
+                    // `**/` matches any number of folders, including none
+                    regex.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match chars[i + 1..].iter().position(|&c| c == ']') {
+                Some(offset) if offset > 0 => {
+                    let class: String = chars[i + 1..i + 1 + offset].iter().collect();
+                    let class = class.strip_prefix('!').map(|rest| format!("^{}", rest)).unwrap_or(class);
+                    regex.push('[');
+                    regex.push_str(&class.replace('\\', "\\\\"));
+                    regex.push(']');
+                    i += offset + 2;
+                    continue;
+                }
+                _ => regex.push_str("\\["),
+            },
+            '\\' if i + 1 < chars.len() => {
+                regex.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 2;
+                continue;
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    // A match on a folder covers everything inside it
+    regex.push_str("(?<inside>/.*)?$");
+
+    let regex = Regex::new(&regex).map_err(|e| format!("Invalid pattern \"{}\": {}", line, e))?;
+    Ok(Some(Pattern { regex, negated, dir_only }))
+}
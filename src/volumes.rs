@@ -0,0 +1,270 @@
+use crate::config::{Config, CONFIG};
+use crate::handlers::utils::{is_hidden_file, validate_file_name};
+use crate::visibility::{Access, RuleSet, DEFAULT_EXCLUDE, HIDDEN_PATTERN};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const READ_ONLY_MESSAGE: &str = "This volume is read-only";
+// A new name the user could not see afterwards, such as a dotfile
+pub const HIDDEN_NAME_MESSAGE: &str = "Name is reserved for system or hidden files";
+
+// `[roles.<role>]` in config.toml, at the top level or inside a volume
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RoleConfig {
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+// One `[[volumes]]` table in config.toml
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VolumeConfig {
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub roles: HashMap<String, RoleConfig>,
+}
+
+// A named folder exposed at the top of /browse, such as "Projects" on
+// /srv/projects, with the rules that hide entries inside it
+#[derive(Debug, Clone)]
+pub struct Volume {
+    pub name: String,
+    pub path: PathBuf,
+    pub read_only: bool,
+    rules: RuleSet,
+    role_rules: HashMap<String, RuleSet>,
+}
+
+// The roots the server exposes and what is hidden in them. Without volumes
+// the home directory is served directly at the top level, as it always was;
+// with them, the first component of every path names the volume it is in.
+//
+// Whether an entry is hidden is decided by gitignore-style rules applied in
+// order, the last match winning: the built-in system list, dotfiles (unless
+// an admin asked to see hidden files), the rules for every volume, those
+// for the viewer's role, the volume's own rules and the volume's rules for
+// that role. The folders the server keeps its own data in are hidden
+// whatever the rules say.
+pub struct Volumes {
+    volumes: Vec<Volume>,
+    named: bool,
+    data_dirs: Vec<PathBuf>,
+    defaults: RuleSet,
+    hidden: RuleSet,
+    rules: RuleSet,
+    role_rules: HashMap<String, RuleSet>,
+}
+
+impl Volumes {
+    // Builds the volumes and rules in `config`. Without any volumes listed,
+    // `root` is served with the configured rules.
+    pub fn new(config: &Config) -> Result<Self, String> {
+        let named = !config.volumes.is_empty();
+        let root = [VolumeConfig { name: String::new(), path: config.root.clone(), read_only: false, exclude: Vec::new(), roles: HashMap::new() }];
+        let configs = if named { config.volumes.as_slice() } else { &root };
+
+        let mut names = HashSet::new();
+        let mut volumes = Vec::new();
+        for volume in configs {
+            if named {
+                validate_file_name(&volume.name).map_err(|e| format!("Volume \"{}\": {}", volume.name, e))?;
+                if !names.insert(volume.name.to_lowercase()) {
+                    return Err(format!("Volume \"{}\" is configured twice", volume.name));
+                }
+                if !volume.path.is_dir() {
+                    return Err(format!("Volume \"{}\": {} is not a folder", volume.name, volume.path.display()));
+                }
+            }
+            let context = |e: String| if named { format!("Volume \"{}\": {}", volume.name, e) } else { e };
+            volumes.push(Volume {
+                rules: RuleSet::parse(&volume.exclude).map_err(context)?,
+                role_rules: parse_roles(&volume.roles).map_err(context)?,
+                name: volume.name.clone(),
+                path: volume.path.clone(),
+                read_only: volume.read_only,
+            });
+        }
+
+        // A data folder holding a volume would hide all of it
+        let data_dirs = data_dirs(config);
+        for volume in &volumes {
+            if let Some(dir) = data_dirs.iter().find(|dir| volume.path.starts_with(dir)) {
+                return Err(format!("{} is inside the server's data folder {}", volume.path.display(), dir.display()));
+            }
+        }
+
+        Ok(Volumes {
+            volumes,
+            named,
+            data_dirs,
+            defaults: RuleSet::parse(DEFAULT_EXCLUDE)?,
+            hidden: RuleSet::parse(&[HIDDEN_PATTERN])?,
+            rules: RuleSet::parse(&config.exclude)?,
+            role_rules: parse_roles(&config.roles)?,
+        })
+    }
+
+    // Main has already checked the configuration, so this only fails when
+    // a folder disappeared in between. Serving the wrong tree would be worse
+    // than not starting.
+    pub fn load() -> Self {
+        match Self::new(&CONFIG) {
+            Ok(volumes) => volumes,
+            Err(e) => {
+                eprintln!("❌ Invalid volume configuration: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Whether the top level lists volumes rather than a folder's contents
+    pub fn is_named(&self) -> bool {
+        self.named
+    }
+
+    pub fn list(&self) -> &[Volume] {
+        &self.volumes
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Volume> {
+        self.volumes.iter().find(|volume| volume.name == name)
+    }
+
+    // Maps a decoded path such as "Projects/site/index.html" onto the
+    // filesystem. Refuses `..` and anything hidden from `access`, including
+    // paths that lead through a hidden folder.
+    pub fn resolve(&self, relative_path: &str, access: &Access) -> Option<PathBuf> {
+        let mut components = Vec::new();
+        for component in relative_path.split(['/', '\\']) {
+            if component.is_empty() || component == "." {
+                continue;
+            }
+            if component == ".." {
+                return None;
+            }
+            components.push(component);
+        }
+
+        let (volume, rest) = if self.named {
+            let (name, rest) = components.split_first()?;
+            (self.get(name)?, rest)
+        } else {
+            (&self.volumes[0], components.as_slice())
+        };
+
+        let mut full_path = volume.path.clone();
+        for (i, component) in rest.iter().enumerate() {
+            full_path.push(component);
+            let is_dir = i + 1 < rest.len() || fs::metadata(&full_path).is_ok_and(|m| m.is_dir());
+            if self.is_excluded(volume, &rest[..=i].join("/"), is_dir, &full_path, access) {
+                return None;
+            }
+        }
+        Some(full_path)
+    }
+
+    // Whether `access` may see the entry at `path`. Only the entry itself is
+    // checked, so callers walking a tree should not descend into hidden
+    // folders. Paths outside every volume are never visible.
+    pub fn is_visible(&self, path: &Path, is_dir: bool, access: &Access) -> bool {
+        let (volume, rest) = match self.volume_of(path) {
+            Some(found) => found,
+            None => return false,
+        };
+        let relative: Vec<String> = rest.components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        relative.is_empty() || !self.is_excluded(volume, &relative.join("/"), is_dir, path, access)
+    }
+
+    fn is_excluded(&self, volume: &Volume, relative: &str, is_dir: bool, path: &Path, access: &Access) -> bool {
+        if self.data_dirs.iter().any(|dir| path.starts_with(dir)) {
+            return true;
+        }
+        let mut excluded = self.defaults.matched(relative, is_dir).unwrap_or(false);
+        if !access.show_hidden && (self.hidden.matched(relative, is_dir).is_some() || is_hidden_file(path)) {
+            excluded = true;
+        }
+        let layers = [
+            Some(&self.rules),
+            self.role_rules.get(&access.role),
+            Some(&volume.rules),
+            volume.role_rules.get(&access.role),
+        ];
+        for rules in layers.into_iter().flatten() {
+            if let Some(matched) = rules.matched(relative, is_dir) {
+                excluded = matched;
+            }
+        }
+        excluded
+    }
+
+    // True for "" and, with named volumes, for a bare volume name: places
+    // that cannot be renamed, moved or deleted
+    pub fn is_root(&self, relative_path: &str) -> bool {
+        let depth = relative_path.split(['/', '\\']).filter(|c| !c.is_empty() && *c != ".").count();
+        depth == 0 || (self.named && depth == 1)
+    }
+
+    // The volume a filesystem path lies in, with the path below its root
+    pub fn volume_of(&self, path: &Path) -> Option<(&Volume, PathBuf)> {
+        self.volumes.iter()
+            .filter_map(|volume| path.strip_prefix(&volume.path).ok().map(|rest| (volume, rest.to_path_buf())))
+            .max_by_key(|(volume, _)| volume.path.components().count())
+    }
+
+    // The path clients use for a filesystem path, like "Projects/site"
+    pub fn relative_path(&self, path: &Path) -> Option<String> {
+        let (volume, rest) = self.volume_of(path)?;
+        let mut parts = Vec::new();
+        if self.named {
+            parts.push(volume.name.clone());
+        }
+        parts.extend(rest.components().map(|c| c.as_os_str().to_string_lossy().into_owned()));
+        Some(parts.join("/"))
+    }
+
+    pub fn is_read_only(&self, path: &Path) -> bool {
+        self.volume_of(path).is_none_or(|(volume, _)| volume.read_only)
+    }
+}
+
+// The server's own storage, such as the trash, old versions and the content
+// index, which must not be browsable when it lies inside a volume. Each
+// folder is listed as configured and with symlinks resolved, so paths
+// spelled either way match.
+fn data_dirs(config: &Config) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for (_, dir) in config.data_dirs() {
+        let Ok(absolute) = std::path::absolute(dir) else { continue };
+        let canonical = match (absolute.parent(), absolute.file_name()) {
+            (Some(parent), Some(name)) => fs::canonicalize(parent).ok().map(|parent| parent.join(name)),
+            _ => None,
+        };
+        dirs.extend(canonical.filter(|canonical| *canonical != absolute));
+        dirs.push(absolute);
+    }
+    dirs
+}
+
+fn parse_roles(roles: &HashMap<String, RoleConfig>) -> Result<HashMap<String, RuleSet>, String> {
+    roles.iter()
+        .map(|(role, config)| {
+            RuleSet::parse(&config.exclude)
+                .map(|rules| (role.clone(), rules))
+                .map_err(|e| format!("Role \"{}\": {}", role, e))
+        })
+        .collect()
+}
+
+lazy_static::lazy_static! {
+    pub static ref VOLUMES: Volumes = Volumes::load();
+}
@@ -0,0 +1,160 @@
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+// Events buffered per folder before slow viewers are told to reload
+const EVENT_BUFFER: usize = 256;
+
+// A change to one entry of a watched folder. Renames arrive as a `Removed`
+// and an `Added` sharing a tracker, which viewers can pair up. Events are
+// not filtered here, since what is hidden depends on who is watching.
+#[derive(Debug, Clone)]
+pub enum DirEvent {
+    Added { name: String, tracker: Option<usize> },
+    Removed { name: String, tracker: Option<usize> },
+    // Contents written and the file closed
+    Changed { name: String },
+}
+
+struct WatchState {
+    watcher: Option<RecommendedWatcher>,
+    watched: HashSet<PathBuf>,
+}
+
+// Watches folders (non-recursively) only while someone is viewing them
+pub struct DirectoryWatcher {
+    subscribers: Mutex<HashMap<PathBuf, (usize, broadcast::Sender<DirEvent>)>>,
+    // Separate from `subscribers`: the notify backend waits on its event
+    // thread in watch(), and that thread needs `subscribers` to dispatch
+    state: Mutex<WatchState>,
+}
+
+impl Default for DirectoryWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirectoryWatcher {
+    pub fn new() -> Self {
+        DirectoryWatcher {
+            subscribers: Mutex::new(HashMap::new()),
+            state: Mutex::new(WatchState { watcher: None, watched: HashSet::new() }),
+        }
+    }
+
+    pub fn subscribe(&'static self, dir: &Path) -> Subscription {
+        let receiver = {
+            let mut subscribers = self.subscribers.lock().unwrap();
+            let (count, sender) = subscribers.entry(dir.to_path_buf())
+                .or_insert_with(|| (0, broadcast::channel(EVENT_BUFFER).0));
+            *count += 1;
+            sender.subscribe()
+        };
+        self.sync_watch(dir);
+        Subscription { watcher: self, dir: dir.to_path_buf(), receiver }
+    }
+
+    fn unsubscribe(&'static self, dir: &Path) {
+        {
+            let mut subscribers = self.subscribers.lock().unwrap();
+            if let Some((count, _)) = subscribers.get_mut(dir) {
+                *count -= 1;
+                if *count == 0 {
+                    subscribers.remove(dir);
+                }
+            }
+        }
+        self.sync_watch(dir);
+    }
+
+    // Starts or stops watching `dir` to match whether it has subscribers
+    fn sync_watch(&'static self, dir: &Path) {
+        let mut state = self.state.lock().unwrap();
+        let wanted = self.subscribers.lock().unwrap().contains_key(dir);
+        let watching = state.watched.contains(dir);
+
+        if state.watcher.is_none() {
+            match notify::recommended_watcher(move |result: notify::Result<Event>| {
+                if let Ok(event) = result {
+                    self.dispatch(event);
+                }
+            }) {
+                Ok(watcher) => state.watcher = Some(watcher),
+                Err(e) => {
+                    eprintln!("❌ Could not start the directory watcher: {}", e);
+                    return;
+                }
+            }
+        }
+        let watcher = state.watcher.as_mut().unwrap();
+
+        if wanted && !watching {
+            match watcher.watch(dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    state.watched.insert(dir.to_path_buf());
+                }
+                Err(e) => eprintln!("❌ Could not watch {}: {}", dir.display(), e),
+            }
+        } else if !wanted && watching {
+            let _ = watcher.unwatch(dir);
+            state.watched.remove(dir);
+        }
+    }
+
+    fn dispatch(&self, event: Event) {
+        let tracker = event.tracker();
+        let make: fn(String, Option<usize>) -> DirEvent = match event.kind {
+            EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                |name, tracker| DirEvent::Added { name, tracker }
+            }
+            EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                |name, tracker| DirEvent::Removed { name, tracker }
+            }
+            EventKind::Access(AccessKind::Close(AccessMode::Write)) => |name, _| DirEvent::Changed { name },
+            _ => return,
+        };
+
+        let subscribers = match self.subscribers.lock() {
+            Ok(subscribers) => subscribers,
+            Err(_) => return,
+        };
+        for path in &event.paths {
+            let (parent, name) = match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name)) => (parent, name.to_string_lossy().into_owned()),
+                _ => continue,
+            };
+            if let Some((_, sender)) = subscribers.get(parent) {
+                // No receivers left just means the last viewer is leaving
+                let _ = sender.send(make(name, tracker));
+            }
+        }
+    }
+}
+
+// Receives the events of one folder; dropping it stops the watch once no
+// one else is viewing the folder
+pub struct Subscription {
+    watcher: &'static DirectoryWatcher,
+    dir: PathBuf,
+    receiver: broadcast::Receiver<DirEvent>,
+}
+
+impl Subscription {
+    pub async fn recv(&mut self) -> Result<DirEvent, broadcast::error::RecvError> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.watcher.unsubscribe(&self.dir);
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref DIRECTORY_WATCHER: DirectoryWatcher = DirectoryWatcher::new();
+}